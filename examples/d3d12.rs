@@ -1,9 +1,12 @@
+#[cfg(windows)]
 use windows::Win32::{
     Foundation::HWND, Graphics::Direct3D::*, Graphics::Direct3D12::*, Graphics::Dxgi::Common::*,
     Graphics::Dxgi::*,
 };
+#[cfg(windows)]
 use windows::core::Interface;
 
+#[cfg(windows)]
 fn main() -> anyhow::Result<()> {
     pnte::co_initialize(pnte::CoInit::ApartmentThreaded)?;
     unsafe {
@@ -178,3 +181,6 @@ fn main() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {}
@@ -1,5 +1,7 @@
+#[cfg(windows)]
 use wiard::ToLogical;
 
+#[cfg(windows)]
 fn main() -> anyhow::Result<()> {
     pnte::co_initialize(pnte::CoInit::ApartmentThreaded)?;
     let mut event_rx = wiard::EventReceiver::new();
@@ -218,3 +220,6 @@ fn main() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {}
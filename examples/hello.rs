@@ -1,3 +1,4 @@
+#[cfg(windows)]
 fn main() -> anyhow::Result<()> {
     pnte::co_initialize(pnte::CoInit::ApartmentThreaded)?;
     let mut event_rx = wiard::EventReceiver::new();
@@ -35,3 +36,6 @@ fn main() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {}
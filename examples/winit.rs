@@ -1,14 +1,17 @@
+#[cfg(windows)]
 use winit::{
     application::ApplicationHandler, dpi::LogicalSize, event::WindowEvent, event_loop::EventLoop,
     window::Window,
 };
 
+#[cfg(windows)]
 struct App {
     window: Option<Window>,
     ctx: Option<pnte::Context<pnte::Direct2D>>,
     render_target: Option<pnte::d2d1::RenderTarget>,
 }
 
+#[cfg(windows)]
 impl App {
     fn new() -> anyhow::Result<Self> {
         Ok(Self {
@@ -19,6 +22,7 @@ impl App {
     }
 }
 
+#[cfg(windows)]
 impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
         if self.window.is_none() {
@@ -64,9 +68,13 @@ impl ApplicationHandler for App {
     }
 }
 
+#[cfg(windows)]
 fn main() -> anyhow::Result<()> {
     let event_loop = EventLoop::new()?;
     let mut app = App::new()?;
     event_loop.run_app(&mut app)?;
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {}
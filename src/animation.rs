@@ -0,0 +1,474 @@
+use crate::codec::{decode_apng, decode_gif};
+use crate::*;
+#[cfg(windows)]
+use std::path::Path;
+use std::time::Duration;
+
+// What happens to a frame's rectangle before the next frame is drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Disposal {
+    None,
+    // Clears the rectangle to transparent.
+    Background,
+    // Restores the rectangle to what it was before the frame was drawn.
+    Previous,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Blend {
+    // Replaces the pixels under the frame, alpha included.
+    Source,
+    Over,
+}
+
+// How many times the whole animation plays.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LoopCount {
+    Infinite,
+    Finite(u32),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FrameInfo {
+    // Where the frame is drawn on the canvas.
+    pub rect: Rect<u32>,
+    pub delay: Duration,
+    pub disposal: Disposal,
+    pub blend: Blend,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AnimationFrame {
+    pub info: FrameInfo,
+    // Straight alpha, covering `info.rect` only.
+    pub pixels: Vec<Rgba8>,
+}
+
+// Frames as they are stored in a file, each covering part of the canvas.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Animation {
+    size: Size<u32>,
+    frames: Vec<AnimationFrame>,
+    loop_count: LoopCount,
+}
+
+fn blend_over(src: Rgba8, dest: Rgba8) -> Rgba8 {
+    match (src.a, dest.a) {
+        (255, _) | (_, 0) => return src,
+        (0, _) => return dest,
+        _ => {}
+    }
+    let sa = src.a as f32 / 255.0;
+    let da = dest.a as f32 / 255.0 * (1.0 - sa);
+    let a = sa + da;
+    let c = |s: u8, d: u8| ((s as f32 * sa + d as f32 * da) / a).round() as u8;
+    Rgba8::new(
+        c(src.r, dest.r),
+        c(src.g, dest.g),
+        c(src.b, dest.b),
+        (a * 255.0).round() as u8,
+    )
+}
+
+impl Animation {
+    pub fn new(
+        size: impl Into<Size<u32>>,
+        frames: Vec<AnimationFrame>,
+        loop_count: LoopCount,
+    ) -> Result<Self> {
+        let size: Size<u32> = size.into();
+        if frames.is_empty() {
+            return Err(Error::NoFrames);
+        }
+        for frame in &frames {
+            let rect = frame.info.rect;
+            let frame_size = region_size(rect, size)?;
+            let expected = frame_size.width as usize * frame_size.height as usize;
+            if frame.pixels.len() != expected {
+                return Err(Error::PixelCount {
+                    expected,
+                    actual: frame.pixels.len(),
+                });
+            }
+        }
+        Ok(Self {
+            size,
+            frames,
+            loop_count,
+        })
+    }
+
+    // Sniffs GIF or PNG. A PNG without animation control becomes a single frame.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            decode_gif(data)
+        } else if ImageFormat::detect(data) == Some(ImageFormat::Png) {
+            decode_apng(data)
+        } else {
+            Err(Error::UnsupportedImage)
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+
+    #[inline]
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    #[inline]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[inline]
+    pub fn loop_count(&self) -> LoopCount {
+        self.loop_count
+    }
+
+    #[inline]
+    pub fn player(&self) -> AnimationPlayer {
+        AnimationPlayer::new(
+            self.frames.iter().map(|frame| frame.info.delay).collect(),
+            self.loop_count,
+        )
+    }
+
+    // Draws every frame over what the previous ones left behind, giving one full canvas per
+    // frame in straight alpha.
+    pub fn composite(&self) -> Vec<PixelBuffer> {
+        let width = self.size.width as usize;
+        let mut canvas = vec![Rgba8::default(); width * self.size.height as usize];
+        let mut out = Vec::with_capacity(self.frames.len());
+        for frame in &self.frames {
+            let rect = frame.info.rect;
+            let saved = (frame.info.disposal == Disposal::Previous).then(|| canvas.clone());
+            let frame_width = (rect.right - rect.left) as usize;
+            for (y, row) in (rect.top as usize..rect.bottom as usize)
+                .zip(frame.pixels.chunks(frame_width.max(1)))
+            {
+                let start = y * width + rect.left as usize;
+                for (dest, &src) in canvas[start..start + frame_width].iter_mut().zip(row) {
+                    *dest = match frame.info.blend {
+                        Blend::Source => src,
+                        Blend::Over => blend_over(src, *dest),
+                    };
+                }
+            }
+            out.push(PixelBuffer::from_rgba8(self.size, &canvas).unwrap());
+            match frame.info.disposal {
+                Disposal::None => {}
+                Disposal::Background => {
+                    for y in rect.top as usize..rect.bottom as usize {
+                        let start = y * width + rect.left as usize;
+                        canvas[start..start + frame_width].fill(Rgba8::default());
+                    }
+                }
+                Disposal::Previous => canvas = saved.unwrap(),
+            }
+        }
+        out
+    }
+}
+
+// Picks the frame to show after some time has passed since the animation started.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AnimationPlayer {
+    delays: Vec<Duration>,
+    loop_count: LoopCount,
+}
+
+impl AnimationPlayer {
+    // Delays of 10 ms or less play as 100 ms, as in browsers, since many GIFs rely on it.
+    pub fn new(delays: Vec<Duration>, loop_count: LoopCount) -> Self {
+        let delays = delays
+            .into_iter()
+            .map(|delay| {
+                if delay <= Duration::from_millis(10) {
+                    Duration::from_millis(100)
+                } else {
+                    delay
+                }
+            })
+            .collect();
+        Self { delays, loop_count }
+    }
+
+    // The length of one loop, saturating at `Duration::MAX`.
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.delays
+            .iter()
+            .fold(Duration::ZERO, |sum, &delay| sum.saturating_add(delay))
+    }
+
+    #[inline]
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        match self.loop_count {
+            LoopCount::Infinite => false,
+            LoopCount::Finite(count) => {
+                elapsed.as_nanos() >= self.duration().as_nanos() * count as u128
+            }
+        }
+    }
+
+    // Stays on the last frame once every loop has played.
+    pub fn frame_at(&self, elapsed: Duration) -> usize {
+        if self.delays.is_empty() {
+            return 0;
+        }
+        if self.is_finished(elapsed) {
+            return self.delays.len() - 1;
+        }
+        let mut time = elapsed.as_nanos() % self.duration().as_nanos();
+        for (i, delay) in self.delays.iter().enumerate() {
+            if time < delay.as_nanos() {
+                return i;
+            }
+            time -= delay.as_nanos();
+        }
+        self.delays.len() - 1
+    }
+}
+
+// Composited frames uploaded as images, ready to draw.
+#[cfg(windows)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AnimatedImage {
+    size: Size<u32>,
+    frames: Vec<Image>,
+    infos: Vec<FrameInfo>,
+    loop_count: LoopCount,
+    player: AnimationPlayer,
+}
+
+#[cfg(windows)]
+impl AnimatedImage {
+    pub fn from_animation<T>(ctx: &Context<T>, animation: &Animation) -> Result<Self>
+    where
+        T: Backend,
+    {
+        let frames = animation
+            .composite()
+            .iter()
+            .map(|canvas| Image::from_pixel_buffer(ctx, canvas))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            size: animation.size(),
+            frames,
+            infos: animation.frames().iter().map(|frame| frame.info).collect(),
+            loop_count: animation.loop_count(),
+            player: animation.player(),
+        })
+    }
+
+    // GIF and PNG are decoded by `Animation::decode`. Other formats go through WIC, and their
+    // frames, such as the pages of a TIFF, are shown one after another without compositing.
+    pub fn from_bytes<T>(ctx: &Context<T>, data: &[u8]) -> Result<Self>
+    where
+        T: Backend,
+    {
+        match Animation::decode(data) {
+            Err(Error::UnsupportedImage) => {}
+            animation => return Self::from_animation(ctx, &animation?),
+        }
+        let decoder = wic_decoder(ctx, data)?;
+        let count = unsafe { decoder.GetFrameCount()? };
+        if count == 0 {
+            return Err(Error::NoFrames);
+        }
+        let frames = (0..count)
+            .map(|i| {
+                let frame = unsafe { decoder.GetFrame(i)? };
+                Image::from_frame(ctx, &frame)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let infos = frames
+            .iter()
+            .map(|frame| {
+                let size = frame.pixel_size();
+                FrameInfo {
+                    rect: Rect::new(0, 0, size.width, size.height),
+                    delay: Duration::ZERO,
+                    disposal: Disposal::None,
+                    blend: Blend::Source,
+                }
+            })
+            .collect::<Vec<_>>();
+        let loop_count = LoopCount::Finite(1);
+        Ok(Self {
+            size: Size::new(infos[0].rect.right, infos[0].rect.bottom),
+            player: AnimationPlayer::new(infos.iter().map(|info| info.delay).collect(), loop_count),
+            frames,
+            infos,
+            loop_count,
+        })
+    }
+
+    #[inline]
+    pub fn from_file<T>(ctx: &Context<T>, path: impl AsRef<Path>) -> Result<Self>
+    where
+        T: Backend,
+    {
+        Self::from_bytes(ctx, &std::fs::read(path)?)
+    }
+
+    #[inline]
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+
+    // Each frame is a full canvas with the earlier frames already composited in.
+    #[inline]
+    pub fn frames(&self) -> &[Image] {
+        &self.frames
+    }
+
+    #[inline]
+    pub fn frame_infos(&self) -> &[FrameInfo] {
+        &self.infos
+    }
+
+    #[inline]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[inline]
+    pub fn loop_count(&self) -> LoopCount {
+        self.loop_count
+    }
+
+    #[inline]
+    pub fn player(&self) -> &AnimationPlayer {
+        &self.player
+    }
+
+    #[inline]
+    pub fn frame_at(&self, elapsed: Duration) -> &Image {
+        &self.frames[self.player.frame_at(elapsed)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(
+        rect: Rect<u32>,
+        disposal: Disposal,
+        blend: Blend,
+        pixels: &[Rgba8],
+    ) -> AnimationFrame {
+        AnimationFrame {
+            info: FrameInfo {
+                rect,
+                delay: Duration::from_millis(50),
+                disposal,
+                blend,
+            },
+            pixels: pixels.to_vec(),
+        }
+    }
+
+    #[test]
+    fn rejects_frames_outside_the_canvas() {
+        let pixel = [Rgba8::new(0, 0, 0, 255)];
+        let outside = frame(Rect::new(2, 0, 3, 1), Disposal::None, Blend::Over, &pixel);
+        assert!(Animation::new((2, 2), vec![outside], LoopCount::Infinite).is_err());
+        let short = frame(Rect::new(0, 0, 2, 1), Disposal::None, Blend::Over, &pixel);
+        assert!(matches!(
+            Animation::new((2, 2), vec![short], LoopCount::Infinite),
+            Err(Error::PixelCount {
+                expected: 2,
+                actual: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_empty_animations() {
+        assert!(matches!(
+            Animation::new((2, 2), Vec::new(), LoopCount::Infinite),
+            Err(Error::NoFrames)
+        ));
+    }
+
+    #[test]
+    fn composites_with_disposal_and_blending() {
+        let clear = Rgba8::default();
+        let red = Rgba8::new(255, 0, 0, 255);
+        let blue = Rgba8::new(0, 0, 255, 255);
+        let half_blue = Rgba8::new(0, 0, 255, 128);
+        let animation = Animation::new(
+            (2, 1),
+            vec![
+                frame(
+                    Rect::new(0, 0, 2, 1),
+                    Disposal::None,
+                    Blend::Source,
+                    &[red, red],
+                ),
+                frame(
+                    Rect::new(1, 0, 2, 1),
+                    Disposal::Previous,
+                    Blend::Over,
+                    &[half_blue],
+                ),
+                frame(
+                    Rect::new(0, 0, 1, 1),
+                    Disposal::Background,
+                    Blend::Source,
+                    &[half_blue],
+                ),
+                frame(Rect::new(1, 0, 2, 1), Disposal::None, Blend::Over, &[blue]),
+            ],
+            LoopCount::Infinite,
+        )
+        .unwrap();
+        let canvases = animation
+            .composite()
+            .iter()
+            .map(|canvas| canvas.to_rgba8())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            canvases,
+            [
+                [red, red],
+                [red, Rgba8::new(127, 0, 128, 255)],
+                [half_blue, red],
+                [clear, blue],
+            ]
+        );
+    }
+
+    #[test]
+    fn player_loops_and_stops_on_the_last_frame() {
+        let ms = Duration::from_millis;
+        // The zero delay plays as 100 ms.
+        let player = AnimationPlayer::new(vec![ms(100), ms(50), ms(0)], LoopCount::Finite(2));
+        assert_eq!(player.duration(), ms(250));
+        let frames = [0, 99, 100, 149, 150, 249, 250, 400, 499, 500, 10_000]
+            .map(|elapsed| player.frame_at(ms(elapsed)));
+        assert_eq!(frames, [0, 0, 1, 1, 2, 2, 0, 2, 2, 2, 2]);
+        assert!(!player.is_finished(ms(499)));
+        assert!(player.is_finished(ms(500)));
+        let player = AnimationPlayer::new(vec![ms(20), ms(30)], LoopCount::Infinite);
+        assert_eq!(player.frame_at(ms(1_000_025)), 1);
+        assert!(!player.is_finished(Duration::MAX));
+        assert_eq!(
+            AnimationPlayer::new(Vec::new(), LoopCount::Infinite).frame_at(ms(5)),
+            0
+        );
+        let player = AnimationPlayer::new(vec![Duration::MAX, ms(50)], LoopCount::Finite(2));
+        assert_eq!(player.duration(), Duration::MAX);
+        assert_eq!(player.frame_at(ms(5)), 0);
+        assert!(!player.is_finished(Duration::MAX));
+    }
+}
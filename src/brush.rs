@@ -1,9 +1,57 @@
 use crate::*;
-use windows::Win32::Graphics::{Direct2D::Common::*, Direct2D::*};
+use windows::Win32::Graphics::{Direct2D::Common::*, Direct2D::*, Dxgi::Common::*};
 use windows::core::Interface;
 
+// A brush wraps a Direct2D object that its clones share, so changing the opacity or the
+// transform of one clone changes all of them. Create a new brush for independent state.
 pub trait Brush {
     fn handle(&self) -> &ID2D1Brush;
+
+    #[inline]
+    fn opacity(&self) -> f32 {
+        unsafe { self.handle().GetOpacity() }
+    }
+
+    #[inline]
+    fn set_opacity(&mut self, opacity: f32) {
+        unsafe {
+            self.handle().SetOpacity(opacity);
+        }
+    }
+
+    #[inline]
+    fn transform(&self) -> Matrix3x2 {
+        let mut transform = Default::default();
+        unsafe {
+            self.handle().GetTransform(&mut transform);
+        }
+        transform.into()
+    }
+
+    #[inline]
+    fn set_transform(&mut self, transform: Matrix3x2) {
+        unsafe {
+            self.handle().SetTransform(&transform.into());
+        }
+    }
+
+    #[inline]
+    fn with_opacity(mut self, opacity: f32) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_opacity(opacity);
+        self
+    }
+
+    #[inline]
+    fn with_transform(mut self, transform: Matrix3x2) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_transform(transform);
+        self
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -28,58 +76,97 @@ impl Brush for SolidColorBrush {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-#[repr(C)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct GradientStop {
-    pub position: f32,
-    pub color: Rgba,
+impl From<GradientStop> for D2D1_GRADIENT_STOP {
+    #[inline]
+    fn from(value: GradientStop) -> Self {
+        Self {
+            position: value.position,
+            color: value.color.into(),
+        }
+    }
 }
 
-impl GradientStop {
+impl From<GradientMode> for D2D1_EXTEND_MODE {
     #[inline]
-    pub fn new(position: f32, color: impl Into<Rgba>) -> Self {
-        Self {
-            position,
-            color: color.into(),
+    fn from(value: GradientMode) -> Self {
+        match value {
+            GradientMode::Clamp => D2D1_EXTEND_MODE_CLAMP,
+            GradientMode::Mirror => D2D1_EXTEND_MODE_MIRROR,
+            GradientMode::Wrap => D2D1_EXTEND_MODE_WRAP,
         }
     }
 }
 
-impl From<GradientStop> for D2D1_GRADIENT_STOP {
+impl From<BufferPrecision> for D2D1_BUFFER_PRECISION {
     #[inline]
-    fn from(value: GradientStop) -> Self {
-        Self {
-            position: value.position,
-            color: value.color.into(),
+    fn from(value: BufferPrecision) -> Self {
+        match value {
+            BufferPrecision::Unorm8 => D2D1_BUFFER_PRECISION_8BPC_UNORM,
+            BufferPrecision::Float16 => D2D1_BUFFER_PRECISION_16BPC_FLOAT,
+            BufferPrecision::Float32 => D2D1_BUFFER_PRECISION_32BPC_FLOAT,
         }
     }
 }
 
-impl<C> From<(f32, C)> for GradientStop
-where
-    C: Into<Rgba>,
-{
+impl From<AlphaInterpolation> for D2D1_COLOR_INTERPOLATION_MODE {
     #[inline]
-    fn from(value: (f32, C)) -> Self {
-        Self {
-            position: value.0,
-            color: value.1.into(),
+    fn from(value: AlphaInterpolation) -> Self {
+        match value {
+            AlphaInterpolation::Straight => D2D1_COLOR_INTERPOLATION_MODE_STRAIGHT,
+            AlphaInterpolation::Premultiplied => D2D1_COLOR_INTERPOLATION_MODE_PREMULTIPLIED,
         }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-#[repr(i32)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub enum GradientMode {
-    Clamp = D2D1_EXTEND_MODE_CLAMP.0,
-    Mirror = D2D1_EXTEND_MODE_MIRROR.0,
-    Wrap = D2D1_EXTEND_MODE_WRAP.0,
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GradientStopCollection(ID2D1GradientStopCollection1);
+
+impl GradientStopCollection {
+    pub fn new<T, G>(
+        ctx: &Context<T>,
+        mode: GradientMode,
+        stops: &[G],
+        options: &GradientOptions,
+    ) -> Result<Self>
+    where
+        T: Backend,
+        G: Into<GradientStop> + Clone,
+    {
+        let linear = options.color_space == GradientColorSpace::Linear;
+        let stops: Vec<D2D1_GRADIENT_STOP> = GradientStops::new(stops)
+            .expand(options)
+            .into_iter()
+            .map(|stop| {
+                let color = if linear {
+                    let c = LinearRgba::from(stop.color);
+                    Rgba::new(c.r, c.g, c.b, c.a)
+                } else {
+                    stop.color
+                };
+                D2D1_GRADIENT_STOP::from(GradientStop::new(stop.position, color))
+            })
+            .collect();
+        let pre_interpolation_space = if linear {
+            D2D1_COLOR_SPACE_SCRGB
+        } else {
+            D2D1_COLOR_SPACE_SRGB
+        };
+        let handle = unsafe {
+            ctx.d2d1_device_context.CreateGradientStopCollection(
+                &stops,
+                pre_interpolation_space,
+                D2D1_COLOR_SPACE_SRGB,
+                options.precision.into(),
+                mode.into(),
+                options.alpha.into(),
+            )?
+        };
+        Ok(Self(handle))
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
-pub struct LinearGradientBrush(ID2D1Brush);
+pub struct LinearGradientBrush(ID2D1LinearGradientBrush);
 
 impl LinearGradientBrush {
     #[inline]
@@ -93,23 +180,22 @@ impl LinearGradientBrush {
     where
         T: Backend,
         G: Into<GradientStop> + Clone,
+    {
+        let stops = GradientStopCollection::new(ctx, mode, stops, &GradientOptions::default())?;
+        Self::from_stop_collection(ctx, start, end, &stops)
+    }
+
+    #[inline]
+    pub fn from_stop_collection<T>(
+        ctx: &Context<T>,
+        start: impl Into<Point<f32>>,
+        end: impl Into<Point<f32>>,
+        stops: &GradientStopCollection,
+    ) -> Result<Self>
+    where
+        T: Backend,
     {
         let dc = &ctx.d2d1_device_context;
-        let stops: Vec<D2D1_GRADIENT_STOP> = stops
-            .iter()
-            .cloned()
-            .map(|stop| D2D1_GRADIENT_STOP::from(stop.into()))
-            .collect();
-        let stops = unsafe {
-            dc.CreateGradientStopCollection(
-                &stops,
-                D2D1_COLOR_SPACE_SRGB,
-                D2D1_COLOR_SPACE_SRGB,
-                D2D1_BUFFER_PRECISION_8BPC_UNORM,
-                D2D1_EXTEND_MODE(mode as i32),
-                D2D1_COLOR_INTERPOLATION_MODE_PREMULTIPLIED,
-            )?
-        };
         let brush = unsafe {
             dc.CreateLinearGradientBrush(
                 &D2D1_LINEAR_GRADIENT_BRUSH_PROPERTIES {
@@ -117,10 +203,34 @@ impl LinearGradientBrush {
                     endPoint: D2D_POINT_2F::from(end.into()),
                 },
                 None,
-                &stops,
+                &stops.0,
             )?
         };
-        Ok(Self(brush.cast().unwrap()))
+        Ok(Self(brush))
+    }
+
+    #[inline]
+    pub fn start_point(&self) -> Point<f32> {
+        unsafe { self.0.GetStartPoint().into() }
+    }
+
+    #[inline]
+    pub fn set_start_point(&mut self, point: impl Into<Point<f32>>) {
+        unsafe {
+            self.0.SetStartPoint(point.into().into());
+        }
+    }
+
+    #[inline]
+    pub fn end_point(&self) -> Point<f32> {
+        unsafe { self.0.GetEndPoint().into() }
+    }
+
+    #[inline]
+    pub fn set_end_point(&mut self, point: impl Into<Point<f32>>) {
+        unsafe {
+            self.0.SetEndPoint(point.into().into());
+        }
     }
 }
 
@@ -132,7 +242,7 @@ impl Brush for LinearGradientBrush {
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
-pub struct RadialGradientBrush(ID2D1Brush);
+pub struct RadialGradientBrush(ID2D1RadialGradientBrush);
 
 impl RadialGradientBrush {
     #[inline]
@@ -146,23 +256,22 @@ impl RadialGradientBrush {
     where
         T: Backend,
         G: Into<GradientStop> + Clone,
+    {
+        let stops = GradientStopCollection::new(ctx, mode, stops, &GradientOptions::default())?;
+        Self::from_stop_collection(ctx, ellipse, offset, &stops)
+    }
+
+    #[inline]
+    pub fn from_stop_collection<T>(
+        ctx: &Context<T>,
+        ellipse: impl Into<Ellipse>,
+        offset: impl Into<Vector<f32>>,
+        stops: &GradientStopCollection,
+    ) -> Result<Self>
+    where
+        T: Backend,
     {
         let dc = &ctx.d2d1_device_context;
-        let stops: Vec<D2D1_GRADIENT_STOP> = stops
-            .iter()
-            .cloned()
-            .map(|stop| D2D1_GRADIENT_STOP::from(stop.into()))
-            .collect();
-        let stops = unsafe {
-            dc.CreateGradientStopCollection(
-                &stops,
-                D2D1_COLOR_SPACE_SRGB,
-                D2D1_COLOR_SPACE_SRGB,
-                D2D1_BUFFER_PRECISION_8BPC_UNORM,
-                D2D1_EXTEND_MODE(mode as i32),
-                D2D1_COLOR_INTERPOLATION_MODE_PREMULTIPLIED,
-            )?
-        };
         let ellipse: Ellipse = ellipse.into();
         let offset: Vector<f32> = offset.into();
         let brush = unsafe {
@@ -174,10 +283,72 @@ impl RadialGradientBrush {
                     gradientOriginOffset: offset.as_point().into(),
                 },
                 None,
-                &stops,
+                &stops.0,
             )?
         };
-        Ok(Self(brush.cast().unwrap()))
+        Ok(Self(brush))
+    }
+
+    #[inline]
+    pub fn center(&self) -> Point<f32> {
+        unsafe { self.0.GetCenter().into() }
+    }
+
+    #[inline]
+    pub fn set_center(&mut self, center: impl Into<Point<f32>>) {
+        unsafe {
+            self.0.SetCenter(center.into().into());
+        }
+    }
+
+    #[inline]
+    pub fn radius_x(&self) -> f32 {
+        unsafe { self.0.GetRadiusX() }
+    }
+
+    #[inline]
+    pub fn set_radius_x(&mut self, radius: f32) {
+        unsafe {
+            self.0.SetRadiusX(radius);
+        }
+    }
+
+    #[inline]
+    pub fn radius_y(&self) -> f32 {
+        unsafe { self.0.GetRadiusY() }
+    }
+
+    #[inline]
+    pub fn set_radius_y(&mut self, radius: f32) {
+        unsafe {
+            self.0.SetRadiusY(radius);
+        }
+    }
+
+    #[inline]
+    pub fn ellipse(&self) -> Ellipse {
+        Ellipse::new(self.center(), self.radius_x(), self.radius_y())
+    }
+
+    #[inline]
+    pub fn set_ellipse(&mut self, ellipse: impl Into<Ellipse>) {
+        let ellipse: Ellipse = ellipse.into();
+        self.set_center(ellipse.center);
+        self.set_radius_x(ellipse.radius_x);
+        self.set_radius_y(ellipse.radius_y);
+    }
+
+    #[inline]
+    pub fn offset(&self) -> Vector<f32> {
+        unsafe { Point::from(self.0.GetGradientOriginOffset()).as_vector() }
+    }
+
+    #[inline]
+    pub fn set_offset(&mut self, offset: impl Into<Vector<f32>>) {
+        unsafe {
+            self.0
+                .SetGradientOriginOffset(offset.into().as_point().into());
+        }
     }
 }
 
@@ -187,3 +358,336 @@ impl Brush for RadialGradientBrush {
         &self.0
     }
 }
+
+// Direct2D has no sweep gradient, so the gradient is evaluated on the CPU over `bounds`
+// and drawn through a bitmap brush. Outside of `bounds` the edge pixels are repeated.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConicGradientBrush {
+    handle: ID2D1Brush,
+    bitmap_transform: Matrix3x2,
+}
+
+impl ConicGradientBrush {
+    #[inline]
+    pub fn new<T, G>(
+        ctx: &Context<T>,
+        bounds: impl Into<Rect<f32>>,
+        center: impl Into<Point<f32>>,
+        start_angle: f32,
+        end_angle: f32,
+        mode: GradientMode,
+        stops: &[G],
+    ) -> Result<Self>
+    where
+        T: Backend,
+        G: Into<GradientStop> + Clone,
+    {
+        let gradient = Gradient::conic(center, start_angle, end_angle, mode, stops);
+        Self::from_gradient(ctx, bounds, &gradient)
+    }
+
+    pub fn from_gradient<T>(
+        ctx: &Context<T>,
+        bounds: impl Into<Rect<f32>>,
+        gradient: &Gradient,
+    ) -> Result<Self>
+    where
+        T: Backend,
+    {
+        let dc = &ctx.d2d1_device_context;
+        let bounds: Rect<f32> = bounds.into();
+        let size = bounds.size();
+        let (mut dpi_x, mut dpi_y) = (0.0, 0.0);
+        unsafe {
+            dc.GetDpi(&mut dpi_x, &mut dpi_y);
+        }
+        let pixel_size = Size::new(
+            (size.width * dpi_x / 96.0).ceil().max(1.0) as u32,
+            (size.height * dpi_y / 96.0).ceil().max(1.0) as u32,
+        );
+        let pixels: Vec<u8> = gradient
+            .rasterize(bounds, pixel_size)
+            .into_iter()
+            .flat_map(|color| {
+                let c = color.premultiply();
+                Rgba8::from(Rgba::new(c.r, c.g, c.b, c.a)).to_bytes(ChannelOrder::Bgra)
+            })
+            .collect();
+        let bitmap_transform = Matrix3x2::scale(
+            size.width / pixel_size.width as f32,
+            size.height / pixel_size.height as f32,
+        )
+        .then(Matrix3x2::translation(bounds.left, bounds.top));
+        let brush = unsafe {
+            let bitmap = dc.CreateBitmap(
+                pixel_size.into(),
+                Some(pixels.as_ptr() as *const std::ffi::c_void),
+                pixel_size.width * 4,
+                &D2D1_BITMAP_PROPERTIES1 {
+                    pixelFormat: D2D1_PIXEL_FORMAT {
+                        format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                        alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+                    },
+                    dpiX: 96.0,
+                    dpiY: 96.0,
+                    ..Default::default()
+                },
+            )?;
+            dc.CreateBitmapBrush(
+                &bitmap,
+                Some(&D2D1_BITMAP_BRUSH_PROPERTIES1 {
+                    extendModeX: D2D1_EXTEND_MODE_CLAMP,
+                    extendModeY: D2D1_EXTEND_MODE_CLAMP,
+                    interpolationMode: D2D1_INTERPOLATION_MODE_LINEAR,
+                }),
+                Some(&D2D1_BRUSH_PROPERTIES {
+                    opacity: 1.0,
+                    transform: bitmap_transform.into(),
+                }),
+            )?
+        };
+        Ok(Self {
+            handle: brush.cast().unwrap(),
+            bitmap_transform,
+        })
+    }
+}
+
+impl Brush for ConicGradientBrush {
+    #[inline]
+    fn handle(&self) -> &ID2D1Brush {
+        &self.handle
+    }
+
+    #[inline]
+    fn transform(&self) -> Matrix3x2 {
+        bitmap_brush_transform(&self.handle, &self.bitmap_transform)
+    }
+
+    #[inline]
+    fn set_transform(&mut self, transform: Matrix3x2) {
+        set_bitmap_brush_transform(&self.handle, &self.bitmap_transform, transform);
+    }
+}
+
+// Brushes drawing a generated bitmap keep the bitmap's own placement out of the
+// transform seen by users.
+fn bitmap_brush_transform(handle: &ID2D1Brush, bitmap_transform: &Matrix3x2) -> Matrix3x2 {
+    let mut transform = Default::default();
+    unsafe {
+        handle.GetTransform(&mut transform);
+    }
+    let inverse = bitmap_transform.invert().unwrap_or_default();
+    inverse.then(transform.into())
+}
+
+fn set_bitmap_brush_transform(
+    handle: &ID2D1Brush,
+    bitmap_transform: &Matrix3x2,
+    transform: Matrix3x2,
+) {
+    unsafe {
+        handle.SetTransform(&bitmap_transform.then(transform).into());
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PatternBrush {
+    handle: ID2D1Brush,
+    pattern: Pattern,
+    bitmap_transform: Matrix3x2,
+}
+
+impl PatternBrush {
+    pub fn new<T>(ctx: &Context<T>, pattern: &Pattern) -> Result<Self>
+    where
+        T: Backend,
+    {
+        let dc = &ctx.d2d1_device_context;
+        let (mut dpi_x, mut dpi_y) = (0.0, 0.0);
+        unsafe {
+            dc.GetDpi(&mut dpi_x, &mut dpi_y);
+        }
+        let scale = dpi_x.max(dpi_y) / 96.0;
+        let tile_size = pattern.tile_size(scale);
+        let bitmap_transform = pattern.tile_transform(scale);
+        let pixels: Vec<u8> = pattern
+            .render_tile(scale)
+            .into_iter()
+            .flat_map(|color| {
+                let c = color.premultiply();
+                Rgba8::from(Rgba::new(c.r, c.g, c.b, c.a)).to_bytes(ChannelOrder::Bgra)
+            })
+            .collect();
+        let brush = unsafe {
+            let bitmap = dc.CreateBitmap(
+                tile_size.into(),
+                Some(pixels.as_ptr() as *const std::ffi::c_void),
+                tile_size.width * 4,
+                &D2D1_BITMAP_PROPERTIES1 {
+                    pixelFormat: D2D1_PIXEL_FORMAT {
+                        format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                        alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+                    },
+                    dpiX: 96.0,
+                    dpiY: 96.0,
+                    ..Default::default()
+                },
+            )?;
+            dc.CreateBitmapBrush(
+                &bitmap,
+                Some(&D2D1_BITMAP_BRUSH_PROPERTIES1 {
+                    extendModeX: D2D1_EXTEND_MODE_WRAP,
+                    extendModeY: D2D1_EXTEND_MODE_WRAP,
+                    interpolationMode: D2D1_INTERPOLATION_MODE_LINEAR,
+                }),
+                Some(&D2D1_BRUSH_PROPERTIES {
+                    opacity: 1.0,
+                    transform: bitmap_transform.into(),
+                }),
+            )?
+        };
+        Ok(Self {
+            handle: brush.cast().unwrap(),
+            pattern: *pattern,
+            bitmap_transform,
+        })
+    }
+
+    #[inline]
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+}
+
+impl Brush for PatternBrush {
+    #[inline]
+    fn handle(&self) -> &ID2D1Brush {
+        &self.handle
+    }
+
+    #[inline]
+    fn transform(&self) -> Matrix3x2 {
+        bitmap_brush_transform(&self.handle, &self.bitmap_transform)
+    }
+
+    #[inline]
+    fn set_transform(&mut self, transform: Matrix3x2) {
+        set_bitmap_brush_transform(&self.handle, &self.bitmap_transform, transform);
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ImageBrush(ID2D1Brush);
+
+impl ImageBrush {
+    #[inline]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<'a, T: Backend>(ctx: &'a Context<T>, image: &'a Image) -> ImageBrushBuilder<'a, T> {
+        ImageBrushBuilder::new(ctx, image)
+    }
+}
+
+impl Brush for ImageBrush {
+    #[inline]
+    fn handle(&self) -> &ID2D1Brush {
+        &self.0
+    }
+}
+
+pub struct ImageBrushBuilder<'a, T>
+where
+    T: Backend,
+{
+    ctx: &'a Context<T>,
+    image: &'a Image,
+    source_rect: Option<Rect<f32>>,
+    extend_mode_x: GradientMode,
+    extend_mode_y: GradientMode,
+    interpolation: Interpolation,
+    opacity: f32,
+    transform: Matrix3x2,
+}
+
+impl<'a, T> ImageBrushBuilder<'a, T>
+where
+    T: Backend,
+{
+    fn new(ctx: &'a Context<T>, image: &'a Image) -> Self {
+        Self {
+            ctx,
+            image,
+            source_rect: None,
+            extend_mode_x: GradientMode::Wrap,
+            extend_mode_y: GradientMode::Wrap,
+            interpolation: Interpolation::Linear,
+            opacity: 1.0,
+            transform: Matrix3x2::IDENTITY,
+        }
+    }
+
+    #[inline]
+    pub fn source_rect(mut self, rect: impl Into<Rect<f32>>) -> Self {
+        self.source_rect = Some(rect.into());
+        self
+    }
+
+    #[inline]
+    pub fn extend_mode(mut self, mode: GradientMode) -> Self {
+        self.extend_mode_x = mode;
+        self.extend_mode_y = mode;
+        self
+    }
+
+    #[inline]
+    pub fn extend_mode_x(mut self, mode: GradientMode) -> Self {
+        self.extend_mode_x = mode;
+        self
+    }
+
+    #[inline]
+    pub fn extend_mode_y(mut self, mode: GradientMode) -> Self {
+        self.extend_mode_y = mode;
+        self
+    }
+
+    #[inline]
+    pub fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    #[inline]
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    #[inline]
+    pub fn transform(mut self, transform: impl Into<Matrix3x2>) -> Self {
+        self.transform = transform.into();
+        self
+    }
+
+    pub fn build(self) -> Result<ImageBrush> {
+        let source_rect = self
+            .source_rect
+            .unwrap_or_else(|| Rect::from_point_size((0.0, 0.0), self.image.size()));
+        let brush = unsafe {
+            self.ctx.d2d1_device_context.CreateImageBrush(
+                self.image.handle(),
+                &D2D1_IMAGE_BRUSH_PROPERTIES {
+                    sourceRectangle: source_rect.into(),
+                    extendModeX: self.extend_mode_x.into(),
+                    extendModeY: self.extend_mode_y.into(),
+                    interpolationMode: self.interpolation.into(),
+                },
+                Some(&D2D1_BRUSH_PROPERTIES {
+                    opacity: self.opacity,
+                    transform: self.transform.into(),
+                }),
+            )?
+        };
+        Ok(ImageBrush(brush.cast().unwrap()))
+    }
+}
@@ -0,0 +1,172 @@
+#[cfg(any(windows, test))]
+mod cache;
+
+use crate::*;
+#[cfg(windows)]
+pub use cache::AsBrush;
+#[cfg(windows)]
+pub(crate) use cache::BrushCache;
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BrushDesc {
+    Solid(Rgba),
+    Linear {
+        start: Point<f32>,
+        end: Point<f32>,
+        mode: GradientMode,
+        stops: GradientStops,
+        options: GradientOptions,
+    },
+    Radial {
+        ellipse: Ellipse,
+        offset: Vector<f32>,
+        mode: GradientMode,
+        stops: GradientStops,
+        options: GradientOptions,
+    },
+    Conic {
+        bounds: Rect<f32>,
+        center: Point<f32>,
+        start_angle: f32,
+        end_angle: f32,
+        mode: GradientMode,
+        stops: GradientStops,
+        options: GradientOptions,
+    },
+    Pattern(Pattern),
+}
+
+impl BrushDesc {
+    #[inline]
+    pub fn solid(color: impl Into<Rgba>) -> Self {
+        Self::Solid(color.into())
+    }
+
+    #[inline]
+    pub fn linear<G>(
+        start: impl Into<Point<f32>>,
+        end: impl Into<Point<f32>>,
+        mode: GradientMode,
+        stops: &[G],
+    ) -> Self
+    where
+        G: Into<GradientStop> + Clone,
+    {
+        Self::Linear {
+            start: start.into(),
+            end: end.into(),
+            mode,
+            stops: GradientStops::new(stops),
+            options: GradientOptions::default(),
+        }
+    }
+
+    #[inline]
+    pub fn radial<G>(
+        ellipse: impl Into<Ellipse>,
+        offset: impl Into<Vector<f32>>,
+        mode: GradientMode,
+        stops: &[G],
+    ) -> Self
+    where
+        G: Into<GradientStop> + Clone,
+    {
+        Self::Radial {
+            ellipse: ellipse.into(),
+            offset: offset.into(),
+            mode,
+            stops: GradientStops::new(stops),
+            options: GradientOptions::default(),
+        }
+    }
+
+    #[inline]
+    pub fn conic<G>(
+        bounds: impl Into<Rect<f32>>,
+        center: impl Into<Point<f32>>,
+        start_angle: f32,
+        end_angle: f32,
+        mode: GradientMode,
+        stops: &[G],
+    ) -> Self
+    where
+        G: Into<GradientStop> + Clone,
+    {
+        Self::Conic {
+            bounds: bounds.into(),
+            center: center.into(),
+            start_angle,
+            end_angle,
+            mode,
+            stops: GradientStops::new(stops),
+            options: GradientOptions::default(),
+        }
+    }
+
+    #[inline]
+    pub fn options(mut self, gradient_options: GradientOptions) -> Self {
+        match &mut self {
+            Self::Linear { options, .. }
+            | Self::Radial { options, .. }
+            | Self::Conic { options, .. } => *options = gradient_options,
+            Self::Solid(_) | Self::Pattern(_) => {}
+        }
+        self
+    }
+}
+
+impl From<Rgba> for BrushDesc {
+    #[inline]
+    fn from(value: Rgba) -> Self {
+        Self::Solid(value)
+    }
+}
+
+impl From<Pattern> for BrushDesc {
+    #[inline]
+    fn from(value: Pattern) -> Self {
+        Self::Pattern(value)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_serde() {
+        let stops = [(0.0, Rgba::RED), (1.0, Rgba::BLUE)];
+        let descs = [
+            BrushDesc::solid(Rgba::RED),
+            BrushDesc::linear((0.0, 0.0), (10.0, 0.0), GradientMode::Mirror, &stops)
+                .options(GradientOptions::new().color_space(GradientColorSpace::Perceptual)),
+            BrushDesc::radial(
+                Ellipse::new((5.0, 5.0), 5.0, 2.0),
+                (1.0, 0.0),
+                GradientMode::Clamp,
+                &stops,
+            ),
+            BrushDesc::conic(
+                Rect::new(0.0, 0.0, 10.0, 10.0),
+                (5.0, 5.0),
+                90.0,
+                450.0,
+                GradientMode::Wrap,
+                &stops,
+            ),
+            Pattern::new(HatchStyle::Dots)
+                .spacing(6.0)
+                .background(Rgba::BLUE)
+                .into(),
+        ];
+        for desc in descs {
+            let json = serde_json::to_string(&desc).unwrap();
+            assert_eq!(
+                serde_json::from_str::<BrushDesc>(&json).unwrap(),
+                desc,
+                "{json}"
+            );
+        }
+    }
+}
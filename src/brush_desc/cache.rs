@@ -0,0 +1,430 @@
+use crate::*;
+#[cfg(windows)]
+use std::borrow::Cow;
+use std::collections::HashMap;
+#[cfg(windows)]
+use windows::Win32::Graphics::Direct2D::*;
+
+#[cfg(windows)]
+impl BrushDesc {
+    fn create<T>(&self, ctx: &Context<T>) -> Result<ID2D1Brush>
+    where
+        T: Backend,
+    {
+        let brush = match self {
+            Self::Solid(color) => SolidColorBrush::new(ctx, *color)?.handle().clone(),
+            Self::Linear {
+                start,
+                end,
+                mode,
+                stops,
+                options,
+            } => {
+                let stops = GradientStopCollection::new(ctx, *mode, stops.as_slice(), options)?;
+                LinearGradientBrush::from_stop_collection(ctx, *start, *end, &stops)?
+                    .handle()
+                    .clone()
+            }
+            Self::Radial {
+                ellipse,
+                offset,
+                mode,
+                stops,
+                options,
+            } => {
+                let stops = GradientStopCollection::new(ctx, *mode, stops.as_slice(), options)?;
+                RadialGradientBrush::from_stop_collection(ctx, *ellipse, *offset, &stops)?
+                    .handle()
+                    .clone()
+            }
+            Self::Conic {
+                bounds,
+                center,
+                start_angle,
+                end_angle,
+                mode,
+                stops,
+                options,
+            } => {
+                let gradient = Gradient {
+                    shape: GradientShape::Conic {
+                        center: *center,
+                        start_angle: *start_angle,
+                        end_angle: *end_angle,
+                    },
+                    mode: *mode,
+                    stops: stops.clone(),
+                    options: *options,
+                };
+                ConicGradientBrush::from_gradient(ctx, *bounds, &gradient)?
+                    .handle()
+                    .clone()
+            }
+            Self::Pattern(pattern) => PatternBrush::new(ctx, pattern)?.handle().clone(),
+        };
+        Ok(brush)
+    }
+}
+
+#[cfg(windows)]
+pub trait AsBrush {
+    fn as_brush<T: Backend>(&self, ctx: &Context<T>) -> Option<Cow<'_, ID2D1Brush>>;
+}
+
+#[cfg(windows)]
+impl<B> AsBrush for B
+where
+    B: Brush,
+{
+    #[inline]
+    fn as_brush<T: Backend>(&self, _ctx: &Context<T>) -> Option<Cow<'_, ID2D1Brush>> {
+        Some(Cow::Borrowed(self.handle()))
+    }
+}
+
+// The brush is shared with every draw of an equal descriptor until it is evicted, so its
+// opacity and transform must be left as they are.
+#[cfg(windows)]
+impl AsBrush for BrushDesc {
+    #[inline]
+    fn as_brush<T: Backend>(&self, ctx: &Context<T>) -> Option<Cow<'_, ID2D1Brush>> {
+        ctx.brush_cache
+            .lock()
+            .unwrap()
+            .get(self, || self.create(ctx))
+            .map(Cow::Owned)
+    }
+}
+
+// Brushes that have not been used for this many frames are released.
+const RETAIN_FRAMES: u64 = 120;
+
+struct CachedBrush<B> {
+    brush: B,
+    last_used: u64,
+}
+
+// Generic over the brush so that the keying and eviction can be tested without Direct2D.
+pub(crate) struct BrushCache<B> {
+    brushes: HashMap<Vec<u32>, CachedBrush<B>>,
+    key: Vec<u32>,
+    frame: u64,
+    error: Option<Error>,
+}
+
+impl<B> Default for BrushCache<B> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            brushes: HashMap::new(),
+            key: Vec::new(),
+            frame: 0,
+            error: None,
+        }
+    }
+}
+
+impl<B: Clone> BrushCache<B> {
+    // A failed creation is not cached; its error is kept for `end_frame` and the
+    // descriptor is tried again on its next use.
+    fn get(&mut self, desc: &BrushDesc, create: impl FnOnce() -> Result<B>) -> Option<B> {
+        self.key.clear();
+        desc.write_key(&mut self.key);
+        if let Some(cached) = self.brushes.get_mut(self.key.as_slice()) {
+            cached.last_used = self.frame;
+            return Some(cached.brush.clone());
+        }
+        match create() {
+            Ok(brush) => {
+                self.brushes.insert(
+                    self.key.clone(),
+                    CachedBrush {
+                        brush: brush.clone(),
+                        last_used: self.frame,
+                    },
+                );
+                Some(brush)
+            }
+            Err(e) => {
+                self.error.get_or_insert(e);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn end_frame(&mut self) -> Option<Error> {
+        let frame = self.frame;
+        self.brushes
+            .retain(|_, cached| frame - cached.last_used < RETAIN_FRAMES);
+        self.frame += 1;
+        self.error.take()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.brushes.clear();
+    }
+}
+
+// Descriptors are keyed by the bits of their fields, with -0.0 folded into 0.0, so that
+// equal descriptors share a brush and NaN fields still find their own entry.
+trait WriteKey {
+    fn write_key(&self, key: &mut Vec<u32>);
+}
+
+impl WriteKey for f32 {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        key.push(if *self == 0.0 { 0 } else { self.to_bits() });
+    }
+}
+
+impl WriteKey for Rgba {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        for c in [self.r, self.g, self.b, self.a] {
+            c.write_key(key);
+        }
+    }
+}
+
+impl WriteKey for Point<f32> {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        self.x.write_key(key);
+        self.y.write_key(key);
+    }
+}
+
+impl WriteKey for Vector<f32> {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        self.x.write_key(key);
+        self.y.write_key(key);
+    }
+}
+
+impl WriteKey for Rect<f32> {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        for v in [self.left, self.top, self.right, self.bottom] {
+            v.write_key(key);
+        }
+    }
+}
+
+impl WriteKey for Ellipse {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        self.center.write_key(key);
+        self.radius_x.write_key(key);
+        self.radius_y.write_key(key);
+    }
+}
+
+impl WriteKey for GradientMode {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        key.push(*self as u32);
+    }
+}
+
+impl WriteKey for GradientOptions {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        key.push(self.color_space as u32);
+        key.push(self.precision as u32);
+        key.push(self.alpha as u32);
+    }
+}
+
+impl WriteKey for GradientStops {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        key.push(self.as_slice().len() as u32);
+        for stop in self.as_slice() {
+            stop.position.write_key(key);
+            stop.color.write_key(key);
+        }
+    }
+}
+
+impl WriteKey for Pattern {
+    #[inline]
+    fn write_key(&self, key: &mut Vec<u32>) {
+        key.push(self.style as u32);
+        self.spacing.write_key(key);
+        self.line_width.write_key(key);
+        self.angle.write_key(key);
+        self.foreground.write_key(key);
+        self.background.write_key(key);
+    }
+}
+
+impl WriteKey for BrushDesc {
+    fn write_key(&self, key: &mut Vec<u32>) {
+        match self {
+            Self::Solid(color) => {
+                key.push(0);
+                color.write_key(key);
+            }
+            Self::Linear {
+                start,
+                end,
+                mode,
+                stops,
+                options,
+            } => {
+                key.push(1);
+                start.write_key(key);
+                end.write_key(key);
+                mode.write_key(key);
+                stops.write_key(key);
+                options.write_key(key);
+            }
+            Self::Radial {
+                ellipse,
+                offset,
+                mode,
+                stops,
+                options,
+            } => {
+                key.push(2);
+                ellipse.write_key(key);
+                offset.write_key(key);
+                mode.write_key(key);
+                stops.write_key(key);
+                options.write_key(key);
+            }
+            Self::Conic {
+                bounds,
+                center,
+                start_angle,
+                end_angle,
+                mode,
+                stops,
+                options,
+            } => {
+                key.push(3);
+                bounds.write_key(key);
+                center.write_key(key);
+                start_angle.write_key(key);
+                end_angle.write_key(key);
+                mode.write_key(key);
+                stops.write_key(key);
+                options.write_key(key);
+            }
+            Self::Pattern(pattern) => {
+                key.push(4);
+                pattern.write_key(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(desc: &BrushDesc) -> Vec<u32> {
+        let mut key = Vec::new();
+        desc.write_key(&mut key);
+        key
+    }
+
+    fn linear(mode: GradientMode) -> BrushDesc {
+        BrushDesc::linear(
+            (0.0, 0.0),
+            (1.0, 0.0),
+            mode,
+            &[(0.0, Rgba::BLACK), (1.0, Rgba::WHITE)],
+        )
+    }
+
+    #[test]
+    fn keys_distinguish_every_field() {
+        let red = Rgba::new(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(key(&BrushDesc::solid(red)), key(&BrushDesc::solid(red)));
+        assert_ne!(
+            key(&BrushDesc::solid(red)),
+            key(&BrushDesc::solid(red.with_alpha(0.5)))
+        );
+        assert_eq!(
+            key(&BrushDesc::solid(Rgba::new(-0.0, 0.0, 0.0, 1.0))),
+            key(&BrushDesc::solid(Rgba::new(0.0, 0.0, 0.0, 1.0)))
+        );
+        assert_ne!(
+            key(&linear(GradientMode::Clamp)),
+            key(&linear(GradientMode::Wrap))
+        );
+        assert_ne!(
+            key(&linear(GradientMode::Clamp)),
+            key(&linear(GradientMode::Clamp)
+                .options(GradientOptions::new().color_space(GradientColorSpace::Linear)))
+        );
+        let pattern = Pattern::new(HatchStyle::Grid);
+        assert_ne!(
+            key(&BrushDesc::Pattern(pattern)),
+            key(&BrushDesc::Pattern(pattern.angle(30.0)))
+        );
+        // The variant tag keeps different kinds apart even when their fields would not.
+        assert_ne!(
+            key(&BrushDesc::solid(red))[0],
+            key(&linear(GradientMode::Clamp))[0]
+        );
+    }
+
+    #[test]
+    fn shares_brushes_of_equal_descriptors() {
+        let mut cache = BrushCache::default();
+        let mut created = 0;
+        let mut create = || {
+            created += 1;
+            Ok(created)
+        };
+        let nan = BrushDesc::solid(Rgba::new(f32::NAN, 0.0, 0.0, 1.0));
+        assert_eq!(
+            cache.get(&linear(GradientMode::Clamp), &mut create),
+            Some(1)
+        );
+        assert_eq!(
+            cache.get(&linear(GradientMode::Clamp), &mut create),
+            Some(1)
+        );
+        assert_eq!(
+            cache.get(&linear(GradientMode::Mirror), &mut create),
+            Some(2)
+        );
+        // NaN fields compare by their bits, so they still find their own entry.
+        assert_eq!(cache.get(&nan, &mut create), Some(3));
+        assert_eq!(cache.get(&nan, &mut create), Some(3));
+        cache.clear();
+        assert_eq!(cache.get(&nan, &mut create), Some(4));
+    }
+
+    #[test]
+    fn evicts_brushes_unused_for_the_retained_frames() {
+        let mut cache = BrushCache::default();
+        let (kept, dropped) = (linear(GradientMode::Clamp), linear(GradientMode::Wrap));
+        assert_eq!(cache.get(&kept, || Ok(1)), Some(1));
+        assert_eq!(cache.get(&dropped, || Ok(2)), Some(2));
+        // The frame that used them and then the retained frames without a use.
+        for _ in 0..=RETAIN_FRAMES {
+            assert_eq!(cache.get(&kept, || Ok(3)), Some(1));
+            assert!(cache.end_frame().is_none());
+        }
+        assert_eq!(cache.get(&kept, || Ok(3)), Some(1));
+        assert_eq!(cache.get(&dropped, || Ok(4)), Some(4));
+    }
+
+    #[test]
+    fn reports_the_first_error_at_the_end_of_the_frame() {
+        let mut cache = BrushCache::<u32>::default();
+        let desc = linear(GradientMode::Clamp);
+        assert_eq!(cache.get(&desc, || Err(Error::NoInterface)), None);
+        assert_eq!(cache.get(&desc, || Err(Error::NeedRecreateTarget)), None);
+        assert!(matches!(cache.end_frame(), Some(Error::NoInterface)));
+        assert!(cache.end_frame().is_none());
+        // The failure is not cached, so the next use tries again.
+        assert_eq!(cache.get(&desc, || Ok(1)), Some(1));
+    }
+}
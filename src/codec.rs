@@ -0,0 +1,597 @@
+mod bmp;
+mod deflate;
+mod exif;
+mod gif;
+mod inflate;
+mod jpeg;
+mod png;
+mod qoi;
+mod zlib;
+
+pub(crate) use bmp::*;
+pub(crate) use exif::*;
+pub(crate) use gif::*;
+pub(crate) use jpeg::*;
+pub(crate) use png::*;
+pub(crate) use qoi::*;
+#[cfg(feature = "pdf")]
+pub(crate) use zlib::compress;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Blend, ColorModel, Disposal, Error, ImageMetadata, LoopCount, Orientation, Rect, Rgba8,
+        Size, SourcePixelFormat,
+    };
+
+    #[test]
+    fn png_chunks_have_valid_checksums() {
+        assert_eq!(png::crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(zlib::adler32(b"Wikipedia"), 0x11e6_0398);
+        let data = encode_png(Size::new(1, 1), &[Rgba8::new(1, 2, 3, 4)]);
+        assert_eq!(&data[12..16], b"IHDR");
+        assert_eq!(&data[data.len() - 8..data.len() - 4], b"IEND");
+    }
+
+    #[test]
+    fn zlib_splits_long_input_into_stored_blocks() {
+        // Bytes from an LCG leave nothing for matching to find.
+        let mut state = 1u32;
+        let data = (0..0x1_0000 + 10)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 24) as u8
+            })
+            .collect::<Vec<_>>();
+        let out = zlib::compress(&data);
+        assert_eq!(out.len(), 2 + 5 + 0xffff + 5 + 11 + 4);
+        assert_eq!(out[2], 0);
+        assert_eq!(out[2 + 5 + 0xffff], 1);
+    }
+
+    #[test]
+    fn zlib_codes_repeats_as_matches() {
+        assert_eq!(
+            zlib::compress(b"abcabcabcabc"),
+            [
+                0x78, 0x01, 0x4b, 0x4c, 0x4a, 0x86, 0x23, 0x00, 0x1d, 0xe0, 0x04, 0x99
+            ]
+        );
+        assert!(zlib::compress(&[7; 0x1_0000]).len() < 1024);
+    }
+
+    #[test]
+    fn bmp_keeps_alpha_and_stores_rows_bottom_up() {
+        let pixels = [Rgba8::new(1, 2, 3, 4), Rgba8::new(5, 6, 7, 8)];
+        let data = encode_bmp(Size::new(1, 2), &pixels).unwrap();
+        assert_eq!(&data[..2], b"BM");
+        assert_eq!(u32::from_le_bytes(data[2..6].try_into().unwrap()), 130);
+        assert_eq!(u32::from_le_bytes(data[10..14].try_into().unwrap()), 122);
+        assert_eq!(
+            u32::from_le_bytes(data[66..70].try_into().unwrap()),
+            0xff00_0000
+        );
+        assert_eq!(&data[122..], [7, 6, 5, 8, 3, 2, 1, 4]);
+        assert!(matches!(
+            encode_bmp(Size::new(32768, 32768), &[]),
+            Err(Error::ImageTooLarge)
+        ));
+    }
+
+    #[test]
+    fn qoi_picks_the_shortest_op() {
+        let pixels = [
+            Rgba8::new(0, 0, 0, 255),
+            Rgba8::new(0, 0, 0, 255),
+            Rgba8::new(1, 0, 255, 255),
+            Rgba8::new(11, 10, 8, 255),
+            Rgba8::new(200, 100, 50, 255),
+            Rgba8::new(200, 100, 50, 128),
+            Rgba8::new(1, 0, 255, 255),
+        ];
+        let data = encode_qoi(Size::new(7, 1), &pixels);
+        assert_eq!(&data[..14], b"qoif\0\0\0\x07\0\0\0\x01\x04\x00");
+        assert_eq!(
+            &data[14..],
+            [
+                0xc1,                       // run of 2
+                0x40 | 3 << 4 | 2 << 2 | 1, // diff
+                0x80 | 42,
+                8 << 4 | 7, // luma
+                0xfe,
+                200,
+                100,
+                50, // rgb
+                0xff,
+                200,
+                100,
+                50,
+                128, // rgba
+                49,  // index
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                1,
+            ]
+        );
+    }
+
+    const DYNAMIC: [u8; 84] = [
+        0x78, 0xda, 0xb5, 0xcb, 0xc9, 0x11, 0x80, 0x20, 0x10, 0x44, 0xd1, 0x54, 0x3a, 0x0f, 0xa3,
+        0x01, 0x65, 0x53, 0x60, 0xd8, 0x11, 0xa3, 0x77, 0xca, 0x1c, 0x3c, 0x76, 0xfd, 0xd7, 0xcd,
+        0x2a, 0xe4, 0xee, 0xf6, 0x0b, 0xb2, 0xd0, 0x8c, 0xd0, 0x74, 0xe3, 0xec, 0x21, 0x55, 0xd0,
+        0x50, 0x05, 0x8d, 0xb3, 0x17, 0xcf, 0xc2, 0x41, 0x66, 0xfb, 0xd6, 0x3f, 0x38, 0x09, 0x76,
+        0x61, 0x41, 0x32, 0x9a, 0xae, 0x59, 0x68, 0x37, 0x14, 0xa7, 0x47, 0x45, 0x78, 0x97, 0x3b,
+        0x15, 0xfe, 0x9a, 0xfa, 0x02, 0xb6, 0x48, 0x3f, 0x86,
+    ];
+
+    #[test]
+    fn zlib_round_trips_and_reads_dynamic_blocks() {
+        let mut text = b"the quick brown fox jumps over the lazy dog; ".repeat(3);
+        text.extend(b"pack my box with five dozen liquor jugs");
+        assert_eq!(zlib::decompress(&DYNAMIC, text.len()).unwrap(), text);
+        assert!(matches!(
+            zlib::decompress(&DYNAMIC, text.len() - 1),
+            Err(Error::InvalidImage)
+        ));
+        let mut corrupt = DYNAMIC;
+        corrupt[83] ^= 1;
+        assert!(matches!(
+            zlib::decompress(&corrupt, text.len()),
+            Err(Error::InvalidImage)
+        ));
+        let mut state = 7u32;
+        let data = (0..100_000)
+            .map(|i| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                if i % 1000 < 500 {
+                    (state >> 24) as u8
+                } else {
+                    (i / 7) as u8
+                }
+            })
+            .collect::<Vec<_>>();
+        for data in [&data[..0], &data[..1], &data[..5000], &data] {
+            assert_eq!(
+                zlib::decompress(&zlib::compress(data), data.len()).unwrap(),
+                data
+            );
+        }
+    }
+
+    fn build_png(header: [u8; 13], chunks: &[(&[u8; 4], &[u8])], raw: &[u8]) -> Vec<u8> {
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png::chunk(&mut out, b"IHDR", &header);
+        for (kind, data) in chunks {
+            png::chunk(&mut out, kind, data);
+        }
+        png::chunk(&mut out, b"IDAT", &zlib::compress(raw));
+        png::chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    fn ihdr(width: u32, height: u32, depth: u8, color_type: u8, interlace: u8) -> [u8; 13] {
+        let mut header = [0; 13];
+        header[..4].copy_from_slice(&width.to_be_bytes());
+        header[4..8].copy_from_slice(&height.to_be_bytes());
+        header[8..10].copy_from_slice(&[depth, color_type]);
+        header[12] = interlace;
+        header
+    }
+
+    #[test]
+    fn png_decodes_what_it_encodes() {
+        let pixels = (0..7 * 5)
+            .map(|i| {
+                Rgba8::new(
+                    i as u8 * 7,
+                    255 - i as u8,
+                    (i % 3) as u8 * 100,
+                    (i * 9) as u8,
+                )
+            })
+            .collect::<Vec<_>>();
+        let data = encode_png(Size::new(7, 5), &pixels);
+        assert_eq!(decode_png(&data).unwrap(), (Size::new(7, 5), pixels));
+    }
+
+    #[test]
+    fn png_decodes_palettes_and_low_bit_depths() {
+        let palette = [10, 20, 30, 40, 50, 60, 70, 80, 90];
+        let data = build_png(
+            ihdr(3, 2, 2, 3, 0),
+            &[(b"PLTE", &palette), (b"tRNS", &[0, 128])],
+            &[0, 0b00_01_10_00, 0, 0b10_10_01_00],
+        );
+        let (a, b, c) = (
+            Rgba8::new(10, 20, 30, 0),
+            Rgba8::new(40, 50, 60, 128),
+            Rgba8::new(70, 80, 90, 255),
+        );
+        assert_eq!(decode_png(&data).unwrap().1, [a, b, c, c, c, b]);
+        let data = build_png(
+            ihdr(3, 1, 1, 0, 0),
+            &[(b"tRNS", &[0, 1])],
+            &[0, 0b1010_0000],
+        );
+        let (white, black) = (Rgba8::new(255, 255, 255, 0), Rgba8::new(0, 0, 0, 255));
+        assert_eq!(decode_png(&data).unwrap().1, [white, black, white]);
+        let data = build_png(ihdr(1, 1, 16, 4, 0), &[], &[0, 0x12, 0x34, 0xab, 0xcd]);
+        assert_eq!(
+            decode_png(&data).unwrap().1,
+            [Rgba8::new(0x12, 0x12, 0x12, 0xab)]
+        );
+    }
+
+    #[test]
+    fn png_decodes_interlaced_passes() {
+        #[rustfmt::skip]
+        let raw = [
+            0, 0, 0, 7, // pass 1
+            0, 20, 0, 7, // pass 4
+            0, 0, 20, 7, 20, 20, 7, // pass 5
+            0, 10, 0, 7, 0, 10, 20, 7, // pass 6
+            0, 0, 10, 7, 10, 10, 7, 20, 10, 7, // pass 7
+        ];
+        let data = build_png(ihdr(3, 3, 8, 2, 1), &[], &raw);
+        let expected = (0..9)
+            .map(|i| Rgba8::new(i % 3 * 10, i / 3 * 10, 7, 255))
+            .collect::<Vec<_>>();
+        assert_eq!(decode_png(&data).unwrap(), (Size::new(3, 3), expected));
+    }
+
+    #[test]
+    fn png_rejects_corrupt_or_unknown_chunks() {
+        let mut data = encode_png(Size::new(1, 1), &[Rgba8::new(1, 2, 3, 4)]);
+        let mut unknown = data.clone();
+        data[20] ^= 1;
+        assert!(matches!(decode_png(&data), Err(Error::InvalidImage)));
+        // Renaming IDAT to a critical chunk nobody knows.
+        let idat = unknown.windows(4).position(|w| w == b"IDAT").unwrap();
+        unknown[idat..idat + 4].copy_from_slice(b"ABCD");
+        let len = u32::from_be_bytes(unknown[idat - 4..idat].try_into().unwrap()) as usize;
+        let crc = png::crc32(&unknown[idat..idat + 4 + len]);
+        unknown[idat + 4 + len..idat + 8 + len].copy_from_slice(&crc.to_be_bytes());
+        assert!(matches!(decode_png(&unknown), Err(Error::UnsupportedImage)));
+    }
+
+    #[test]
+    fn qoi_decodes_what_it_encodes() {
+        let pixels = (0..200)
+            .map(|i: u32| match i % 50 {
+                0..20 => Rgba8::new(1, 2, 3, 255),
+                20..30 => Rgba8::new(i as u8, i as u8 / 2, 255 - i as u8, 255),
+                _ => Rgba8::new((i * 3) as u8, 0, 9, (i * 5) as u8),
+            })
+            .collect::<Vec<_>>();
+        let data = encode_qoi(Size::new(20, 10), &pixels);
+        assert_eq!(decode_qoi(&data).unwrap(), (Size::new(20, 10), pixels));
+        assert!(matches!(
+            decode_qoi(&data[..data.len() - 9]),
+            Err(Error::InvalidImage)
+        ));
+    }
+
+    // Packs LZW codes of the given widths, least significant bit first.
+    fn pack_codes(codes: &[(u32, u32)]) -> Vec<u8> {
+        let (mut out, mut bits, mut count) = (Vec::new(), 0u32, 0);
+        for &(code, width) in codes {
+            bits |= code << count;
+            count += width;
+            while count >= 8 {
+                out.push(bits as u8);
+                bits >>= 8;
+                count -= 8;
+            }
+        }
+        if count > 0 {
+            out.push(bits as u8);
+        }
+        out
+    }
+
+    // Clearing before every index keeps codes at three bits and the table empty.
+    fn gif_frame(out: &mut Vec<u8>, control: Option<[u8; 4]>, rect: [u16; 4], indices: &[u8]) {
+        if let Some(control) = control {
+            out.extend([0x21, 0xf9, 4]);
+            out.extend(control);
+            out.push(0);
+        }
+        out.push(0x2c);
+        for value in rect {
+            out.extend(value.to_le_bytes());
+        }
+        out.extend([0, 2]);
+        let mut codes = indices
+            .iter()
+            .flat_map(|&i| [(4, 3), (i as u32, 3)])
+            .collect::<Vec<_>>();
+        codes.push((5, 3));
+        let data = pack_codes(&codes);
+        for block in data.chunks(255) {
+            out.push(block.len() as u8);
+            out.extend(block);
+        }
+        out.push(0);
+    }
+
+    #[test]
+    fn gif_lzw_grows_codes_and_repeats_strings() {
+        // Codes 6 and 7 refer to themselves as they are added, and 8 entries widen codes to
+        // four bits.
+        let data = pack_codes(&[(4, 3), (1, 3), (6, 3), (7, 3), (5, 4)]);
+        let mut gif = b"GIF89a\x06\x00\x01\x00\x81\x00\x00".to_vec();
+        gif.extend([0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255]);
+        gif.extend([0x2c, 0, 0, 0, 0, 6, 0, 1, 0, 0, 2, data.len() as u8]);
+        gif.extend(&data);
+        gif.extend([0, 0x3b]);
+        let animation = decode_gif(&gif).unwrap();
+        assert_eq!(
+            animation.frames()[0].pixels,
+            [Rgba8::new(255, 0, 0, 255); 6]
+        );
+    }
+
+    #[test]
+    fn gif_rejects_huge_screens_before_allocating() {
+        let mut gif = b"GIF89a\xff\xff\xff\xff\x80\x00\x00".to_vec();
+        gif.extend([0; 6]);
+        gif.extend([0x2c, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0, 2, 0, 0x3b]);
+        assert!(matches!(decode_gif(&gif), Err(Error::ImageTooLarge)));
+        // A frame larger than a small screen is cropped to it.
+        gif[6..10].copy_from_slice(&[2, 0, 1, 0]);
+        let animation = decode_gif(&gif).unwrap();
+        assert_eq!(animation.frames()[0].info.rect, Rect::new(0, 0, 2, 1));
+        assert_eq!(animation.frames()[0].pixels.len(), 2);
+    }
+
+    #[test]
+    fn gif_reads_frames_timing_and_loops() {
+        let (black, red, green, blue) = (
+            Rgba8::new(0, 0, 0, 255),
+            Rgba8::new(255, 0, 0, 255),
+            Rgba8::new(0, 255, 0, 255),
+            Rgba8::new(0, 0, 255, 255),
+        );
+        let mut gif = b"GIF89a\x03\x00\x02\x00\x81\x00\x00".to_vec();
+        gif.extend([0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255]);
+        gif.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x02\x00\x00");
+        gif_frame(
+            &mut gif,
+            Some([0x04, 5, 0, 0]),
+            [0, 0, 3, 2],
+            &[1, 1, 1, 2, 2, 2],
+        );
+        gif_frame(
+            &mut gif,
+            Some([0x0d, 10, 0, 0]),
+            [1, 0, 2, 2],
+            &[3, 0, 0, 3],
+        );
+        gif_frame(&mut gif, None, [0, 1, 1, 1], &[0]);
+        gif.push(0x3b);
+        let animation = decode_gif(&gif).unwrap();
+        assert_eq!(animation.size(), Size::new(3, 2));
+        assert_eq!(animation.loop_count(), LoopCount::Finite(3));
+        let infos = animation
+            .frames()
+            .iter()
+            .map(|frame| (frame.info.delay.as_millis(), frame.info.disposal))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            infos,
+            [
+                (50, Disposal::None),
+                (100, Disposal::Previous),
+                (0, Disposal::None)
+            ]
+        );
+        let canvases = animation
+            .composite()
+            .iter()
+            .map(|canvas| canvas.to_rgba8())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            canvases,
+            [
+                [red, red, red, green, green, green],
+                [red, blue, red, green, green, blue],
+                [red, red, red, black, green, green],
+            ]
+        );
+    }
+
+    #[test]
+    fn apng_frames_dispose_and_blend() {
+        fn frame_control(seq: u32, rect: [u32; 4], delay: [u16; 2], ops: [u8; 2]) -> Vec<u8> {
+            let mut out = seq.to_be_bytes().to_vec();
+            for value in rect {
+                out.extend(value.to_be_bytes());
+            }
+            out.extend(delay[0].to_be_bytes());
+            out.extend(delay[1].to_be_bytes());
+            out.extend(ops);
+            out
+        }
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        png::chunk(&mut data, b"IHDR", &ihdr(2, 1, 8, 6, 0));
+        png::chunk(&mut data, b"acTL", &[0, 0, 0, 2, 0, 0, 0, 0]);
+        png::chunk(
+            &mut data,
+            b"fcTL",
+            &frame_control(0, [2, 1, 0, 0], [1, 2], [1, 0]),
+        );
+        let first = zlib::compress(&[0, 255, 0, 0, 255, 0, 0, 255, 128]);
+        png::chunk(&mut data, b"IDAT", &first);
+        png::chunk(
+            &mut data,
+            b"fcTL",
+            &frame_control(1, [1, 1, 1, 0], [3, 0], [0, 1]),
+        );
+        let mut second = 2u32.to_be_bytes().to_vec();
+        second.extend(zlib::compress(&[0, 0, 255, 0, 255]));
+        png::chunk(&mut data, b"fdAT", &second);
+        png::chunk(&mut data, b"IEND", &[]);
+        let animation = decode_apng(&data).unwrap();
+        assert_eq!(animation.loop_count(), LoopCount::Infinite);
+        let infos = animation
+            .frames()
+            .iter()
+            .map(|frame| frame.info)
+            .collect::<Vec<_>>();
+        assert_eq!(infos[0].delay.as_millis(), 500);
+        assert_eq!(infos[0].disposal, Disposal::Background);
+        assert_eq!(infos[1].delay.as_millis(), 30);
+        assert_eq!(infos[1].rect, Rect::new(1, 0, 2, 1));
+        assert_eq!(infos[1].blend, Blend::Over);
+        let canvases = animation.composite();
+        assert_eq!(
+            canvases[0].to_rgba8(),
+            [Rgba8::new(255, 0, 0, 255), Rgba8::new(0, 0, 255, 128)]
+        );
+        assert_eq!(
+            canvases[1].to_rgba8(),
+            [Rgba8::new(0, 0, 0, 0), Rgba8::new(0, 255, 0, 255)]
+        );
+        // Without acTL only the default image shows.
+        let still = encode_png(Size::new(1, 1), &[Rgba8::new(1, 2, 3, 4)]);
+        assert_eq!(decode_apng(&still).unwrap().frame_count(), 1);
+    }
+
+    // A TIFF header and one IFD holding the orientation and, optionally, a resolution in
+    // inches.
+    fn exif(big_endian: bool, orientation: u16, dpi: Option<u32>) -> Vec<u8> {
+        let u16_bytes = |v: u16| {
+            if big_endian {
+                v.to_be_bytes()
+            } else {
+                v.to_le_bytes()
+            }
+        };
+        let u32_bytes = |v: u32| {
+            if big_endian {
+                v.to_be_bytes()
+            } else {
+                v.to_le_bytes()
+            }
+        };
+        let mut out = if big_endian { b"MM\0*" } else { b"II*\0" }.to_vec();
+        out.extend(u32_bytes(8));
+        let mut entries = vec![(0x0112, 3, u32::from(orientation))];
+        if dpi.is_some() {
+            // Both resolutions point at the same rational after the IFD.
+            let rational = 8 + 2 + 4 * 12 + 4;
+            entries.extend([(0x011a, 5, rational), (0x011b, 5, rational), (0x0128, 3, 2)]);
+        }
+        out.extend(u16_bytes(entries.len() as u16));
+        for (tag, kind, value) in entries {
+            out.extend(u16_bytes(tag));
+            out.extend(u16_bytes(kind));
+            out.extend(u32_bytes(1));
+            if kind == 3 {
+                out.extend(u16_bytes(value as u16));
+                out.extend([0, 0]);
+            } else {
+                out.extend(u32_bytes(value));
+            }
+        }
+        out.extend(u32_bytes(0));
+        if let Some(dpi) = dpi {
+            out.extend(u32_bytes(dpi * 2));
+            out.extend(u32_bytes(2));
+        }
+        out
+    }
+
+    fn segment(out: &mut Vec<u8>, marker: u8, body: &[u8]) {
+        out.extend([0xff, marker]);
+        out.extend((body.len() as u16 + 2).to_be_bytes());
+        out.extend(body);
+    }
+
+    #[test]
+    fn jpeg_reads_exif_orientation() {
+        let mut data = vec![0xff, 0xd8];
+        segment(
+            &mut data,
+            0xe0,
+            b"JFIF\0\x01\x02\x00\x00\x01\x00\x01\x00\x00",
+        );
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend(exif(true, 6, Some(300)));
+        segment(&mut data, 0xe1, &app1);
+        segment(&mut data, 0xe2, b"ICC_PROFILE\0\x01\x01");
+        // Fill bytes may come before a marker.
+        data.push(0xff);
+        segment(
+            &mut data,
+            0xc2,
+            &[8, 0, 3, 0, 4, 3, 1, 0x11, 0, 2, 0x11, 1, 3, 0x11, 1],
+        );
+        segment(&mut data, 0xda, &[]);
+        let metadata = ImageMetadata::read(&data).unwrap();
+        assert_eq!(metadata.size, Size::new(4, 3));
+        assert_eq!(metadata.oriented_size(), Size::new(3, 4));
+        assert_eq!(
+            metadata.pixel_format,
+            SourcePixelFormat::new(ColorModel::Rgb, 8)
+        );
+        assert_eq!(metadata.orientation, Orientation::Rotate90);
+        assert!(metadata.has_color_profile);
+        // JFIF without units leaves the resolution to EXIF.
+        assert_eq!(metadata.dpi, Some((300.0, 300.0)));
+
+        let mut data = vec![0xff, 0xd8];
+        segment(
+            &mut data,
+            0xe0,
+            b"JFIF\0\x01\x02\x02\x00\x64\x00\x32\x00\x00",
+        );
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend(exif(false, 8, None));
+        segment(&mut data, 0xe1, &app1);
+        segment(&mut data, 0xc0, &[8, 0, 2, 0, 5, 1, 1, 0x11, 0]);
+        segment(&mut data, 0xda, &[]);
+        let metadata = ImageMetadata::read(&data).unwrap();
+        assert_eq!(metadata.orientation, Orientation::Rotate270);
+        assert_eq!(
+            metadata.pixel_format,
+            SourcePixelFormat::new(ColorModel::Gray, 8)
+        );
+        assert_eq!(metadata.dpi, Some((254.0, 127.0)));
+        assert!(!metadata.has_color_profile);
+        assert!(ImageMetadata::read(&data[..20]).is_err());
+    }
+
+    #[test]
+    fn png_reads_metadata_chunks() {
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        png::chunk(&mut data, b"IHDR", &ihdr(2, 1, 16, 4, 0));
+        png::chunk(&mut data, b"pHYs", &[0, 0, 0x0b, 0x13, 0, 0, 0x0b, 0x13, 1]);
+        png::chunk(&mut data, b"iCCP", b"icc\0\0");
+        png::chunk(&mut data, b"IDAT", &[]);
+        png::chunk(&mut data, b"eXIf", &exif(true, 3, None));
+        png::chunk(&mut data, b"IEND", &[]);
+        let metadata = ImageMetadata::read(&data).unwrap();
+        assert_eq!(metadata.size, Size::new(2, 1));
+        assert_eq!(
+            metadata.pixel_format,
+            SourcePixelFormat::new(ColorModel::GrayAlpha, 16)
+        );
+        let (x, y) = metadata.dpi.unwrap();
+        assert!((x - 72.0).abs() < 0.01 && (y - 72.0).abs() < 0.01);
+        assert!(metadata.has_color_profile);
+        assert_eq!(metadata.orientation, Orientation::Rotate180);
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        assert!(matches!(
+            ImageMetadata::read(&data),
+            Err(Error::InvalidImage)
+        ));
+    }
+}
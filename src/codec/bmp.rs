@@ -0,0 +1,99 @@
+use crate::*;
+
+const FILE_HEADER_LEN: u32 = 14;
+const INFO_HEADER_LEN: u32 = 108;
+const BI_BITFIELDS: u32 = 3;
+const LCS_SRGB: u32 = u32::from_be_bytes(*b"sRGB");
+// 96 DPI, the resolution Direct2D targets are created with.
+const PIXELS_PER_METER: u32 = 3780;
+
+// 32-bit BGRA with a BITMAPV4HEADER, whose alpha mask makes readers keep the straight alpha
+// channel. Rows are stored bottom-up. The file size is a 32-bit field, so images of 4 GiB
+// and more fail with `Error::ImageTooLarge`.
+pub(crate) fn encode_bmp(size: Size<u32>, pixels: &[Rgba8]) -> Result<Vec<u8>> {
+    let offset = FILE_HEADER_LEN + INFO_HEADER_LEN;
+    let image_len = size.width as u64 * size.height as u64 * 4;
+    let file_len = u32::try_from(offset as u64 + image_len).map_err(|_| Error::ImageTooLarge)?;
+    let image_len = file_len - offset;
+    let mut out = Vec::with_capacity(file_len as usize);
+    out.extend(b"BM");
+    out.extend(file_len.to_le_bytes());
+    out.extend(0u32.to_le_bytes());
+    out.extend(offset.to_le_bytes());
+    out.extend(INFO_HEADER_LEN.to_le_bytes());
+    out.extend((size.width as i32).to_le_bytes());
+    out.extend((size.height as i32).to_le_bytes());
+    out.extend(1u16.to_le_bytes());
+    out.extend(32u16.to_le_bytes());
+    out.extend(BI_BITFIELDS.to_le_bytes());
+    out.extend(image_len.to_le_bytes());
+    out.extend(PIXELS_PER_METER.to_le_bytes());
+    out.extend(PIXELS_PER_METER.to_le_bytes());
+    out.extend([0; 8]);
+    for mask in [0x00ff_0000u32, 0x0000_ff00, 0x0000_00ff, 0xff00_0000] {
+        out.extend(mask.to_le_bytes());
+    }
+    out.extend(LCS_SRGB.to_le_bytes());
+    // Endpoints and gammas are ignored for sRGB.
+    out.extend([0; 48]);
+    for row in pixels.chunks(size.width.max(1) as usize).rev() {
+        out.extend(
+            row.iter()
+                .flat_map(|pixel| pixel.to_bytes(ChannelOrder::Bgra)),
+        );
+    }
+    Ok(out)
+}
+
+// Core headers from OS/2 have 16-bit sizes and none of the later fields.
+pub(crate) fn bmp_metadata(data: &[u8]) -> Result<ImageMetadata> {
+    const PROFILE_EMBEDDED: u32 = u32::from_be_bytes(*b"MBED");
+    let u16_at = |i: usize| {
+        data.get(i..i + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .ok_or(Error::InvalidImage)
+    };
+    let u32_at = |i: usize| {
+        data.get(i..i + 4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .ok_or(Error::InvalidImage)
+    };
+    let header_len = u32_at(14)?;
+    let (size, bit_count) = if header_len == 12 {
+        let size = Size::new(u16_at(18)? as u32, u16_at(20)? as u32);
+        (size, u16_at(24)?)
+    } else if header_len >= 40 {
+        // A negative height means the rows are stored top-down.
+        let size = Size::new(
+            u32_at(18)? as i32 as u32,
+            (u32_at(22)? as i32).unsigned_abs(),
+        );
+        (size, u16_at(28)?)
+    } else {
+        return Err(Error::InvalidImage);
+    };
+    let model = match bit_count {
+        1 | 2 | 4 | 8 => ColorModel::Indexed,
+        32 if header_len >= 56 && u32_at(66)? != 0 => ColorModel::Rgba,
+        16 | 24 | 32 => ColorModel::Rgb,
+        _ => return Err(Error::InvalidImage),
+    };
+    let bit_depth = match bit_count {
+        16 => 5,
+        24 | 32 => 8,
+        n => n as u8,
+    };
+    let dpi = if header_len >= 40 {
+        let (x, y) = (u32_at(38)? as i32, u32_at(42)? as i32);
+        (x > 0 && y > 0).then_some((x as f32 * 0.0254, y as f32 * 0.0254))
+    } else {
+        None
+    };
+    Ok(ImageMetadata {
+        size,
+        pixel_format: SourcePixelFormat::new(model, bit_depth),
+        dpi,
+        has_color_profile: header_len >= 124 && u32_at(70)? == PROFILE_EMBEDDED,
+        orientation: Orientation::Normal,
+    })
+}
@@ -0,0 +1,137 @@
+const WINDOW: usize = 1 << 15;
+const HASH_BITS: u32 = 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+
+pub(super) const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+pub(super) const LENGTH_EXTRA: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+pub(super) const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+pub(super) const DISTANCE_EXTRA: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, len: u32) {
+        self.bits |= (value as u64) << self.count;
+        self.count += len;
+        while self.count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes are packed starting from their most significant bit.
+    fn write_code(&mut self, code: u32, len: u32) {
+        self.write(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn literal(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn copy(&mut self, len: usize, distance: usize) {
+        let i = LENGTH_BASE.partition_point(|&base| base as usize <= len) - 1;
+        self.literal(257 + i as u32);
+        self.write((len - LENGTH_BASE[i] as usize) as u32, LENGTH_EXTRA[i]);
+        let i = DISTANCE_BASE.partition_point(|&base| base as usize <= distance) - 1;
+        self.write_code(i as u32, 5);
+        self.write(
+            (distance - DISTANCE_BASE[i] as usize) as u32,
+            DISTANCE_EXTRA[i],
+        );
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.bits as u8);
+        }
+        self.out
+    }
+}
+
+fn hash(data: &[u8]) -> usize {
+    let key = u32::from_le_bytes([data[0], data[1], data[2], 0]);
+    (key.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+}
+
+fn insert(data: &[u8], i: usize, head: &mut [usize], prev: &mut [usize]) {
+    if i + MIN_MATCH <= data.len() {
+        let h = hash(&data[i..]);
+        prev[i % WINDOW] = head[h];
+        head[h] = i;
+    }
+}
+
+// A single deflate block of LZ77 matches coded with the fixed Huffman tables.
+pub(super) fn deflate_fixed(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter {
+        out: Vec::with_capacity(data.len() / 2),
+        bits: 0,
+        count: 0,
+    };
+    writer.write(1, 1);
+    writer.write(1, 2);
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let max = (data.len() - i).min(MAX_MATCH);
+            let mut candidate = head[hash(&data[i..])];
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || i - candidate > WINDOW {
+                    break;
+                }
+                let len = data[candidate..]
+                    .iter()
+                    .zip(&data[i..i + max])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best.0 {
+                    best = (len, i - candidate);
+                    if len == max {
+                        break;
+                    }
+                }
+                candidate = prev[candidate % WINDOW];
+            }
+        }
+        let (len, distance) = best;
+        if len >= MIN_MATCH {
+            writer.copy(len, distance);
+            for j in i..i + len {
+                insert(data, j, &mut head, &mut prev);
+            }
+            i += len;
+        } else {
+            writer.literal(data[i] as u32);
+            insert(data, i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+    writer.literal(256);
+    writer.finish()
+}
@@ -0,0 +1,78 @@
+use crate::*;
+
+const ORIENTATION: u16 = 0x0112;
+const X_RESOLUTION: u16 = 0x011a;
+const Y_RESOLUTION: u16 = 0x011b;
+const RESOLUTION_UNIT: u16 = 0x0128;
+
+pub(crate) struct Exif {
+    pub(crate) orientation: Orientation,
+    pub(crate) dpi: Option<(f32, f32)>,
+}
+
+// Reads the tags of the first IFD of a TIFF structure, the part of EXIF that describes the
+// main image.
+pub(crate) fn parse_exif(data: &[u8]) -> Result<Exif> {
+    let big_endian = match data.get(..4) {
+        Some(b"II*\0") => false,
+        Some(b"MM\0*") => true,
+        _ => return Err(Error::InvalidImage),
+    };
+    let u16_at = |i: usize| {
+        data.get(i..i + 2).map(|b| {
+            let b = [b[0], b[1]];
+            if big_endian {
+                u16::from_be_bytes(b)
+            } else {
+                u16::from_le_bytes(b)
+            }
+        })
+    };
+    let u32_at = |i: usize| {
+        data.get(i..i + 4).map(|b| {
+            let b = b.try_into().unwrap();
+            if big_endian {
+                u32::from_be_bytes(b)
+            } else {
+                u32::from_le_bytes(b)
+            }
+        })
+    };
+    let ifd = u32_at(4).ok_or(Error::InvalidImage)? as usize;
+    let count = u16_at(ifd).ok_or(Error::InvalidImage)? as usize;
+    let mut exif = Exif {
+        orientation: Orientation::Normal,
+        dpi: None,
+    };
+    let (mut x, mut y, mut unit) = (None, None, 2);
+    for entry in (0..count).map(|i| ifd + 2 + i * 12) {
+        let Some(tag) = u16_at(entry) else {
+            break;
+        };
+        let value = entry + 8;
+        // Rationals do not fit in an entry, so the entry holds their offset.
+        let rational = || {
+            let offset = u32_at(value)? as usize;
+            let (num, den) = (u32_at(offset)?, u32_at(offset + 4)?);
+            (den != 0).then(|| num as f32 / den as f32)
+        };
+        match tag {
+            ORIENTATION => {
+                exif.orientation = u16_at(value).map_or(Orientation::Normal, Orientation::from_exif)
+            }
+            X_RESOLUTION => x = rational(),
+            Y_RESOLUTION => y = rational(),
+            RESOLUTION_UNIT => unit = u16_at(value).unwrap_or(2),
+            _ => {}
+        }
+    }
+    let scale = match unit {
+        2 => Some(1.0),
+        3 => Some(2.54),
+        _ => None,
+    };
+    if let (Some(x), Some(y), Some(scale)) = (x, y, scale) {
+        exif.dpi = Some((x * scale, y * scale));
+    }
+    Ok(exif)
+}
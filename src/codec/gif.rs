@@ -0,0 +1,258 @@
+use crate::*;
+use std::time::Duration;
+
+const MAX_CODES: usize = 4096;
+// 8192 × 8192. Frames are cropped to the logical screen, so this bounds the pixels a frame
+// of a few bytes can make the decoder allocate.
+const MAX_SCREEN_PIXELS: u64 = 1 << 26;
+
+// Decodes LZW codes into palette indices, stopping at the end code, at the end of the data or
+// once `count` indices are out.
+fn lzw(data: &[u8], min_size: u8, count: usize) -> Result<Vec<u8>> {
+    if !(1..=11).contains(&min_size) {
+        return Err(Error::InvalidImage);
+    }
+    let clear = 1usize << min_size;
+    let end = clear + 1;
+    let mut prefix = [0u16; MAX_CODES];
+    let mut suffix = [0u8; MAX_CODES];
+    let mut first = [0u8; MAX_CODES];
+    let mut lens = [0u16; MAX_CODES];
+    for code in 0..clear {
+        suffix[code] = code as u8;
+        first[code] = code as u8;
+        lens[code] = 1;
+    }
+    let mut next = end + 1;
+    let mut width = min_size as u32 + 1;
+    let mut prev: Option<usize> = None;
+    // `count` comes from the image descriptor, so it is not trusted before the data is read.
+    let mut out = Vec::with_capacity(count.min(data.len()));
+    let (mut bits, mut bit_count, mut pos) = (0u32, 0u32, 0);
+    while out.len() < count {
+        while bit_count < width {
+            let Some(&byte) = data.get(pos) else {
+                return Ok(out);
+            };
+            bits |= (byte as u32) << bit_count;
+            bit_count += 8;
+            pos += 1;
+        }
+        let code = (bits & ((1 << width) - 1)) as usize;
+        bits >>= width;
+        bit_count -= width;
+        if code == clear {
+            next = end + 1;
+            width = min_size as u32 + 1;
+            prev = None;
+            continue;
+        }
+        if code == end {
+            break;
+        }
+        if let Some(prev) = prev {
+            // A code one past the table repeats the previous string plus its first index.
+            if code > next || (code == next && next == MAX_CODES) {
+                return Err(Error::InvalidImage);
+            }
+            if next < MAX_CODES {
+                prefix[next] = prev as u16;
+                suffix[next] = first[if code == next { prev } else { code }];
+                first[next] = first[prev];
+                lens[next] = lens[prev] + 1;
+                next += 1;
+                if next == 1 << width && width < 12 {
+                    width += 1;
+                }
+            }
+        } else if code >= clear {
+            return Err(Error::InvalidImage);
+        }
+        let start = out.len();
+        out.resize(start + lens[code] as usize, 0);
+        let mut c = code;
+        for i in (start..out.len()).rev() {
+            out[i] = suffix[c];
+            c = prefix[c] as usize;
+        }
+        prev = Some(code);
+    }
+    out.truncate(count);
+    Ok(out)
+}
+
+// Splits data sub-blocks, leaving `pos` after the terminator. Data that ends early yields the
+// blocks read so far, as browsers show truncated GIFs as far as they go.
+fn sub_blocks<'a>(data: &'a [u8], pos: &mut usize) -> Vec<&'a [u8]> {
+    let mut blocks = Vec::new();
+    while let Some(&len) = data.get(*pos) {
+        *pos += 1;
+        if len == 0 {
+            break;
+        }
+        let end = (*pos + len as usize).min(data.len());
+        blocks.push(&data[*pos..end]);
+        *pos = end;
+    }
+    blocks
+}
+
+fn palette(data: &[u8], pos: &mut usize, flags: u8) -> Result<Option<Vec<Rgba8>>> {
+    if flags & 0x80 == 0 {
+        return Ok(None);
+    }
+    let len = 3 << ((flags & 7) + 1);
+    let colors = data.get(*pos..*pos + len).ok_or(Error::InvalidImage)?;
+    *pos += len;
+    Ok(Some(
+        colors
+            .chunks_exact(3)
+            .map(|c| Rgba8::new(c[0], c[1], c[2], 255))
+            .collect(),
+    ))
+}
+
+// Rows of an interlaced image come in four passes.
+fn deinterlace(height: usize) -> Vec<usize> {
+    [(0, 8), (4, 8), (2, 4), (1, 2)]
+        .into_iter()
+        .flat_map(|(start, step)| (start..height).step_by(step))
+        .collect()
+}
+
+// Frames always blend over the canvas, since a GIF pixel is either opaque or transparent.
+// Frames that reach past the logical screen are cropped.
+pub(crate) fn decode_gif(data: &[u8]) -> Result<Animation> {
+    if data.len() < 13 || !(data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a")) {
+        return Err(Error::InvalidImage);
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]) as u32;
+    let size = Size::new(u16_at(6), u16_at(8));
+    if size.width as u64 * size.height as u64 > MAX_SCREEN_PIXELS {
+        return Err(Error::ImageTooLarge);
+    }
+    let mut pos = 13;
+    let global = palette(data, &mut pos, data[10])?;
+    let mut frames = Vec::new();
+    let mut loop_count = LoopCount::Finite(1);
+    let mut delay = Duration::ZERO;
+    let mut disposal = Disposal::None;
+    let mut transparent = None;
+    while let Some(&kind) = data.get(pos) {
+        pos += 1;
+        match kind {
+            0x21 => {
+                let label = *data.get(pos).ok_or(Error::InvalidImage)?;
+                pos += 1;
+                let blocks = sub_blocks(data, &mut pos);
+                match (label, blocks.as_slice()) {
+                    (0xf9, [control, ..]) if control.len() >= 4 => {
+                        disposal = match (control[0] >> 2) & 7 {
+                            2 => Disposal::Background,
+                            3 => Disposal::Previous,
+                            _ => Disposal::None,
+                        };
+                        delay = Duration::from_millis(
+                            u16::from_le_bytes([control[1], control[2]]) as u64 * 10,
+                        );
+                        transparent = (control[0] & 1 != 0).then_some(control[3]);
+                    }
+                    (0xff, [app, ext, ..])
+                        if *app == b"NETSCAPE2.0" && ext.len() >= 3 && ext[0] == 1 =>
+                    {
+                        // The count is of repeats after the first play.
+                        loop_count = match u16::from_le_bytes([ext[1], ext[2]]) {
+                            0 => LoopCount::Infinite,
+                            n => LoopCount::Finite(n as u32 + 1),
+                        };
+                    }
+                    _ => {}
+                }
+            }
+            0x2c => {
+                let descriptor = data.get(pos..pos + 9).ok_or(Error::InvalidImage)?;
+                let u16_at = |i: usize| u16::from_le_bytes([descriptor[i], descriptor[i + 1]]);
+                let (left, top) = (u16_at(0) as u32, u16_at(2) as u32);
+                let (width, height) = (u16_at(4) as usize, u16_at(6) as usize);
+                let flags = descriptor[8];
+                pos += 9;
+                let local = palette(data, &mut pos, flags)?;
+                let colors = local
+                    .as_ref()
+                    .or(global.as_ref())
+                    .ok_or(Error::InvalidImage)?;
+                let min_size = *data.get(pos).ok_or(Error::InvalidImage)?;
+                pos += 1;
+                let compressed = sub_blocks(data, &mut pos).concat();
+                let indices = lzw(&compressed, min_size, width * height)?;
+                let rows = if flags & 0x40 != 0 {
+                    deinterlace(height)
+                } else {
+                    (0..height).collect()
+                };
+                let rect = Rect::new(
+                    left.min(size.width),
+                    top.min(size.height),
+                    (left + width as u32).min(size.width),
+                    (top + height as u32).min(size.height),
+                );
+                let frame_width = (rect.right - rect.left) as usize;
+                let frame_height = (rect.bottom - rect.top) as usize;
+                let mut pixels = vec![Rgba8::default(); frame_width * frame_height];
+                // Missing indices at the end of truncated data stay transparent.
+                for (i, &index) in indices.iter().enumerate() {
+                    let (x, y) = (i % width, rows[i / width]);
+                    if x >= frame_width || y >= frame_height || transparent == Some(index) {
+                        continue;
+                    }
+                    if let Some(&color) = colors.get(index as usize) {
+                        pixels[y * frame_width + x] = color;
+                    }
+                }
+                frames.push(AnimationFrame {
+                    info: FrameInfo {
+                        rect,
+                        delay,
+                        disposal,
+                        blend: Blend::Over,
+                    },
+                    pixels,
+                });
+                delay = Duration::ZERO;
+                disposal = Disposal::None;
+                transparent = None;
+            }
+            0x3b => break,
+            _ => return Err(Error::InvalidImage),
+        }
+    }
+    if frames.is_empty() {
+        return Err(Error::InvalidImage);
+    }
+    Animation::new(size, frames, loop_count)
+}
+
+// The bit depth is the size of the global color table, or 8 when each frame brings its own.
+pub(crate) fn gif_metadata(data: &[u8]) -> Result<ImageMetadata> {
+    if data.len() < 13 || !(data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a")) {
+        return Err(Error::InvalidImage);
+    }
+    let flags = data[10];
+    Ok(ImageMetadata {
+        size: Size::new(
+            u16::from_le_bytes([data[6], data[7]]) as u32,
+            u16::from_le_bytes([data[8], data[9]]) as u32,
+        ),
+        pixel_format: SourcePixelFormat::new(
+            ColorModel::Indexed,
+            if flags & 0x80 != 0 {
+                (flags & 7) + 1
+            } else {
+                8
+            },
+        ),
+        dpi: None,
+        has_color_profile: false,
+        orientation: Orientation::Normal,
+    })
+}
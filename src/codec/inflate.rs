@@ -0,0 +1,220 @@
+use super::deflate::{DISTANCE_BASE, DISTANCE_EXTRA, LENGTH_BASE, LENGTH_EXTRA};
+use crate::*;
+
+const MAX_BITS: u32 = 15;
+// The order code length code lengths are stored in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u64,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    // Pads with zeros past the end so that `peek` can always look ahead; `consume` catches
+    // reads that go beyond the data.
+    fn refill(&mut self) {
+        while self.count <= 56 {
+            let byte = self.data.get(self.pos).copied().unwrap_or(0);
+            self.bits |= (byte as u64) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+    }
+
+    fn peek(&mut self, len: u32) -> u32 {
+        if self.count < len {
+            self.refill();
+        }
+        (self.bits & ((1 << len) - 1)) as u32
+    }
+
+    fn consume(&mut self, len: u32) -> Result<()> {
+        self.bits >>= len;
+        self.count -= len;
+        if self.pos * 8 - self.count as usize > self.data.len() * 8 {
+            return Err(Error::InvalidImage);
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, len: u32) -> Result<u32> {
+        let value = self.peek(len);
+        self.consume(len)?;
+        Ok(value)
+    }
+
+    fn align(&mut self) -> Result<()> {
+        self.consume(self.count % 8)
+    }
+}
+
+// Maps every `max_len`-bit pattern to the symbol whose code it starts with and that code's
+// length, so decoding a symbol is a single lookup.
+struct Huffman {
+    table: Vec<(u16, u8)>,
+    max_len: u32,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self> {
+        let max_len = lengths.iter().copied().max().unwrap_or(0) as u32;
+        let mut counts = [0u32; MAX_BITS as usize + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut next = [0u32; MAX_BITS as usize + 2];
+        for len in 1..=MAX_BITS as usize {
+            next[len + 1] = (next[len] + counts[len]) << 1;
+        }
+        // Oversubscribed code lengths cannot be decoded; incomplete ones only leave holes.
+        if (1..=max_len as usize).any(|len| next[len] + counts[len] > 1 << len) {
+            return Err(Error::InvalidImage);
+        }
+        let mut table = vec![(0, 0); 1 << max_len];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len == 0 {
+                continue;
+            }
+            let len = len as u32;
+            let code = next[len as usize];
+            next[len as usize] += 1;
+            let reversed = code.reverse_bits() >> (32 - len);
+            for fill in (reversed as usize..table.len()).step_by(1 << len) {
+                table[fill] = (symbol as u16, len as u8);
+            }
+        }
+        Ok(Self { table, max_len })
+    }
+
+    fn fixed_literals() -> Self {
+        let mut lengths = [8; 288];
+        lengths[144..256].fill(9);
+        lengths[256..280].fill(7);
+        Self::new(&lengths).unwrap()
+    }
+
+    fn fixed_distances() -> Self {
+        Self::new(&[5; 30]).unwrap()
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16> {
+        let (symbol, len) = self.table[reader.peek(self.max_len) as usize];
+        if len == 0 {
+            return Err(Error::InvalidImage);
+        }
+        reader.consume(len as u32)?;
+        Ok(symbol)
+    }
+}
+
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman)> {
+    let literals = reader.read(5)? as usize + 257;
+    let distances = reader.read(5)? as usize + 1;
+    let code_lengths = reader.read(4)? as usize + 4;
+    let mut lengths = [0; 19];
+    for &i in &CODE_LENGTH_ORDER[..code_lengths] {
+        lengths[i] = reader.read(3)? as u8;
+    }
+    let code_length = Huffman::new(&lengths)?;
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (value, repeat) = match code_length.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (
+                *lengths.last().ok_or(Error::InvalidImage)?,
+                3 + reader.read(2)?,
+            ),
+            17 => (0, 3 + reader.read(3)?),
+            _ => (0, 11 + reader.read(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literals + distances || lengths[256] == 0 {
+        return Err(Error::InvalidImage);
+    }
+    Ok((
+        Huffman::new(&lengths[..literals])?,
+        Huffman::new(&lengths[literals..])?,
+    ))
+}
+
+// Decodes a raw deflate stream, failing once the output would grow past `limit`. Also returns
+// how many bytes of `data` the stream took up.
+pub(super) fn inflate(data: &[u8], limit: usize) -> Result<(Vec<u8>, usize)> {
+    let mut reader = BitReader::new(data);
+    let mut out = Vec::new();
+    loop {
+        let last = reader.read(1)? == 1;
+        match reader.read(2)? {
+            0 => {
+                reader.align()?;
+                let len = reader.read(16)?;
+                if reader.read(16)? != !len & 0xffff || out.len() + len as usize > limit {
+                    return Err(Error::InvalidImage);
+                }
+                for _ in 0..len {
+                    out.push(reader.read(8)? as u8);
+                }
+            }
+            kind @ (1 | 2) => {
+                let (literals, distances) = if kind == 1 {
+                    (Huffman::fixed_literals(), Huffman::fixed_distances())
+                } else {
+                    dynamic_tables(&mut reader)?
+                };
+                loop {
+                    let symbol = literals.decode(&mut reader)? as usize;
+                    if symbol < 256 {
+                        if out.len() == limit {
+                            return Err(Error::InvalidImage);
+                        }
+                        out.push(symbol as u8);
+                        continue;
+                    }
+                    if symbol == 256 {
+                        break;
+                    }
+                    let i = symbol - 257;
+                    if i >= LENGTH_BASE.len() {
+                        return Err(Error::InvalidImage);
+                    }
+                    let len = LENGTH_BASE[i] as usize + reader.read(LENGTH_EXTRA[i])? as usize;
+                    let i = distances.decode(&mut reader)? as usize;
+                    if i >= DISTANCE_BASE.len() {
+                        return Err(Error::InvalidImage);
+                    }
+                    let distance =
+                        DISTANCE_BASE[i] as usize + reader.read(DISTANCE_EXTRA[i])? as usize;
+                    if distance > out.len() || out.len() + len > limit {
+                        return Err(Error::InvalidImage);
+                    }
+                    let start = out.len() - distance;
+                    // Copies byte by byte because the match may overlap what it produces.
+                    for j in start..start + len {
+                        out.push(out[j]);
+                    }
+                }
+            }
+            _ => return Err(Error::InvalidImage),
+        }
+        if last {
+            reader.align()?;
+            return Ok((out, reader.pos - reader.count as usize / 8));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use super::parse_exif;
+use crate::*;
+
+// Walks the marker segments up to the first scan, which is where JFIF, EXIF, ICC profiles and
+// the frame header live.
+pub(crate) fn jpeg_metadata(data: &[u8]) -> Result<ImageMetadata> {
+    if !data.starts_with(&[0xff, 0xd8]) {
+        return Err(Error::InvalidImage);
+    }
+    let mut frame = None;
+    let mut jfif_dpi = None;
+    let mut exif = None;
+    let mut has_color_profile = false;
+    let mut pos = 2;
+    loop {
+        // Markers may be padded with any number of 0xff bytes.
+        while data.get(pos) == Some(&0xff) && data.get(pos + 1) == Some(&0xff) {
+            pos += 1;
+        }
+        let marker = match data.get(pos..pos + 2) {
+            Some(&[0xff, marker]) => marker,
+            _ => return Err(Error::InvalidImage),
+        };
+        pos += 2;
+        if matches!(marker, 0x01 | 0xd0..=0xd7) {
+            continue;
+        }
+        if matches!(marker, 0xd9 | 0xda) {
+            break;
+        }
+        let len = data
+            .get(pos..pos + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+            .filter(|&len| len >= 2)
+            .ok_or(Error::InvalidImage)?;
+        let body = data.get(pos + 2..pos + len).ok_or(Error::InvalidImage)?;
+        pos += len;
+        match marker {
+            // Start of frame, other than DHT, JPG and DAC which share the range.
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                if body.len() < 6 {
+                    return Err(Error::InvalidImage);
+                }
+                frame = Some((
+                    body[0],
+                    Size::new(
+                        u16::from_be_bytes([body[3], body[4]]) as u32,
+                        u16::from_be_bytes([body[1], body[2]]) as u32,
+                    ),
+                    body[5],
+                ));
+            }
+            0xe0 if body.len() >= 12 && body.starts_with(b"JFIF\0") => {
+                let density = |i: usize| u16::from_be_bytes([body[i], body[i + 1]]) as f32;
+                let scale = match body[7] {
+                    1 => Some(1.0),
+                    2 => Some(2.54),
+                    _ => None,
+                };
+                jfif_dpi = scale.map(|scale| (density(8) * scale, density(10) * scale));
+            }
+            0xe1 if body.starts_with(b"Exif\0\0") => exif = parse_exif(&body[6..]).ok(),
+            0xe2 if body.starts_with(b"ICC_PROFILE\0") => has_color_profile = true,
+            _ => {}
+        }
+    }
+    let (precision, size, components) = frame.ok_or(Error::InvalidImage)?;
+    let model = match components {
+        1 => ColorModel::Gray,
+        3 => ColorModel::Rgb,
+        4 => ColorModel::Cmyk,
+        _ => return Err(Error::UnsupportedImage),
+    };
+    Ok(ImageMetadata {
+        size,
+        pixel_format: SourcePixelFormat::new(model, precision),
+        dpi: jfif_dpi.or(exif.as_ref().and_then(|exif| exif.dpi)),
+        has_color_profile,
+        orientation: exif.map_or(Orientation::Normal, |exif| exif.orientation),
+    })
+}
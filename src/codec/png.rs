@@ -0,0 +1,549 @@
+use super::{parse_exif, zlib};
+use crate::*;
+use std::time::Duration;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn filter(kind: u8, row: &[u8], prev: &[u8], out: &mut Vec<u8>) {
+    const BPP: usize = 4;
+    out.clear();
+    out.push(kind);
+    for (i, (&x, &b)) in row.iter().zip(prev).enumerate() {
+        let a = if i >= BPP { row[i - BPP] } else { 0 };
+        let c = if i >= BPP { prev[i - BPP] } else { 0 };
+        let predicted = match kind {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            _ => paeth(a, b, c),
+        };
+        out.push(x.wrapping_sub(predicted));
+    }
+}
+
+// Straight-alpha RGBA with 8 bits per channel. Each row takes the filter whose output has
+// the smallest sum of absolute differences, the heuristic the PNG specification suggests.
+pub(crate) fn encode_png(size: Size<u32>, pixels: &[Rgba8]) -> Vec<u8> {
+    let row_len = size.width as usize * 4;
+    let mut raw = Vec::with_capacity((row_len + 1) * size.height as usize);
+    let mut prev = vec![0; row_len];
+    let mut candidates: [Vec<u8>; 5] = Default::default();
+    for row in pixels.chunks(size.width.max(1) as usize) {
+        let row = Rgba8::as_bytes(row);
+        for (kind, candidate) in candidates.iter_mut().enumerate() {
+            filter(kind as u8, row, &prev, candidate);
+        }
+        let best = candidates
+            .iter()
+            .min_by_key(|candidate| {
+                candidate[1..]
+                    .iter()
+                    .map(|&byte| (byte as i8).unsigned_abs() as u32)
+                    .sum::<u32>()
+            })
+            .unwrap();
+        raw.extend(best);
+        prev.copy_from_slice(row);
+    }
+    let mut header = Vec::with_capacity(13);
+    header.extend(size.width.to_be_bytes());
+    header.extend(size.height.to_be_bytes());
+    header.extend([8, 6, 0, 0, 0]);
+    let mut out = SIGNATURE.to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &zlib::compress(&raw));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+pub(super) fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |c, &byte| {
+        CRC_TABLE[((c ^ byte as u32) & 0xff) as usize] ^ (c >> 8)
+    })
+}
+
+#[derive(Clone, Copy)]
+struct Header {
+    size: Size<u32>,
+    depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    // Bytes in a row of `width` pixels, not counting the filter type.
+    fn row_len(&self, width: u32) -> usize {
+        (width as usize * self.channels() * self.depth as usize).div_ceil(8)
+    }
+}
+
+fn parse_header(data: &[u8]) -> Result<Header> {
+    if data.len() != 13 {
+        return Err(Error::InvalidImage);
+    }
+    let header = Header {
+        size: Size::new(
+            u32::from_be_bytes(data[0..4].try_into().unwrap()),
+            u32::from_be_bytes(data[4..8].try_into().unwrap()),
+        ),
+        depth: data[8],
+        color_type: data[9],
+        interlaced: data[12] == 1,
+    };
+    let valid_depth = match header.color_type {
+        0 => matches!(header.depth, 1 | 2 | 4 | 8 | 16),
+        3 => matches!(header.depth, 1 | 2 | 4 | 8),
+        2 | 4 | 6 => matches!(header.depth, 8 | 16),
+        _ => false,
+    };
+    if !valid_depth
+        || header.size.width == 0
+        || header.size.height == 0
+        || data[10] != 0
+        || data[11] != 0
+        || data[12] > 1
+    {
+        return Err(Error::InvalidImage);
+    }
+    Ok(header)
+}
+
+// Adam7 passes as (x offset, y offset, x step, y step).
+const PASSES: [(u32, u32, u32, u32); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+fn passes(header: &Header) -> Vec<(u32, u32, u32, u32, Size<u32>)> {
+    let Size { width, height } = header.size;
+    if !header.interlaced {
+        return vec![(0, 0, 1, 1, header.size)];
+    }
+    PASSES
+        .iter()
+        .map(|&(x, y, dx, dy)| {
+            let size = Size::new(
+                width.saturating_sub(x).div_ceil(dx),
+                height.saturating_sub(y).div_ceil(dy),
+            );
+            (x, y, dx, dy, size)
+        })
+        .filter(|(.., size)| size.width > 0 && size.height > 0)
+        .collect()
+}
+
+fn unfilter(kind: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> Result<()> {
+    for i in 0..row.len() {
+        let a = if i >= bpp { row[i - bpp] } else { 0 };
+        let b = prev[i];
+        let c = if i >= bpp { prev[i - bpp] } else { 0 };
+        let predicted = match kind {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            4 => paeth(a, b, c),
+            _ => return Err(Error::InvalidImage),
+        };
+        row[i] = row[i].wrapping_add(predicted);
+    }
+    Ok(())
+}
+
+// Reads sample `i` of a row, scaled to 8 bits. Sixteen-bit samples keep their high byte.
+fn sample(row: &[u8], i: usize, depth: u8) -> u8 {
+    match depth {
+        8 => row[i],
+        16 => row[i * 2],
+        _ => {
+            let per_byte = 8 / depth as usize;
+            let shift = 8 - depth as usize * (i % per_byte + 1);
+            let value = (row[i / per_byte] >> shift) & ((1 << depth) - 1);
+            (value as u32 * 255 / ((1 << depth) - 1)) as u8
+        }
+    }
+}
+
+// Reads a raw sample value, which is what tRNS keys compare against.
+fn raw_sample(row: &[u8], i: usize, depth: u8) -> u16 {
+    match depth {
+        8 => row[i] as u16,
+        16 => u16::from_be_bytes([row[i * 2], row[i * 2 + 1]]),
+        _ => {
+            let per_byte = 8 / depth as usize;
+            let shift = 8 - depth as usize * (i % per_byte + 1);
+            ((row[i / per_byte] >> shift) & ((1 << depth) - 1)) as u16
+        }
+    }
+}
+
+// Placement and timing of an APNG frame from its fcTL chunk.
+struct FrameControl {
+    info: FrameInfo,
+    data: Vec<u8>,
+}
+
+fn parse_frame_control(body: &[u8], size: Size<u32>, first: bool) -> Result<FrameInfo> {
+    if body.len() != 26 {
+        return Err(Error::InvalidImage);
+    }
+    let u32_at = |i: usize| u32::from_be_bytes(body[i..i + 4].try_into().unwrap());
+    let u16_at = |i: usize| u16::from_be_bytes([body[i], body[i + 1]]);
+    let (width, height, x, y) = (u32_at(4), u32_at(8), u32_at(12), u32_at(16));
+    let rect = Rect::new(
+        x,
+        y,
+        x.checked_add(width).ok_or(Error::InvalidImage)?,
+        y.checked_add(height).ok_or(Error::InvalidImage)?,
+    );
+    if width == 0 || height == 0 || region_size(rect, size).is_err() {
+        return Err(Error::InvalidImage);
+    }
+    // A zero denominator means hundredths of a second.
+    let denominator = match u16_at(22) {
+        0 => 100,
+        d => d as u64,
+    };
+    let disposal = match body[24] {
+        0 => Disposal::None,
+        1 => Disposal::Background,
+        // The first frame has nothing to go back to but the cleared canvas.
+        2 if first => Disposal::Background,
+        2 => Disposal::Previous,
+        _ => return Err(Error::InvalidImage),
+    };
+    let blend = match body[25] {
+        0 => Blend::Source,
+        1 => Blend::Over,
+        _ => return Err(Error::InvalidImage),
+    };
+    Ok(FrameInfo {
+        rect,
+        delay: Duration::from_nanos(u16_at(20) as u64 * 1_000_000_000 / denominator),
+        disposal,
+        blend,
+    })
+}
+
+struct Png {
+    header: Header,
+    palette: Vec<Rgba8>,
+    // Gray or RGB samples that tRNS marks as fully transparent.
+    key: Option<Vec<u16>>,
+    data: Vec<u8>,
+    // The number of plays from acTL.
+    plays: Option<u32>,
+    frames: Vec<FrameControl>,
+}
+
+// Splits the chunks up to IEND into their types and bodies, checking their CRCs.
+fn chunks(data: &[u8]) -> Result<Vec<(&[u8], &[u8])>> {
+    if data.get(..8) != Some(&SIGNATURE) {
+        return Err(Error::InvalidImage);
+    }
+    let mut pos = 8;
+    let mut chunks = Vec::new();
+    loop {
+        let len = data
+            .get(pos..pos + 4)
+            .map(|b| u32::from_be_bytes(b.try_into().unwrap()) as usize)
+            .ok_or(Error::InvalidImage)?;
+        let chunk = data
+            .get(pos + 4..pos + 8 + len)
+            .ok_or(Error::InvalidImage)?;
+        let crc = data
+            .get(pos + 8 + len..pos + 12 + len)
+            .ok_or(Error::InvalidImage)?;
+        if crc != crc32(chunk).to_be_bytes() {
+            return Err(Error::InvalidImage);
+        }
+        pos += 12 + len;
+        let (kind, body) = chunk.split_at(4);
+        if kind == b"IEND" {
+            return Ok(chunks);
+        }
+        chunks.push((kind, body));
+    }
+}
+
+fn parse(data: &[u8]) -> Result<Png> {
+    let chunks = chunks(data)?;
+    let header = match chunks.first() {
+        Some((b"IHDR", body)) => parse_header(body)?,
+        _ => return Err(Error::InvalidImage),
+    };
+    let mut palette: Vec<Rgba8> = Vec::new();
+    let mut transparency = None;
+    let mut compressed = Vec::new();
+    let mut plays = None;
+    let mut frames: Vec<FrameControl> = Vec::new();
+    for &(kind, body) in &chunks[1..] {
+        match kind {
+            b"PLTE" => {
+                if body.len() % 3 != 0 || body.len() > 256 * 3 {
+                    return Err(Error::InvalidImage);
+                }
+                palette = body
+                    .chunks_exact(3)
+                    .map(|c| Rgba8::new(c[0], c[1], c[2], 255))
+                    .collect();
+            }
+            b"tRNS" => transparency = Some(body),
+            b"IDAT" => {
+                compressed.extend_from_slice(body);
+                // An fcTL before the image data makes it the first frame.
+                if let [frame] = frames.as_mut_slice() {
+                    frame.data.extend_from_slice(body);
+                }
+            }
+            b"acTL" if body.len() == 8 => {
+                plays = Some(u32::from_be_bytes(body[4..8].try_into().unwrap()));
+            }
+            b"fcTL" => frames.push(FrameControl {
+                info: parse_frame_control(body, header.size, frames.is_empty())?,
+                data: Vec::new(),
+            }),
+            b"fdAT" => frames
+                .last_mut()
+                .filter(|_| body.len() >= 4)
+                .ok_or(Error::InvalidImage)?
+                .data
+                .extend_from_slice(&body[4..]),
+            _ if kind[0] & 0x20 == 0 => return Err(Error::UnsupportedImage),
+            _ => {}
+        }
+    }
+    if header.color_type == 3 {
+        if palette.is_empty() {
+            return Err(Error::InvalidImage);
+        }
+        if let Some(alpha) = transparency {
+            for (color, &a) in palette.iter_mut().zip(alpha) {
+                color.a = a;
+            }
+        }
+    }
+    let key = transparency
+        .filter(|_| matches!(header.color_type, 0 | 2))
+        .map(|t| {
+            t.chunks_exact(2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .collect()
+        });
+    Ok(Png {
+        header,
+        palette,
+        key,
+        data: compressed,
+        plays,
+        frames,
+    })
+}
+
+impl Png {
+    fn decode(&self, size: Size<u32>, compressed: &[u8]) -> Result<Vec<Rgba8>> {
+        let header = Header {
+            size,
+            ..self.header
+        };
+        let passes = passes(&header);
+        let expected = passes
+            .iter()
+            .map(|(.., size)| (header.row_len(size.width) + 1) * size.height as usize)
+            .sum();
+        let raw = zlib::decompress(compressed, expected)?;
+        if raw.len() != expected {
+            return Err(Error::InvalidImage);
+        }
+        let Size { width, height } = size;
+        let mut pixels = vec![Rgba8::default(); width as usize * height as usize];
+        let channels = header.channels();
+        let bpp = (channels * header.depth as usize).div_ceil(8);
+        let mut raw = raw.as_slice();
+        for (x0, y0, dx, dy, size) in passes {
+            let row_len = header.row_len(size.width);
+            let mut prev = vec![0; row_len];
+            for y in 0..size.height {
+                let (row, rest) = raw.split_at(row_len + 1);
+                raw = rest;
+                let (&kind, row) = row.split_first().unwrap();
+                let mut row = row.to_vec();
+                unfilter(kind, &mut row, &prev, bpp)?;
+                for x in 0..size.width as usize {
+                    let i = x * channels;
+                    let s = |c: usize| sample(&row, i + c, header.depth);
+                    let pixel = match header.color_type {
+                        0 => Rgba8::new(s(0), s(0), s(0), 255),
+                        2 => Rgba8::new(s(0), s(1), s(2), 255),
+                        3 => *self
+                            .palette
+                            .get(raw_sample(&row, x, header.depth) as usize)
+                            .ok_or(Error::InvalidImage)?,
+                        4 => Rgba8::new(s(0), s(0), s(0), s(1)),
+                        _ => Rgba8::new(s(0), s(1), s(2), s(3)),
+                    };
+                    let transparent = self.key.as_ref().is_some_and(|key| {
+                        key.len() == channels
+                            && (0..channels)
+                                .all(|c| raw_sample(&row, i + c, header.depth) == key[c])
+                    });
+                    let index =
+                        (y0 + y * dy) as usize * width as usize + (x0 + x as u32 * dx) as usize;
+                    pixels[index] = if transparent {
+                        Rgba8::new(pixel.r, pixel.g, pixel.b, 0)
+                    } else {
+                        pixel
+                    };
+                }
+                prev = row;
+            }
+        }
+        Ok(pixels)
+    }
+}
+
+// Decodes any conforming PNG into straight-alpha RGBA. Ancillary chunks other than tRNS are
+// ignored, so gamma and color profiles are not applied.
+pub(crate) fn decode_png(data: &[u8]) -> Result<(Size<u32>, Vec<Rgba8>)> {
+    let png = parse(data)?;
+    let size = png.header.size;
+    Ok((size, png.decode(size, &png.data)?))
+}
+
+// Falls back to the default image as a single frame when there is no acTL. The default image
+// is not part of the animation unless an fcTL comes before it.
+pub(crate) fn decode_apng(data: &[u8]) -> Result<Animation> {
+    let png = parse(data)?;
+    let size = png.header.size;
+    let Some(plays) = png.plays.filter(|_| !png.frames.is_empty()) else {
+        let frame = AnimationFrame {
+            info: FrameInfo {
+                rect: Rect::new(0, 0, size.width, size.height),
+                delay: Duration::ZERO,
+                disposal: Disposal::None,
+                blend: Blend::Source,
+            },
+            pixels: png.decode(size, &png.data)?,
+        };
+        return Animation::new(size, vec![frame], LoopCount::Finite(1));
+    };
+    let frames = png
+        .frames
+        .iter()
+        .map(|frame| {
+            let rect = frame.info.rect;
+            let frame_size = Size::new(rect.right - rect.left, rect.bottom - rect.top);
+            Ok(AnimationFrame {
+                info: frame.info,
+                pixels: png.decode(frame_size, &frame.data)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let loop_count = match plays {
+        0 => LoopCount::Infinite,
+        n => LoopCount::Finite(n),
+    };
+    Animation::new(size, frames, loop_count)
+}
+
+// Reads IHDR, pHYs, iCCP and eXIf. Only pHYs in meters gives a resolution; the unknown unit
+// records an aspect ratio alone.
+pub(crate) fn png_metadata(data: &[u8]) -> Result<ImageMetadata> {
+    let chunks = chunks(data)?;
+    let header = match chunks.first() {
+        Some((b"IHDR", body)) => parse_header(body)?,
+        _ => return Err(Error::InvalidImage),
+    };
+    let model = match header.color_type {
+        0 => ColorModel::Gray,
+        2 => ColorModel::Rgb,
+        3 => ColorModel::Indexed,
+        4 => ColorModel::GrayAlpha,
+        _ => ColorModel::Rgba,
+    };
+    let mut metadata = ImageMetadata {
+        size: header.size,
+        pixel_format: SourcePixelFormat::new(model, header.depth),
+        dpi: None,
+        has_color_profile: false,
+        orientation: Orientation::Normal,
+    };
+    for &(kind, body) in &chunks[1..] {
+        match kind {
+            b"pHYs" if body.len() == 9 && body[8] == 1 => {
+                let per_meter = |i: usize| {
+                    u32::from_be_bytes(body[i..i + 4].try_into().unwrap()) as f32 * 0.0254
+                };
+                metadata.dpi = Some((per_meter(0), per_meter(4)));
+            }
+            b"iCCP" => metadata.has_color_profile = true,
+            b"eXIf" => {
+                if let Ok(exif) = parse_exif(body) {
+                    metadata.orientation = exif.orientation;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(metadata)
+}
@@ -0,0 +1,154 @@
+use crate::*;
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xc0;
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+const END: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+fn index_of(pixel: Rgba8) -> usize {
+    (pixel.r as usize * 3 + pixel.g as usize * 5 + pixel.b as usize * 7 + pixel.a as usize * 11)
+        % 64
+}
+
+// Straight-alpha RGBA in sRGB, as the QOI specification describes.
+pub(crate) fn encode_qoi(size: Size<u32>, pixels: &[Rgba8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(14 + pixels.len() * 2 + END.len());
+    out.extend(b"qoif");
+    out.extend(size.width.to_be_bytes());
+    out.extend(size.height.to_be_bytes());
+    out.extend([4, 0]);
+    let mut seen = [Rgba8::default(); 64];
+    let mut prev = Rgba8::new(0, 0, 0, 255);
+    let mut run = 0u8;
+    for &pixel in pixels {
+        if pixel == prev {
+            run += 1;
+            if run == 62 {
+                out.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+            continue;
+        }
+        if run > 0 {
+            out.push(OP_RUN | (run - 1));
+            run = 0;
+        }
+        let index = index_of(pixel);
+        if seen[index] == pixel {
+            out.push(OP_INDEX | index as u8);
+        } else if pixel.a == prev.a {
+            let dr = pixel.r.wrapping_sub(prev.r) as i8;
+            let dg = pixel.g.wrapping_sub(prev.g) as i8;
+            let db = pixel.b.wrapping_sub(prev.b) as i8;
+            let dr_dg = dr.wrapping_sub(dg);
+            let db_dg = db.wrapping_sub(dg);
+            if [dr, dg, db].iter().all(|d| (-2..=1).contains(d)) {
+                out.push(OP_DIFF | ((dr + 2) as u8) << 4 | ((dg + 2) as u8) << 2 | (db + 2) as u8);
+            } else if (-32..=31).contains(&dg)
+                && (-8..=7).contains(&dr_dg)
+                && (-8..=7).contains(&db_dg)
+            {
+                out.push(OP_LUMA | (dg + 32) as u8);
+                out.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+            } else {
+                out.extend([OP_RGB, pixel.r, pixel.g, pixel.b]);
+            }
+        } else {
+            out.extend([OP_RGBA, pixel.r, pixel.g, pixel.b, pixel.a]);
+        }
+        seen[index] = pixel;
+        prev = pixel;
+    }
+    if run > 0 {
+        out.push(OP_RUN | (run - 1));
+    }
+    out.extend(END);
+    out
+}
+
+pub(crate) fn decode_qoi(data: &[u8]) -> Result<(Size<u32>, Vec<Rgba8>)> {
+    if data.len() < 14 + END.len() || &data[..4] != b"qoif" {
+        return Err(Error::InvalidImage);
+    }
+    let size = Size::new(
+        u32::from_be_bytes(data[4..8].try_into().unwrap()),
+        u32::from_be_bytes(data[8..12].try_into().unwrap()),
+    );
+    if !matches!(data[12], 3 | 4) || data[13] > 1 {
+        return Err(Error::InvalidImage);
+    }
+    let count = size.width as usize * size.height as usize;
+    // Every op yields at least one pixel and runs yield up to 62, which bounds the size of a
+    // valid image by its data.
+    if count > (data.len() - 14) * 62 {
+        return Err(Error::InvalidImage);
+    }
+    let mut pixels = Vec::with_capacity(count);
+    let mut seen = [Rgba8::default(); 64];
+    let mut pixel = Rgba8::new(0, 0, 0, 255);
+    let body = &data[14..data.len() - END.len()];
+    let mut pos = 0;
+    let mut next = || {
+        let byte = body.get(pos).copied().ok_or(Error::InvalidImage);
+        pos += 1;
+        byte
+    };
+    while pixels.len() < count {
+        let op = next()?;
+        match op {
+            OP_RGB => pixel = Rgba8::new(next()?, next()?, next()?, pixel.a),
+            OP_RGBA => pixel = Rgba8::new(next()?, next()?, next()?, next()?),
+            _ => match op & 0xc0 {
+                OP_INDEX => pixel = seen[op as usize],
+                OP_DIFF => {
+                    let d = |shift: u8| ((op >> shift) & 3).wrapping_sub(2);
+                    pixel.r = pixel.r.wrapping_add(d(4));
+                    pixel.g = pixel.g.wrapping_add(d(2));
+                    pixel.b = pixel.b.wrapping_add(d(0));
+                }
+                OP_LUMA => {
+                    let dg = (op & 0x3f).wrapping_sub(32);
+                    let byte = next()?;
+                    pixel.r = pixel
+                        .r
+                        .wrapping_add(dg.wrapping_add(byte >> 4).wrapping_sub(8));
+                    pixel.g = pixel.g.wrapping_add(dg);
+                    pixel.b = pixel
+                        .b
+                        .wrapping_add(dg.wrapping_add(byte & 0x0f).wrapping_sub(8));
+                }
+                _ => {
+                    let run = ((op & 0x3f) as usize).min(count - pixels.len() - 1);
+                    pixels.extend(std::iter::repeat_n(pixel, run));
+                }
+            },
+        }
+        seen[index_of(pixel)] = pixel;
+        pixels.push(pixel);
+    }
+    Ok((size, pixels))
+}
+
+pub(crate) fn qoi_metadata(data: &[u8]) -> Result<ImageMetadata> {
+    if data.len() < 14 || &data[..4] != b"qoif" {
+        return Err(Error::InvalidImage);
+    }
+    let model = match data[12] {
+        3 => ColorModel::Rgb,
+        4 => ColorModel::Rgba,
+        _ => return Err(Error::InvalidImage),
+    };
+    Ok(ImageMetadata {
+        size: Size::new(
+            u32::from_be_bytes(data[4..8].try_into().unwrap()),
+            u32::from_be_bytes(data[8..12].try_into().unwrap()),
+        ),
+        pixel_format: SourcePixelFormat::new(model, 8),
+        dpi: None,
+        has_color_profile: false,
+        orientation: Orientation::Normal,
+    })
+}
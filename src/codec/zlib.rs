@@ -0,0 +1,70 @@
+use super::deflate::deflate_fixed;
+use super::inflate::inflate;
+use crate::*;
+
+const MAX_BLOCK: usize = 0xffff;
+
+// Wraps `data` in a zlib stream, falling back to stored blocks when matching does not pay off.
+pub(crate) fn compress(data: &[u8]) -> Vec<u8> {
+    let stored_len = data.len() + data.len().div_ceil(MAX_BLOCK).max(1) * 5;
+    let mut out = Vec::with_capacity(stored_len + 6);
+    out.extend([0x78, 0x01]);
+    let compressed = deflate_fixed(data);
+    if compressed.len() < stored_len {
+        out.extend(compressed);
+    } else {
+        store(data, &mut out);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+// Checks the header and checksum around the deflate stream. Preset dictionaries are not
+// supported.
+pub(crate) fn decompress(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let [cmf, flg, ..] = *data else {
+        return Err(Error::InvalidImage);
+    };
+    if cmf & 0x0f != 8
+        || cmf >> 4 > 7
+        || flg & 0x20 != 0
+        || u16::from_be_bytes([cmf, flg]) % 31 != 0
+    {
+        return Err(Error::InvalidImage);
+    }
+    let (out, len) = inflate(&data[2..], limit)?;
+    let checksum = data.get(2 + len..2 + len + 4).ok_or(Error::InvalidImage)?;
+    if checksum != adler32(&out).to_be_bytes() {
+        return Err(Error::InvalidImage);
+    }
+    Ok(out)
+}
+
+fn store(data: &[u8], out: &mut Vec<u8>) {
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        out.push(blocks.peek().is_none() as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+}
+
+pub(super) fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before `b` could overflow.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
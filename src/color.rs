@@ -1,8 +1,19 @@
+mod composite;
+mod css;
+mod named;
+mod rgba8;
+mod space;
+
+pub use composite::*;
+use named::NAMED_COLORS;
+pub use rgba8::*;
+pub use space::*;
+#[cfg(windows)]
 use windows::Win32::Graphics::Direct2D::Common::*;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(C)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Rgba {
     pub r: f32,
     pub g: f32,
@@ -16,6 +27,7 @@ impl Rgba {
     }
 }
 
+#[cfg(windows)]
 impl From<D2D1_COLOR_F> for Rgba {
     #[inline]
     fn from(value: D2D1_COLOR_F) -> Self {
@@ -28,6 +40,7 @@ impl From<D2D1_COLOR_F> for Rgba {
     }
 }
 
+#[cfg(windows)]
 impl From<Rgba> for D2D1_COLOR_F {
     #[inline]
     fn from(value: Rgba) -> Self {
@@ -51,3 +64,69 @@ impl From<(f32, f32, f32, f32)> for Rgba {
         }
     }
 }
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Rgba {
+    fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::{self, MapAccess, SeqAccess};
+
+        const FIELDS: &[&str] = &["r", "g", "b", "a"];
+
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Rgba;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a CSS color string or a struct with r, g, b and a")
+            }
+
+            fn visit_str<E>(self, v: &str) -> ::core::result::Result<Rgba, E>
+            where
+                E: de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> ::core::result::Result<Rgba, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut c = [0.0f32; 4];
+                for (i, c) in c.iter_mut().enumerate() {
+                    *c = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                Ok(Rgba::new(c[0], c[1], c[2], c[3]))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> ::core::result::Result<Rgba, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut c = [None; 4];
+                while let Some(key) = map.next_key::<String>()? {
+                    let Some(i) = FIELDS.iter().position(|f| *f == key) else {
+                        return Err(de::Error::unknown_field(&key, FIELDS));
+                    };
+                    if c[i].is_some() {
+                        return Err(de::Error::duplicate_field(FIELDS[i]));
+                    }
+                    c[i] = Some(map.next_value::<f32>()?);
+                }
+                let get = |i: usize| c[i].ok_or_else(|| de::Error::missing_field(FIELDS[i]));
+                Ok(Rgba::new(get(0)?, get(1)?, get(2)?, get(3)?))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(Visitor)
+        } else {
+            deserializer.deserialize_struct("Rgba", FIELDS, Visitor)
+        }
+    }
+}
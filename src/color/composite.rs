@@ -0,0 +1,366 @@
+use super::Rgba;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PremulRgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl PremulRgba {
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    #[inline]
+    pub fn unpremultiply(self) -> Rgba {
+        if self.a <= 0.0 {
+            return Rgba::TRANSPARENT;
+        }
+        Rgba::new(self.r / self.a, self.g / self.a, self.b / self.a, self.a)
+    }
+
+    #[inline]
+    pub fn composite(self, dest: PremulRgba, mode: CompositeMode) -> PremulRgba {
+        let (fs, fd) = mode.factors(self.a, dest.a);
+        let f = |s: f32, d: f32| {
+            let v = s * fs + d * fd;
+            if mode == CompositeMode::Plus {
+                v.min(1.0)
+            } else {
+                v
+            }
+        };
+        PremulRgba::new(
+            f(self.r, dest.r),
+            f(self.g, dest.g),
+            f(self.b, dest.b),
+            f(self.a, dest.a),
+        )
+    }
+
+    pub fn blend(self, dest: PremulRgba, mode: BlendMode) -> PremulRgba {
+        let src = self.unpremultiply();
+        let backdrop = dest.unpremultiply();
+        let [r, g, b] = mode.apply([backdrop.r, backdrop.g, backdrop.b], [src.r, src.g, src.b]);
+        let sa = self.a;
+        let da = dest.a;
+        let f = |s: f32, d: f32, mixed: f32| s * (1.0 - da) + d * (1.0 - sa) + sa * da * mixed;
+        PremulRgba::new(
+            f(self.r, dest.r, r),
+            f(self.g, dest.g, g),
+            f(self.b, dest.b, b),
+            sa + da - sa * da,
+        )
+    }
+}
+
+impl Rgba {
+    #[inline]
+    pub fn premultiply(self) -> PremulRgba {
+        PremulRgba::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+}
+
+impl From<Rgba> for PremulRgba {
+    #[inline]
+    fn from(value: Rgba) -> Self {
+        value.premultiply()
+    }
+}
+
+impl From<PremulRgba> for Rgba {
+    #[inline]
+    fn from(value: PremulRgba) -> Self {
+        value.unpremultiply()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompositeMode {
+    Clear,
+    Source,
+    Destination,
+    SourceOver,
+    DestinationOver,
+    SourceIn,
+    DestinationIn,
+    SourceOut,
+    DestinationOut,
+    SourceAtop,
+    DestinationAtop,
+    Xor,
+    Plus,
+}
+
+impl CompositeMode {
+    fn factors(self, sa: f32, da: f32) -> (f32, f32) {
+        match self {
+            Self::Clear => (0.0, 0.0),
+            Self::Source => (1.0, 0.0),
+            Self::Destination => (0.0, 1.0),
+            Self::SourceOver => (1.0, 1.0 - sa),
+            Self::DestinationOver => (1.0 - da, 1.0),
+            Self::SourceIn => (da, 0.0),
+            Self::DestinationIn => (0.0, sa),
+            Self::SourceOut => (1.0 - da, 0.0),
+            Self::DestinationOut => (0.0, 1.0 - sa),
+            Self::SourceAtop => (da, 1.0 - sa),
+            Self::DestinationAtop => (1.0 - da, sa),
+            Self::Xor => (1.0 - da, 1.0 - sa),
+            Self::Plus => (1.0, 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl BlendMode {
+    fn apply(self, cb: [f32; 3], cs: [f32; 3]) -> [f32; 3] {
+        let separable = |f: fn(f32, f32) -> f32| std::array::from_fn(|i| f(cb[i], cs[i]));
+        match self {
+            Self::Normal => cs,
+            Self::Multiply => separable(multiply),
+            Self::Screen => separable(screen),
+            Self::Overlay => separable(|b, s| hard_light(s, b)),
+            Self::Darken => separable(f32::min),
+            Self::Lighten => separable(f32::max),
+            Self::ColorDodge => separable(color_dodge),
+            Self::ColorBurn => separable(color_burn),
+            Self::HardLight => separable(hard_light),
+            Self::SoftLight => separable(soft_light),
+            Self::Difference => separable(|b, s| (b - s).abs()),
+            Self::Exclusion => separable(|b, s| b + s - 2.0 * b * s),
+            Self::Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+            Self::Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+            Self::Color => set_lum(cs, lum(cb)),
+            Self::Luminosity => set_lum(cb, lum(cs)),
+        }
+    }
+}
+
+fn multiply(b: f32, s: f32) -> f32 {
+    b * s
+}
+
+fn screen(b: f32, s: f32) -> f32 {
+    b + s - b * s
+}
+
+fn hard_light(b: f32, s: f32) -> f32 {
+    if s <= 0.5 {
+        multiply(b, 2.0 * s)
+    } else {
+        screen(b, 2.0 * s - 1.0)
+    }
+}
+
+fn color_dodge(b: f32, s: f32) -> f32 {
+    if b <= 0.0 {
+        0.0
+    } else if s >= 1.0 {
+        1.0
+    } else {
+        (b / (1.0 - s)).min(1.0)
+    }
+}
+
+fn color_burn(b: f32, s: f32) -> f32 {
+    if b >= 1.0 {
+        1.0
+    } else if s <= 0.0 {
+        0.0
+    } else {
+        1.0 - ((1.0 - b) / s).min(1.0)
+    }
+}
+
+fn soft_light(b: f32, s: f32) -> f32 {
+    if s <= 0.5 {
+        b - (1.0 - 2.0 * s) * b * (1.0 - b)
+    } else {
+        let d = if b <= 0.25 {
+            ((16.0 * b - 12.0) * b + 4.0) * b
+        } else {
+            b.sqrt()
+        };
+        b + (2.0 * s - 1.0) * (d - b)
+    }
+}
+
+fn lum([r, g, b]: [f32; 3]) -> f32 {
+    0.3 * r + 0.59 * g + 0.11 * b
+}
+
+fn clip_color(c: [f32; 3]) -> [f32; 3] {
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    c.map(|v| {
+        let mut v = v;
+        if n < 0.0 {
+            v = l + (v - l) * l / (l - n);
+        }
+        if x > 1.0 {
+            v = l + (v - l) * (1.0 - l) / (x - l);
+        }
+        v
+    })
+}
+
+fn set_lum(c: [f32; 3], l: f32) -> [f32; 3] {
+    let d = l - lum(c);
+    clip_color(c.map(|v| v + d))
+}
+
+fn sat([r, g, b]: [f32; 3]) -> f32 {
+    r.max(g).max(b) - r.min(g).min(b)
+}
+
+fn set_sat(c: [f32; 3], s: f32) -> [f32; 3] {
+    let mut order = [0, 1, 2];
+    order.sort_by(|&i, &j| c[i].total_cmp(&c[j]));
+    let [min, mid, max] = order;
+    let mut ret = [0.0; 3];
+    if c[max] > c[min] {
+        ret[mid] = (c[mid] - c[min]) * s / (c[max] - c[min]);
+        ret[max] = s;
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: PremulRgba, expected: [f32; 4]) {
+        let actual = [actual.r, actual.g, actual.b, actual.a];
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(a, e)| (a - e).abs() < 1e-5),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    fn opaque([r, g, b]: [f32; 3]) -> PremulRgba {
+        PremulRgba::new(r, g, b, 1.0)
+    }
+
+    #[test]
+    fn porter_duff_modes() {
+        // Half transparent red over three quarters opaque blue.
+        let src = Rgba::new(1.0, 0.0, 0.0, 0.5).premultiply();
+        let dest = Rgba::new(0.0, 0.0, 1.0, 0.75).premultiply();
+        let cases = [
+            (CompositeMode::Clear, [0.0, 0.0, 0.0, 0.0]),
+            (CompositeMode::Source, [0.5, 0.0, 0.0, 0.5]),
+            (CompositeMode::Destination, [0.0, 0.0, 0.75, 0.75]),
+            (CompositeMode::SourceOver, [0.5, 0.0, 0.375, 0.875]),
+            (CompositeMode::DestinationOver, [0.125, 0.0, 0.75, 0.875]),
+            (CompositeMode::SourceIn, [0.375, 0.0, 0.0, 0.375]),
+            (CompositeMode::DestinationIn, [0.0, 0.0, 0.375, 0.375]),
+            (CompositeMode::SourceOut, [0.125, 0.0, 0.0, 0.125]),
+            (CompositeMode::DestinationOut, [0.0, 0.0, 0.375, 0.375]),
+            (CompositeMode::SourceAtop, [0.375, 0.0, 0.375, 0.75]),
+            (CompositeMode::DestinationAtop, [0.125, 0.0, 0.375, 0.5]),
+            (CompositeMode::Xor, [0.125, 0.0, 0.375, 0.5]),
+            // Plus saturates instead of exceeding full coverage.
+            (CompositeMode::Plus, [0.5, 0.0, 0.75, 1.0]),
+        ];
+        for (mode, expected) in cases {
+            assert_near(src.composite(dest, mode), expected);
+        }
+    }
+
+    #[test]
+    fn separable_blend_modes() {
+        let backdrop = opaque([0.2, 0.5, 0.8]);
+        let src = opaque([0.6, 0.3, 0.9]);
+        let cases = [
+            (BlendMode::Normal, [0.6, 0.3, 0.9]),
+            (BlendMode::Multiply, [0.12, 0.15, 0.72]),
+            (BlendMode::Screen, [0.68, 0.65, 0.98]),
+            (BlendMode::Overlay, [0.24, 0.3, 0.96]),
+            (BlendMode::Darken, [0.2, 0.3, 0.8]),
+            (BlendMode::Lighten, [0.6, 0.5, 0.9]),
+            (BlendMode::ColorDodge, [0.5, 0.714_286, 1.0]),
+            (BlendMode::ColorBurn, [0.0, 0.0, 0.777_778]),
+            (BlendMode::HardLight, [0.36, 0.3, 0.96]),
+            (BlendMode::SoftLight, [0.2496, 0.4, 0.875_542]),
+            (BlendMode::Difference, [0.4, 0.2, 0.1]),
+            (BlendMode::Exclusion, [0.56, 0.5, 0.26]),
+        ];
+        for (mode, [r, g, b]) in cases {
+            assert_near(src.blend(backdrop, mode), [r, g, b, 1.0]);
+        }
+    }
+
+    #[test]
+    fn non_separable_blend_modes() {
+        let backdrop = opaque([0.2, 0.5, 0.8]);
+        let src = opaque([0.7, 0.3, 0.4]);
+        let cases = [
+            (BlendMode::Hue, [0.8465, 0.2465, 0.3965]),
+            (BlendMode::Saturation, [0.281, 0.481, 0.681]),
+            (BlendMode::Color, [0.712, 0.312, 0.412]),
+            (BlendMode::Luminosity, [0.188, 0.488, 0.788]),
+        ];
+        for (mode, [r, g, b]) in cases {
+            assert_near(src.blend(backdrop, mode), [r, g, b, 1.0]);
+        }
+        // Raising blue to white's luminosity clips into gamut instead of overflowing.
+        let white = opaque([1.0, 1.0, 1.0]);
+        let blue = opaque([0.0, 0.0, 1.0]);
+        assert_near(
+            white.blend(blue, BlendMode::Luminosity),
+            [1.0, 1.0, 1.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn blending_with_partial_alpha() {
+        let src = Rgba::new(1.0, 0.0, 0.0, 0.5).premultiply();
+        let dest = Rgba::new(0.0, 0.0, 1.0, 0.75).premultiply();
+        // Normal blending is source-over.
+        assert_near(src.blend(dest, BlendMode::Normal), [0.5, 0.0, 0.375, 0.875]);
+        // Only the overlap takes the blended color: 0.125 of the source, 0.375 of the
+        // backdrop, and 0.375 of their product, which is black.
+        assert_near(
+            src.blend(dest, BlendMode::Multiply),
+            [0.125, 0.0, 0.375, 0.875],
+        );
+        assert_near(
+            src.blend(PremulRgba::TRANSPARENT, BlendMode::Screen),
+            [0.5, 0.0, 0.0, 0.5],
+        );
+        assert_eq!(PremulRgba::TRANSPARENT.unpremultiply(), Rgba::TRANSPARENT);
+        assert_eq!(src.unpremultiply(), Rgba::new(1.0, 0.0, 0.0, 0.5));
+    }
+}
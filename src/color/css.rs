@@ -0,0 +1,288 @@
+use super::{NAMED_COLORS, Rgba, space::hsl_to_rgb};
+use crate::{Error, Result};
+use std::str::FromStr;
+
+impl Rgba {
+    #[inline]
+    pub fn from_name(name: &str) -> Option<Self> {
+        NAMED_COLORS
+            .binary_search_by(|(n, _)| n.bytes().cmp(name.bytes().map(|b| b.to_ascii_lowercase())))
+            .ok()
+            .map(|i| NAMED_COLORS[i].1)
+    }
+
+    #[inline]
+    pub fn to_hex(&self) -> String {
+        let [r, g, b, a] =
+            [self.r, self.g, self.b, self.a].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        if a == 255 {
+            format!("#{r:02x}{g:02x}{b:02x}")
+        } else {
+            format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+        }
+    }
+}
+
+impl FromStr for Rgba {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse(s).ok_or_else(|| Error::ParseColor(s.to_string()))
+    }
+}
+
+fn parse(s: &str) -> Option<Rgba> {
+    let s = s.trim();
+    if let Some(digits) = s.strip_prefix('#') {
+        return parse_hex(digits);
+    }
+    let s = s.to_ascii_lowercase();
+    let Some((name, args)) = s.split_once('(') else {
+        return Rgba::from_name(&s);
+    };
+    let args = Args::parse(args.strip_suffix(')')?)?;
+    match name.trim_end() {
+        "rgb" | "rgba" => args.rgb(),
+        "hsl" | "hsla" => args.hsl(),
+        "hwb" if !args.legacy => args.hwb(),
+        _ => None,
+    }
+}
+
+fn parse_hex(digits: &str) -> Option<Rgba> {
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let nibble = |i: usize| u8::from_str_radix(&digits[i..i + 1], 16).unwrap() as f32 / 15.0;
+    let byte = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap() as f32 / 255.0;
+    match digits.len() {
+        3 => Some(Rgba::new(nibble(0), nibble(1), nibble(2), 1.0)),
+        4 => Some(Rgba::new(nibble(0), nibble(1), nibble(2), nibble(3))),
+        6 => Some(Rgba::new(byte(0), byte(2), byte(4), 1.0)),
+        8 => Some(Rgba::new(byte(0), byte(2), byte(4), byte(6))),
+        _ => None,
+    }
+}
+
+struct Args<'a> {
+    values: [&'a str; 3],
+    alpha: Option<&'a str>,
+    legacy: bool,
+}
+
+impl<'a> Args<'a> {
+    fn parse(s: &'a str) -> Option<Self> {
+        let legacy = s.contains(',');
+        let (values, alpha): (Vec<&str>, _) = if legacy {
+            let mut values: Vec<&str> = s.split(',').map(str::trim).collect();
+            let alpha = if values.len() == 4 {
+                values.pop()
+            } else {
+                None
+            };
+            (values, alpha)
+        } else {
+            match s.split_once('/') {
+                Some((values, alpha)) => (values.split_whitespace().collect(), Some(alpha.trim())),
+                None => (s.split_whitespace().collect(), None),
+            }
+        };
+        let values: [&str; 3] = values.try_into().ok()?;
+        if values.iter().chain(alpha.iter()).any(|v| v.is_empty()) {
+            return None;
+        }
+        Some(Self {
+            values,
+            alpha,
+            legacy,
+        })
+    }
+
+    fn alpha(&self) -> Option<f32> {
+        match self.alpha {
+            Some(alpha) => Some(percentage(alpha).or_else(|| number(alpha))?.clamp(0.0, 1.0)),
+            None => Some(1.0),
+        }
+    }
+
+    fn rgb(&self) -> Option<Rgba> {
+        let [r, g, b] = self.values;
+        Some(Rgba::new(
+            rgb_channel(r)?,
+            rgb_channel(g)?,
+            rgb_channel(b)?,
+            self.alpha()?,
+        ))
+    }
+
+    fn hsl(&self) -> Option<Rgba> {
+        let [h, s, l] = self.values;
+        let h = hue(h)?;
+        let s = fraction(s)?.max(0.0);
+        let l = fraction(l)?.clamp(0.0, 1.0);
+        let (r, g, b) = hsl_to_rgb(h, s, l);
+        Some(Rgba::new(r, g, b, self.alpha()?))
+    }
+
+    fn hwb(&self) -> Option<Rgba> {
+        let [h, w, b] = self.values;
+        let h = hue(h)?;
+        let w = fraction(w)?.clamp(0.0, 1.0);
+        let b = fraction(b)?.clamp(0.0, 1.0);
+        let (r, g, b) = hwb_to_rgb(h, w, b);
+        Some(Rgba::new(r, g, b, self.alpha()?))
+    }
+}
+
+fn number(s: &str) -> Option<f32> {
+    if s == "none" {
+        return Some(0.0);
+    }
+    s.parse::<f32>().ok().filter(|v| v.is_finite())
+}
+
+fn percentage(s: &str) -> Option<f32> {
+    s.strip_suffix('%').and_then(number).map(|v| v / 100.0)
+}
+
+fn fraction(s: &str) -> Option<f32> {
+    percentage(s).or_else(|| number(s).map(|v| v / 100.0))
+}
+
+fn rgb_channel(s: &str) -> Option<f32> {
+    let v = match percentage(s) {
+        Some(v) => v,
+        None => number(s)? / 255.0,
+    };
+    Some(v.clamp(0.0, 1.0))
+}
+
+fn hue(s: &str) -> Option<f32> {
+    if let Some(v) = s.strip_suffix("deg") {
+        number(v)
+    } else if let Some(v) = s.strip_suffix("grad") {
+        number(v).map(|v| v * 0.9)
+    } else if let Some(v) = s.strip_suffix("rad") {
+        number(v).map(f32::to_degrees)
+    } else if let Some(v) = s.strip_suffix("turn") {
+        number(v).map(|v| v * 360.0)
+    } else {
+        number(s)
+    }
+}
+
+fn hwb_to_rgb(h: f32, w: f32, b: f32) -> (f32, f32, f32) {
+    if w + b >= 1.0 {
+        let gray = w / (w + b);
+        return (gray, gray, gray);
+    }
+    let (r, g, bl) = hsl_to_rgb(h, 1.0, 0.5);
+    let f = |c: f32| c * (1.0 - w - b) + w;
+    (f(r), f(g), f(bl))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(s: &str) -> [f32; 4] {
+        let c: Rgba = s.parse().unwrap();
+        [c.r, c.g, c.b, c.a]
+    }
+
+    fn assert_color(s: &str, expected: [f32; 4]) {
+        let actual = parsed(s);
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(a, e)| (a - e).abs() < 1e-3),
+            "{s}: {actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn parses_hex() {
+        assert_color("#f00", [1.0, 0.0, 0.0, 1.0]);
+        assert_color("#0f08", [0.0, 1.0, 0.0, 8.0 / 15.0]);
+        assert_color("#663399", [0.4, 0.2, 0.6, 1.0]);
+        assert_color("  #FF000080 ", [1.0, 0.0, 0.0, 128.0 / 255.0]);
+        for s in ["#", "#12345", "#ggg", "#1234567", "f00"] {
+            assert!(
+                matches!(s.parse::<Rgba>(), Err(Error::ParseColor(_))),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_rgb_in_both_syntaxes() {
+        assert_color("rgb(255 0 0)", [1.0, 0.0, 0.0, 1.0]);
+        assert_color("rgb(255, 0, 0, 0.5)", [1.0, 0.0, 0.0, 0.5]);
+        assert_color("RGBA(100%, 50%, 0%, 50%)", [1.0, 0.5, 0.0, 0.5]);
+        assert_color("rgb(0 0 255 / 25%)", [0.0, 0.0, 1.0, 0.25]);
+        // Out of range channels and alpha are clamped.
+        assert_color("rgb(300 -5 none / 2)", [1.0, 0.0, 0.0, 1.0]);
+        for s in [
+            "rgb(1 2)",
+            "rgb(1, 2 3)",
+            "rgb(1 2 3",
+            "rgb(a b c)",
+            "rgb(1,,2,3)",
+        ] {
+            assert!(s.parse::<Rgba>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn parses_hsl_and_hwb() {
+        assert_color("hsl(120 100% 50%)", [0.0, 1.0, 0.0, 1.0]);
+        assert_color("hsl(0.5turn 100% 50%)", [0.0, 1.0, 1.0, 1.0]);
+        assert_color("hsla(240, 100%, 50%, 0.25)", [0.0, 0.0, 1.0, 0.25]);
+        assert_color("hsl(-120deg 100% 25%)", [0.0, 0.0, 0.5, 1.0]);
+        assert_color("hsl(200grad 0% 50%)", [0.5, 0.5, 0.5, 1.0]);
+        assert_color("hwb(0 0% 0%)", [1.0, 0.0, 0.0, 1.0]);
+        assert_color("hwb(3.14159rad 20% 20%)", [0.2, 0.8, 0.8, 1.0]);
+        // Whiteness and blackness past 100% together give a gray.
+        assert_color("hwb(90 60% 60%)", [0.5, 0.5, 0.5, 1.0]);
+        // hwb() has no legacy comma syntax.
+        assert!("hwb(0, 0%, 0%)".parse::<Rgba>().is_err());
+    }
+
+    #[test]
+    fn parses_names_and_formats_hex() {
+        assert_eq!(
+            "RebeccaPurple".parse::<Rgba>().unwrap(),
+            Rgba::REBECCA_PURPLE
+        );
+        assert_eq!("transparent".parse::<Rgba>().unwrap(), Rgba::TRANSPARENT);
+        assert_eq!(Rgba::from_name("AliceBlue"), Some(Rgba::ALICE_BLUE));
+        assert_eq!(Rgba::from_name("notacolor"), None);
+        // Lookup is a binary search, so the table must stay sorted.
+        assert!(NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(Rgba::REBECCA_PURPLE.to_hex(), "#663399");
+        assert_eq!(Rgba::new(1.0, 0.0, 0.0, 0.5).to_hex(), "#ff000080");
+        assert_eq!(Rgba::new(2.0, -1.0, 0.0, 1.0).to_hex(), "#ff0000");
+        let c = Rgba::new(0.2, 0.4, 0.6, 0.8);
+        assert_eq!(c.to_hex().parse::<Rgba>().unwrap().to_hex(), c.to_hex());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializes_strings_maps_and_sequences() {
+        let from = |json: &str| serde_json::from_str::<Rgba>(json);
+        assert_eq!(from("\"red\"").unwrap(), Rgba::RED);
+        assert_eq!(from("[1.0, 0.0, 0.0, 1.0]").unwrap(), Rgba::RED);
+        assert_eq!(
+            from(r#"{"a": 1.0, "r": 1.0, "g": 0.0, "b": 0.0}"#).unwrap(),
+            Rgba::RED
+        );
+        let json = serde_json::to_string(&Rgba::RED).unwrap();
+        assert_eq!(from(&json).unwrap(), Rgba::RED);
+        assert!(from("\"reddish\"").is_err());
+        assert!(from("[1.0, 0.0]").is_err());
+        assert!(from(r#"{"r": 1.0, "g": 0.0, "b": 0.0}"#).is_err());
+        assert!(from(r#"{"r": 1.0, "r": 1.0, "g": 0.0, "b": 0.0, "a": 1.0}"#).is_err());
+        assert!(from(r#"{"r": 1.0, "g": 0.0, "b": 0.0, "a": 1.0, "x": 0.0}"#).is_err());
+    }
+}
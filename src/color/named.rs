@@ -0,0 +1,314 @@
+use super::Rgba;
+
+const fn hex(value: u32) -> Rgba {
+    Rgba::new(
+        ((value >> 16) & 0xff) as f32 / 255.0,
+        ((value >> 8) & 0xff) as f32 / 255.0,
+        (value & 0xff) as f32 / 255.0,
+        1.0,
+    )
+}
+
+impl Rgba {
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+    pub const ALICE_BLUE: Self = hex(0xf0f8ff);
+    pub const ANTIQUE_WHITE: Self = hex(0xfaebd7);
+    pub const AQUA: Self = hex(0x00ffff);
+    pub const AQUAMARINE: Self = hex(0x7fffd4);
+    pub const AZURE: Self = hex(0xf0ffff);
+    pub const BEIGE: Self = hex(0xf5f5dc);
+    pub const BISQUE: Self = hex(0xffe4c4);
+    pub const BLACK: Self = hex(0x000000);
+    pub const BLANCHED_ALMOND: Self = hex(0xffebcd);
+    pub const BLUE: Self = hex(0x0000ff);
+    pub const BLUE_VIOLET: Self = hex(0x8a2be2);
+    pub const BROWN: Self = hex(0xa52a2a);
+    pub const BURLYWOOD: Self = hex(0xdeb887);
+    pub const CADET_BLUE: Self = hex(0x5f9ea0);
+    pub const CHARTREUSE: Self = hex(0x7fff00);
+    pub const CHOCOLATE: Self = hex(0xd2691e);
+    pub const CORAL: Self = hex(0xff7f50);
+    pub const CORNFLOWER_BLUE: Self = hex(0x6495ed);
+    pub const CORNSILK: Self = hex(0xfff8dc);
+    pub const CRIMSON: Self = hex(0xdc143c);
+    pub const CYAN: Self = hex(0x00ffff);
+    pub const DARK_BLUE: Self = hex(0x00008b);
+    pub const DARK_CYAN: Self = hex(0x008b8b);
+    pub const DARK_GOLDENROD: Self = hex(0xb8860b);
+    pub const DARK_GRAY: Self = hex(0xa9a9a9);
+    pub const DARK_GREEN: Self = hex(0x006400);
+    pub const DARK_GREY: Self = hex(0xa9a9a9);
+    pub const DARK_KHAKI: Self = hex(0xbdb76b);
+    pub const DARK_MAGENTA: Self = hex(0x8b008b);
+    pub const DARK_OLIVE_GREEN: Self = hex(0x556b2f);
+    pub const DARK_ORANGE: Self = hex(0xff8c00);
+    pub const DARK_ORCHID: Self = hex(0x9932cc);
+    pub const DARK_RED: Self = hex(0x8b0000);
+    pub const DARK_SALMON: Self = hex(0xe9967a);
+    pub const DARK_SEA_GREEN: Self = hex(0x8fbc8f);
+    pub const DARK_SLATE_BLUE: Self = hex(0x483d8b);
+    pub const DARK_SLATE_GRAY: Self = hex(0x2f4f4f);
+    pub const DARK_SLATE_GREY: Self = hex(0x2f4f4f);
+    pub const DARK_TURQUOISE: Self = hex(0x00ced1);
+    pub const DARK_VIOLET: Self = hex(0x9400d3);
+    pub const DEEP_PINK: Self = hex(0xff1493);
+    pub const DEEP_SKY_BLUE: Self = hex(0x00bfff);
+    pub const DIM_GRAY: Self = hex(0x696969);
+    pub const DIM_GREY: Self = hex(0x696969);
+    pub const DODGER_BLUE: Self = hex(0x1e90ff);
+    pub const FIREBRICK: Self = hex(0xb22222);
+    pub const FLORAL_WHITE: Self = hex(0xfffaf0);
+    pub const FOREST_GREEN: Self = hex(0x228b22);
+    pub const FUCHSIA: Self = hex(0xff00ff);
+    pub const GAINSBORO: Self = hex(0xdcdcdc);
+    pub const GHOST_WHITE: Self = hex(0xf8f8ff);
+    pub const GOLD: Self = hex(0xffd700);
+    pub const GOLDENROD: Self = hex(0xdaa520);
+    pub const GRAY: Self = hex(0x808080);
+    pub const GREEN: Self = hex(0x008000);
+    pub const GREEN_YELLOW: Self = hex(0xadff2f);
+    pub const GREY: Self = hex(0x808080);
+    pub const HONEYDEW: Self = hex(0xf0fff0);
+    pub const HOT_PINK: Self = hex(0xff69b4);
+    pub const INDIAN_RED: Self = hex(0xcd5c5c);
+    pub const INDIGO: Self = hex(0x4b0082);
+    pub const IVORY: Self = hex(0xfffff0);
+    pub const KHAKI: Self = hex(0xf0e68c);
+    pub const LAVENDER: Self = hex(0xe6e6fa);
+    pub const LAVENDER_BLUSH: Self = hex(0xfff0f5);
+    pub const LAWN_GREEN: Self = hex(0x7cfc00);
+    pub const LEMON_CHIFFON: Self = hex(0xfffacd);
+    pub const LIGHT_BLUE: Self = hex(0xadd8e6);
+    pub const LIGHT_CORAL: Self = hex(0xf08080);
+    pub const LIGHT_CYAN: Self = hex(0xe0ffff);
+    pub const LIGHT_GOLDENROD_YELLOW: Self = hex(0xfafad2);
+    pub const LIGHT_GRAY: Self = hex(0xd3d3d3);
+    pub const LIGHT_GREEN: Self = hex(0x90ee90);
+    pub const LIGHT_GREY: Self = hex(0xd3d3d3);
+    pub const LIGHT_PINK: Self = hex(0xffb6c1);
+    pub const LIGHT_SALMON: Self = hex(0xffa07a);
+    pub const LIGHT_SEA_GREEN: Self = hex(0x20b2aa);
+    pub const LIGHT_SKY_BLUE: Self = hex(0x87cefa);
+    pub const LIGHT_SLATE_GRAY: Self = hex(0x778899);
+    pub const LIGHT_SLATE_GREY: Self = hex(0x778899);
+    pub const LIGHT_STEEL_BLUE: Self = hex(0xb0c4de);
+    pub const LIGHT_YELLOW: Self = hex(0xffffe0);
+    pub const LIME: Self = hex(0x00ff00);
+    pub const LIME_GREEN: Self = hex(0x32cd32);
+    pub const LINEN: Self = hex(0xfaf0e6);
+    pub const MAGENTA: Self = hex(0xff00ff);
+    pub const MAROON: Self = hex(0x800000);
+    pub const MEDIUM_AQUAMARINE: Self = hex(0x66cdaa);
+    pub const MEDIUM_BLUE: Self = hex(0x0000cd);
+    pub const MEDIUM_ORCHID: Self = hex(0xba55d3);
+    pub const MEDIUM_PURPLE: Self = hex(0x9370db);
+    pub const MEDIUM_SEA_GREEN: Self = hex(0x3cb371);
+    pub const MEDIUM_SLATE_BLUE: Self = hex(0x7b68ee);
+    pub const MEDIUM_SPRING_GREEN: Self = hex(0x00fa9a);
+    pub const MEDIUM_TURQUOISE: Self = hex(0x48d1cc);
+    pub const MEDIUM_VIOLET_RED: Self = hex(0xc71585);
+    pub const MIDNIGHT_BLUE: Self = hex(0x191970);
+    pub const MINT_CREAM: Self = hex(0xf5fffa);
+    pub const MISTY_ROSE: Self = hex(0xffe4e1);
+    pub const MOCCASIN: Self = hex(0xffe4b5);
+    pub const NAVAJO_WHITE: Self = hex(0xffdead);
+    pub const NAVY: Self = hex(0x000080);
+    pub const OLD_LACE: Self = hex(0xfdf5e6);
+    pub const OLIVE: Self = hex(0x808000);
+    pub const OLIVE_DRAB: Self = hex(0x6b8e23);
+    pub const ORANGE: Self = hex(0xffa500);
+    pub const ORANGE_RED: Self = hex(0xff4500);
+    pub const ORCHID: Self = hex(0xda70d6);
+    pub const PALE_GOLDENROD: Self = hex(0xeee8aa);
+    pub const PALE_GREEN: Self = hex(0x98fb98);
+    pub const PALE_TURQUOISE: Self = hex(0xafeeee);
+    pub const PALE_VIOLET_RED: Self = hex(0xdb7093);
+    pub const PAPAYA_WHIP: Self = hex(0xffefd5);
+    pub const PEACH_PUFF: Self = hex(0xffdab9);
+    pub const PERU: Self = hex(0xcd853f);
+    pub const PINK: Self = hex(0xffc0cb);
+    pub const PLUM: Self = hex(0xdda0dd);
+    pub const POWDER_BLUE: Self = hex(0xb0e0e6);
+    pub const PURPLE: Self = hex(0x800080);
+    pub const REBECCA_PURPLE: Self = hex(0x663399);
+    pub const RED: Self = hex(0xff0000);
+    pub const ROSY_BROWN: Self = hex(0xbc8f8f);
+    pub const ROYAL_BLUE: Self = hex(0x4169e1);
+    pub const SADDLE_BROWN: Self = hex(0x8b4513);
+    pub const SALMON: Self = hex(0xfa8072);
+    pub const SANDY_BROWN: Self = hex(0xf4a460);
+    pub const SEA_GREEN: Self = hex(0x2e8b57);
+    pub const SEASHELL: Self = hex(0xfff5ee);
+    pub const SIENNA: Self = hex(0xa0522d);
+    pub const SILVER: Self = hex(0xc0c0c0);
+    pub const SKY_BLUE: Self = hex(0x87ceeb);
+    pub const SLATE_BLUE: Self = hex(0x6a5acd);
+    pub const SLATE_GRAY: Self = hex(0x708090);
+    pub const SLATE_GREY: Self = hex(0x708090);
+    pub const SNOW: Self = hex(0xfffafa);
+    pub const SPRING_GREEN: Self = hex(0x00ff7f);
+    pub const STEEL_BLUE: Self = hex(0x4682b4);
+    pub const TAN: Self = hex(0xd2b48c);
+    pub const TEAL: Self = hex(0x008080);
+    pub const THISTLE: Self = hex(0xd8bfd8);
+    pub const TOMATO: Self = hex(0xff6347);
+    pub const TURQUOISE: Self = hex(0x40e0d0);
+    pub const VIOLET: Self = hex(0xee82ee);
+    pub const WHEAT: Self = hex(0xf5deb3);
+    pub const WHITE: Self = hex(0xffffff);
+    pub const WHITE_SMOKE: Self = hex(0xf5f5f5);
+    pub const YELLOW: Self = hex(0xffff00);
+    pub const YELLOW_GREEN: Self = hex(0x9acd32);
+}
+
+pub(super) static NAMED_COLORS: [(&str, Rgba); 149] = [
+    ("aliceblue", Rgba::ALICE_BLUE),
+    ("antiquewhite", Rgba::ANTIQUE_WHITE),
+    ("aqua", Rgba::AQUA),
+    ("aquamarine", Rgba::AQUAMARINE),
+    ("azure", Rgba::AZURE),
+    ("beige", Rgba::BEIGE),
+    ("bisque", Rgba::BISQUE),
+    ("black", Rgba::BLACK),
+    ("blanchedalmond", Rgba::BLANCHED_ALMOND),
+    ("blue", Rgba::BLUE),
+    ("blueviolet", Rgba::BLUE_VIOLET),
+    ("brown", Rgba::BROWN),
+    ("burlywood", Rgba::BURLYWOOD),
+    ("cadetblue", Rgba::CADET_BLUE),
+    ("chartreuse", Rgba::CHARTREUSE),
+    ("chocolate", Rgba::CHOCOLATE),
+    ("coral", Rgba::CORAL),
+    ("cornflowerblue", Rgba::CORNFLOWER_BLUE),
+    ("cornsilk", Rgba::CORNSILK),
+    ("crimson", Rgba::CRIMSON),
+    ("cyan", Rgba::CYAN),
+    ("darkblue", Rgba::DARK_BLUE),
+    ("darkcyan", Rgba::DARK_CYAN),
+    ("darkgoldenrod", Rgba::DARK_GOLDENROD),
+    ("darkgray", Rgba::DARK_GRAY),
+    ("darkgreen", Rgba::DARK_GREEN),
+    ("darkgrey", Rgba::DARK_GREY),
+    ("darkkhaki", Rgba::DARK_KHAKI),
+    ("darkmagenta", Rgba::DARK_MAGENTA),
+    ("darkolivegreen", Rgba::DARK_OLIVE_GREEN),
+    ("darkorange", Rgba::DARK_ORANGE),
+    ("darkorchid", Rgba::DARK_ORCHID),
+    ("darkred", Rgba::DARK_RED),
+    ("darksalmon", Rgba::DARK_SALMON),
+    ("darkseagreen", Rgba::DARK_SEA_GREEN),
+    ("darkslateblue", Rgba::DARK_SLATE_BLUE),
+    ("darkslategray", Rgba::DARK_SLATE_GRAY),
+    ("darkslategrey", Rgba::DARK_SLATE_GREY),
+    ("darkturquoise", Rgba::DARK_TURQUOISE),
+    ("darkviolet", Rgba::DARK_VIOLET),
+    ("deeppink", Rgba::DEEP_PINK),
+    ("deepskyblue", Rgba::DEEP_SKY_BLUE),
+    ("dimgray", Rgba::DIM_GRAY),
+    ("dimgrey", Rgba::DIM_GREY),
+    ("dodgerblue", Rgba::DODGER_BLUE),
+    ("firebrick", Rgba::FIREBRICK),
+    ("floralwhite", Rgba::FLORAL_WHITE),
+    ("forestgreen", Rgba::FOREST_GREEN),
+    ("fuchsia", Rgba::FUCHSIA),
+    ("gainsboro", Rgba::GAINSBORO),
+    ("ghostwhite", Rgba::GHOST_WHITE),
+    ("gold", Rgba::GOLD),
+    ("goldenrod", Rgba::GOLDENROD),
+    ("gray", Rgba::GRAY),
+    ("green", Rgba::GREEN),
+    ("greenyellow", Rgba::GREEN_YELLOW),
+    ("grey", Rgba::GREY),
+    ("honeydew", Rgba::HONEYDEW),
+    ("hotpink", Rgba::HOT_PINK),
+    ("indianred", Rgba::INDIAN_RED),
+    ("indigo", Rgba::INDIGO),
+    ("ivory", Rgba::IVORY),
+    ("khaki", Rgba::KHAKI),
+    ("lavender", Rgba::LAVENDER),
+    ("lavenderblush", Rgba::LAVENDER_BLUSH),
+    ("lawngreen", Rgba::LAWN_GREEN),
+    ("lemonchiffon", Rgba::LEMON_CHIFFON),
+    ("lightblue", Rgba::LIGHT_BLUE),
+    ("lightcoral", Rgba::LIGHT_CORAL),
+    ("lightcyan", Rgba::LIGHT_CYAN),
+    ("lightgoldenrodyellow", Rgba::LIGHT_GOLDENROD_YELLOW),
+    ("lightgray", Rgba::LIGHT_GRAY),
+    ("lightgreen", Rgba::LIGHT_GREEN),
+    ("lightgrey", Rgba::LIGHT_GREY),
+    ("lightpink", Rgba::LIGHT_PINK),
+    ("lightsalmon", Rgba::LIGHT_SALMON),
+    ("lightseagreen", Rgba::LIGHT_SEA_GREEN),
+    ("lightskyblue", Rgba::LIGHT_SKY_BLUE),
+    ("lightslategray", Rgba::LIGHT_SLATE_GRAY),
+    ("lightslategrey", Rgba::LIGHT_SLATE_GREY),
+    ("lightsteelblue", Rgba::LIGHT_STEEL_BLUE),
+    ("lightyellow", Rgba::LIGHT_YELLOW),
+    ("lime", Rgba::LIME),
+    ("limegreen", Rgba::LIME_GREEN),
+    ("linen", Rgba::LINEN),
+    ("magenta", Rgba::MAGENTA),
+    ("maroon", Rgba::MAROON),
+    ("mediumaquamarine", Rgba::MEDIUM_AQUAMARINE),
+    ("mediumblue", Rgba::MEDIUM_BLUE),
+    ("mediumorchid", Rgba::MEDIUM_ORCHID),
+    ("mediumpurple", Rgba::MEDIUM_PURPLE),
+    ("mediumseagreen", Rgba::MEDIUM_SEA_GREEN),
+    ("mediumslateblue", Rgba::MEDIUM_SLATE_BLUE),
+    ("mediumspringgreen", Rgba::MEDIUM_SPRING_GREEN),
+    ("mediumturquoise", Rgba::MEDIUM_TURQUOISE),
+    ("mediumvioletred", Rgba::MEDIUM_VIOLET_RED),
+    ("midnightblue", Rgba::MIDNIGHT_BLUE),
+    ("mintcream", Rgba::MINT_CREAM),
+    ("mistyrose", Rgba::MISTY_ROSE),
+    ("moccasin", Rgba::MOCCASIN),
+    ("navajowhite", Rgba::NAVAJO_WHITE),
+    ("navy", Rgba::NAVY),
+    ("oldlace", Rgba::OLD_LACE),
+    ("olive", Rgba::OLIVE),
+    ("olivedrab", Rgba::OLIVE_DRAB),
+    ("orange", Rgba::ORANGE),
+    ("orangered", Rgba::ORANGE_RED),
+    ("orchid", Rgba::ORCHID),
+    ("palegoldenrod", Rgba::PALE_GOLDENROD),
+    ("palegreen", Rgba::PALE_GREEN),
+    ("paleturquoise", Rgba::PALE_TURQUOISE),
+    ("palevioletred", Rgba::PALE_VIOLET_RED),
+    ("papayawhip", Rgba::PAPAYA_WHIP),
+    ("peachpuff", Rgba::PEACH_PUFF),
+    ("peru", Rgba::PERU),
+    ("pink", Rgba::PINK),
+    ("plum", Rgba::PLUM),
+    ("powderblue", Rgba::POWDER_BLUE),
+    ("purple", Rgba::PURPLE),
+    ("rebeccapurple", Rgba::REBECCA_PURPLE),
+    ("red", Rgba::RED),
+    ("rosybrown", Rgba::ROSY_BROWN),
+    ("royalblue", Rgba::ROYAL_BLUE),
+    ("saddlebrown", Rgba::SADDLE_BROWN),
+    ("salmon", Rgba::SALMON),
+    ("sandybrown", Rgba::SANDY_BROWN),
+    ("seagreen", Rgba::SEA_GREEN),
+    ("seashell", Rgba::SEASHELL),
+    ("sienna", Rgba::SIENNA),
+    ("silver", Rgba::SILVER),
+    ("skyblue", Rgba::SKY_BLUE),
+    ("slateblue", Rgba::SLATE_BLUE),
+    ("slategray", Rgba::SLATE_GRAY),
+    ("slategrey", Rgba::SLATE_GREY),
+    ("snow", Rgba::SNOW),
+    ("springgreen", Rgba::SPRING_GREEN),
+    ("steelblue", Rgba::STEEL_BLUE),
+    ("tan", Rgba::TAN),
+    ("teal", Rgba::TEAL),
+    ("thistle", Rgba::THISTLE),
+    ("tomato", Rgba::TOMATO),
+    ("transparent", Rgba::TRANSPARENT),
+    ("turquoise", Rgba::TURQUOISE),
+    ("violet", Rgba::VIOLET),
+    ("wheat", Rgba::WHEAT),
+    ("white", Rgba::WHITE),
+    ("whitesmoke", Rgba::WHITE_SMOKE),
+    ("yellow", Rgba::YELLOW),
+    ("yellowgreen", Rgba::YELLOW_GREEN),
+];
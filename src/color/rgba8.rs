@@ -0,0 +1,283 @@
+use super::{LinearRgba, Rgba};
+use crate::{Error, Result};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rgba8 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+// Channel order from the first byte in memory, or from the most significant byte of a packed `u32`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelOrder {
+    Rgba,
+    Argb,
+    Bgra,
+}
+
+impl Rgba8 {
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    #[inline]
+    pub const fn from_bytes(bytes: [u8; 4], order: ChannelOrder) -> Self {
+        let [x, y, z, w] = bytes;
+        match order {
+            ChannelOrder::Rgba => Self::new(x, y, z, w),
+            ChannelOrder::Argb => Self::new(y, z, w, x),
+            ChannelOrder::Bgra => Self::new(z, y, x, w),
+        }
+    }
+
+    #[inline]
+    pub const fn to_bytes(self, order: ChannelOrder) -> [u8; 4] {
+        let Self { r, g, b, a } = self;
+        match order {
+            ChannelOrder::Rgba => [r, g, b, a],
+            ChannelOrder::Argb => [a, r, g, b],
+            ChannelOrder::Bgra => [b, g, r, a],
+        }
+    }
+
+    #[inline]
+    pub const fn from_u32(value: u32, order: ChannelOrder) -> Self {
+        Self::from_bytes(value.to_be_bytes(), order)
+    }
+
+    #[inline]
+    pub const fn to_u32(self, order: ChannelOrder) -> u32 {
+        u32::from_be_bytes(self.to_bytes(order))
+    }
+
+    // Panics if `bytes` does not hold a whole number of pixels.
+    #[inline]
+    pub fn cast_slice(bytes: &[u8]) -> &[Rgba8] {
+        assert!(bytes.len().is_multiple_of(4));
+        // SAFETY: `Rgba8` is `#[repr(C)]` with four `u8` fields, so it has the size of four
+        // bytes and the alignment of `u8`, and every bit pattern is valid.
+        unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const Rgba8, bytes.len() / 4) }
+    }
+
+    // Panics if `bytes` does not hold a whole number of pixels.
+    #[inline]
+    pub fn cast_slice_mut(bytes: &mut [u8]) -> &mut [Rgba8] {
+        assert!(bytes.len().is_multiple_of(4));
+        // SAFETY: as in `cast_slice`; the borrow of `bytes` is moved into the result.
+        unsafe { std::slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut Rgba8, bytes.len() / 4) }
+    }
+
+    #[inline]
+    pub fn as_bytes(pixels: &[Rgba8]) -> &[u8] {
+        // SAFETY: `Rgba8` is `#[repr(C)]` with four `u8` fields and no padding, so a pixel is
+        // exactly four initialized bytes.
+        unsafe { std::slice::from_raw_parts(pixels.as_ptr() as *const u8, pixels.len() * 4) }
+    }
+}
+
+fn quantize(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl From<Rgba> for Rgba8 {
+    #[inline]
+    fn from(value: Rgba) -> Self {
+        Self {
+            r: quantize(value.r),
+            g: quantize(value.g),
+            b: quantize(value.b),
+            a: quantize(value.a),
+        }
+    }
+}
+
+impl From<Rgba8> for Rgba {
+    #[inline]
+    fn from(value: Rgba8) -> Self {
+        Self {
+            r: value.r as f32 / 255.0,
+            g: value.g as f32 / 255.0,
+            b: value.b as f32 / 255.0,
+            a: value.a as f32 / 255.0,
+        }
+    }
+}
+
+impl From<LinearRgba> for Rgba8 {
+    #[inline]
+    fn from(value: LinearRgba) -> Self {
+        Rgba::from(value).into()
+    }
+}
+
+impl From<Rgba8> for LinearRgba {
+    #[inline]
+    fn from(value: Rgba8) -> Self {
+        Rgba::from(value).into()
+    }
+}
+
+impl From<(u8, u8, u8, u8)> for Rgba8 {
+    #[inline]
+    fn from(value: (u8, u8, u8, u8)) -> Self {
+        Self {
+            r: value.0,
+            g: value.1,
+            b: value.2,
+            a: value.3,
+        }
+    }
+}
+
+fn check_len<T, U>(src: &[T], dest: &[U]) -> Result<()> {
+    if src.len() != dest.len() {
+        return Err(Error::PixelCount {
+            expected: dest.len(),
+            actual: src.len(),
+        });
+    }
+    Ok(())
+}
+
+pub fn rgba8_to_rgba(src: &[Rgba8], dest: &mut [Rgba]) -> Result<()> {
+    check_len(src, dest)?;
+    for (s, d) in src.iter().zip(dest.iter_mut()) {
+        *d = (*s).into();
+    }
+    Ok(())
+}
+
+pub fn rgba_to_rgba8(src: &[Rgba], dest: &mut [Rgba8]) -> Result<()> {
+    check_len(src, dest)?;
+    for (s, d) in src.iter().zip(dest.iter_mut()) {
+        *d = (*s).into();
+    }
+    Ok(())
+}
+
+pub fn pack_u32(src: &[Rgba8], dest: &mut [u32], order: ChannelOrder) -> Result<()> {
+    check_len(src, dest)?;
+    for (s, d) in src.iter().zip(dest.iter_mut()) {
+        *d = s.to_u32(order);
+    }
+    Ok(())
+}
+
+pub fn unpack_u32(src: &[u32], dest: &mut [Rgba8], order: ChannelOrder) -> Result<()> {
+    check_len(src, dest)?;
+    for (s, d) in src.iter().zip(dest.iter_mut()) {
+        *d = Rgba8::from_u32(*s, order);
+    }
+    Ok(())
+}
+
+pub fn swizzle(pixels: &mut [u8], from: ChannelOrder, to: ChannelOrder) -> Result<()> {
+    if !pixels.len().is_multiple_of(4) {
+        return Err(Error::ByteCount {
+            expected: pixels.len().next_multiple_of(4),
+            actual: pixels.len(),
+        });
+    }
+    if from == to {
+        return Ok(());
+    }
+    for pixel in pixels.chunks_exact_mut(4) {
+        let c = Rgba8::from_bytes(pixel.try_into().unwrap(), from);
+        pixel.copy_from_slice(&c.to_bytes(to));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const C: Rgba8 = Rgba8::new(0x11, 0x22, 0x33, 0x44);
+
+    #[test]
+    fn channel_orders_place_bytes() {
+        assert_eq!(C.to_bytes(ChannelOrder::Rgba), [0x11, 0x22, 0x33, 0x44]);
+        assert_eq!(C.to_bytes(ChannelOrder::Argb), [0x44, 0x11, 0x22, 0x33]);
+        assert_eq!(C.to_bytes(ChannelOrder::Bgra), [0x33, 0x22, 0x11, 0x44]);
+        assert_eq!(C.to_u32(ChannelOrder::Argb), 0x4411_2233);
+        assert_eq!(C.to_u32(ChannelOrder::Rgba), 0x1122_3344);
+        for order in [ChannelOrder::Rgba, ChannelOrder::Argb, ChannelOrder::Bgra] {
+            assert_eq!(Rgba8::from_bytes(C.to_bytes(order), order), C);
+            assert_eq!(Rgba8::from_u32(C.to_u32(order), order), C);
+        }
+    }
+
+    #[test]
+    fn converts_from_float_with_rounding_and_clamping() {
+        assert_eq!(
+            Rgba8::from(Rgba::new(0.5, 1.5, -0.5, 0.499 / 255.0)),
+            Rgba8::new(128, 255, 0, 0)
+        );
+        for v in 0..=255 {
+            let c = Rgba8::new(v, v, v, v);
+            assert_eq!(Rgba8::from(Rgba::from(c)), c);
+            assert_eq!(Rgba8::from(LinearRgba::from(c)), c);
+        }
+    }
+
+    #[test]
+    fn bulk_conversions() {
+        let pixels = [C, Rgba8::new(1, 2, 3, 4)];
+        let mut packed = [0; 2];
+        pack_u32(&pixels, &mut packed, ChannelOrder::Argb).unwrap();
+        assert_eq!(packed, [0x4411_2233, 0x0401_0203]);
+        let mut unpacked = [Rgba8::default(); 2];
+        unpack_u32(&packed, &mut unpacked, ChannelOrder::Argb).unwrap();
+        assert_eq!(unpacked, pixels);
+        let mut floats = [Rgba::TRANSPARENT; 2];
+        rgba8_to_rgba(&pixels, &mut floats).unwrap();
+        let mut back = [Rgba8::default(); 2];
+        rgba_to_rgba8(&floats, &mut back).unwrap();
+        assert_eq!(back, pixels);
+        assert!(matches!(
+            pack_u32(&pixels, &mut [0; 3], ChannelOrder::Rgba),
+            Err(Error::PixelCount {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn swizzles_and_casts_byte_slices() {
+        let mut bytes = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        swizzle(&mut bytes, ChannelOrder::Rgba, ChannelOrder::Bgra).unwrap();
+        assert_eq!(bytes, [3, 2, 1, 4, 7, 6, 5, 8]);
+        swizzle(&mut bytes, ChannelOrder::Bgra, ChannelOrder::Argb).unwrap();
+        assert_eq!(bytes, [4, 1, 2, 3, 8, 5, 6, 7]);
+        swizzle(&mut bytes, ChannelOrder::Argb, ChannelOrder::Rgba).unwrap();
+        assert_eq!(bytes, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(matches!(
+            swizzle(&mut [0; 6], ChannelOrder::Rgba, ChannelOrder::Bgra),
+            Err(Error::ByteCount {
+                expected: 8,
+                actual: 6
+            })
+        ));
+        assert_eq!(
+            Rgba8::cast_slice(&bytes),
+            [Rgba8::new(1, 2, 3, 4), Rgba8::new(5, 6, 7, 8)]
+        );
+        Rgba8::cast_slice_mut(&mut bytes)[1].a = 9;
+        assert_eq!(
+            Rgba8::as_bytes(Rgba8::cast_slice(&bytes)),
+            [1, 2, 3, 4, 5, 6, 7, 9]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn cast_slice_rejects_partial_pixels() {
+        Rgba8::cast_slice(&[0; 6]);
+    }
+}
@@ -0,0 +1,755 @@
+use super::Rgba;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearRgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl LinearRgba {
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsla {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub alpha: f32,
+}
+
+impl Hsla {
+    pub const fn new(h: f32, s: f32, l: f32, alpha: f32) -> Self {
+        Self { h, s, l, alpha }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsva {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub alpha: f32,
+}
+
+impl Hsva {
+    pub const fn new(h: f32, s: f32, v: f32, alpha: f32) -> Self {
+        Self { h, s, v, alpha }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+impl Lab {
+    pub const fn new(l: f32, a: f32, b: f32, alpha: f32) -> Self {
+        Self { l, a, b, alpha }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub alpha: f32,
+}
+
+impl Lch {
+    pub const fn new(l: f32, c: f32, h: f32, alpha: f32) -> Self {
+        Self { l, c, h, alpha }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+impl Oklab {
+    pub const fn new(l: f32, a: f32, b: f32, alpha: f32) -> Self {
+        Self { l, a, b, alpha }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub alpha: f32,
+}
+
+impl Oklch {
+    pub const fn new(l: f32, c: f32, h: f32, alpha: f32) -> Self {
+        Self { l, c, h, alpha }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorSpace {
+    Srgb,
+    LinearSrgb,
+    Hsl,
+    Hsv,
+    Lab,
+    Lch,
+    Oklab,
+    Oklch,
+}
+
+impl ColorSpace {
+    fn is_polar(self) -> bool {
+        matches!(self, Self::Hsl | Self::Hsv | Self::Lch | Self::Oklch)
+    }
+
+    fn decompose(self, c: Rgba) -> [f32; 4] {
+        match self {
+            Self::Srgb => [c.r, c.g, c.b, c.a],
+            Self::LinearSrgb => {
+                let c = LinearRgba::from(c);
+                [c.r, c.g, c.b, c.a]
+            }
+            Self::Hsl => {
+                let c = Hsla::from(c);
+                [c.h, c.s, c.l, c.alpha]
+            }
+            Self::Hsv => {
+                let c = Hsva::from(c);
+                [c.h, c.s, c.v, c.alpha]
+            }
+            Self::Lab => {
+                let c = Lab::from(c);
+                [c.l, c.a, c.b, c.alpha]
+            }
+            Self::Lch => {
+                let c = Lch::from(c);
+                [c.h, c.c, c.l, c.alpha]
+            }
+            Self::Oklab => {
+                let c = Oklab::from(c);
+                [c.l, c.a, c.b, c.alpha]
+            }
+            Self::Oklch => {
+                let c = Oklch::from(c);
+                [c.h, c.c, c.l, c.alpha]
+            }
+        }
+    }
+
+    fn compose(self, [x, y, z, alpha]: [f32; 4]) -> Rgba {
+        match self {
+            Self::Srgb => Rgba::new(x, y, z, alpha),
+            Self::LinearSrgb => LinearRgba::new(x, y, z, alpha).into(),
+            Self::Hsl => Hsla::new(x, y, z, alpha).into(),
+            Self::Hsv => Hsva::new(x, y, z, alpha).into(),
+            Self::Lab => Lab::new(x, y, z, alpha).into(),
+            Self::Lch => Lch::new(z, y, x, alpha).into(),
+            Self::Oklab => Oklab::new(x, y, z, alpha).into(),
+            Self::Oklch => Oklch::new(z, y, x, alpha).into(),
+        }
+    }
+}
+
+impl Rgba {
+    #[inline]
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    #[inline]
+    pub fn lerp(self, other: Rgba, t: f32, space: ColorSpace) -> Self {
+        self.lerp_with(other, t, space, true)
+    }
+
+    pub(crate) fn lerp_with(
+        self,
+        other: Rgba,
+        t: f32,
+        space: ColorSpace,
+        premultiplied: bool,
+    ) -> Self {
+        let mut x = space.decompose(self);
+        let mut y = space.decompose(other);
+        let polar = space.is_polar();
+        if polar {
+            // Polar spaces keep the hue in the first component and the saturation or chroma
+            // in the second one; a gray has no meaningful hue, so it takes the other one.
+            const POWERLESS: f32 = 1e-4;
+            if x[1].abs() <= POWERLESS {
+                x[0] = y[0];
+            } else if y[1].abs() <= POWERLESS {
+                y[0] = x[0];
+            }
+            let d = y[0] - x[0];
+            if d > 180.0 {
+                x[0] += 360.0;
+            } else if d < -180.0 {
+                y[0] += 360.0;
+            }
+        }
+        let first = if polar { 1 } else { 0 };
+        if premultiplied {
+            for i in first..3 {
+                x[i] *= x[3];
+                y[i] *= y[3];
+            }
+        }
+        let mut v: [f32; 4] = std::array::from_fn(|i| x[i] + (y[i] - x[i]) * t);
+        let alpha = v[3];
+        if premultiplied && alpha > 0.0 {
+            for c in &mut v[first..3] {
+                *c /= alpha;
+            }
+        }
+        if polar {
+            v[0] = v[0].rem_euclid(360.0);
+        }
+        space.compose(v)
+    }
+
+    #[inline]
+    pub fn lighten(self, amount: f32) -> Self {
+        let c = Hsla::from(self);
+        Hsla {
+            l: (c.l + amount).clamp(0.0, 1.0),
+            ..c
+        }
+        .into()
+    }
+
+    #[inline]
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    #[inline]
+    pub fn saturate(self, amount: f32) -> Self {
+        let c = Hsla::from(self);
+        Hsla {
+            s: (c.s + amount).clamp(0.0, 1.0),
+            ..c
+        }
+        .into()
+    }
+
+    #[inline]
+    pub fn desaturate(self, amount: f32) -> Self {
+        self.saturate(-amount)
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    let v = c.abs();
+    let v = if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    };
+    v.copysign(c)
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    let v = c.abs();
+    let v = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    v.copysign(c)
+}
+
+impl From<Rgba> for LinearRgba {
+    #[inline]
+    fn from(value: Rgba) -> Self {
+        Self {
+            r: srgb_to_linear(value.r),
+            g: srgb_to_linear(value.g),
+            b: srgb_to_linear(value.b),
+            a: value.a,
+        }
+    }
+}
+
+impl From<LinearRgba> for Rgba {
+    #[inline]
+    fn from(value: LinearRgba) -> Self {
+        Self {
+            r: linear_to_srgb(value.r),
+            g: linear_to_srgb(value.g),
+            b: linear_to_srgb(value.b),
+            a: value.a,
+        }
+    }
+}
+
+fn hue_from_rgb(r: f32, g: f32, b: f32, max: f32, d: f32) -> f32 {
+    if d == 0.0 {
+        return 0.0;
+    }
+    let h = if max == r {
+        (g - b) / d + if g < b { 6.0 } else { 0.0 }
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    h * 60.0
+}
+
+pub(super) fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
+    let h = h.rem_euclid(360.0);
+    let a = s * l.min(1.0 - l);
+    let f = |n: f32| {
+        let k = (n + h / 30.0) % 12.0;
+        l - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    (f(0.0), f(8.0), f(4.0))
+}
+
+impl From<Rgba> for Hsla {
+    fn from(value: Rgba) -> Self {
+        let Rgba { r, g, b, a } = value;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+        let l = (max + min) / 2.0;
+        let s = if d == 0.0 || l <= 0.0 || l >= 1.0 {
+            0.0
+        } else {
+            d / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        Self {
+            h: hue_from_rgb(r, g, b, max, d),
+            s,
+            l,
+            alpha: a,
+        }
+    }
+}
+
+impl From<Hsla> for Rgba {
+    #[inline]
+    fn from(value: Hsla) -> Self {
+        let (r, g, b) = hsl_to_rgb(value.h, value.s, value.l);
+        Self::new(r, g, b, value.alpha)
+    }
+}
+
+impl From<Rgba> for Hsva {
+    fn from(value: Rgba) -> Self {
+        let Rgba { r, g, b, a } = value;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+        Self {
+            h: hue_from_rgb(r, g, b, max, d),
+            s: if max == 0.0 { 0.0 } else { d / max },
+            v: max,
+            alpha: a,
+        }
+    }
+}
+
+impl From<Hsva> for Rgba {
+    fn from(value: Hsva) -> Self {
+        let Hsva { h, s, v, alpha } = value;
+        let h = h.rem_euclid(360.0);
+        let f = |n: f32| {
+            let k = (n + h / 60.0) % 6.0;
+            v - v * s * k.min(4.0 - k).clamp(0.0, 1.0)
+        };
+        Self::new(f(5.0), f(3.0), f(1.0), alpha)
+    }
+}
+
+type Matrix = [[f64; 3]; 3];
+
+fn mul(m: &Matrix, v: [f32; 3]) -> [f32; 3] {
+    let v = v.map(f64::from);
+    std::array::from_fn(|i| (m[i][0] * v[0] + m[i][1] * v[1] + m[i][2] * v[2]) as f32)
+}
+
+// CIE Lab is relative to D50 like CSS, so sRGB (D65) is chromatically adapted with Bradford.
+const LINEAR_SRGB_TO_XYZ_D50: Matrix = [
+    [0.43606575, 0.38515151, 0.14307842],
+    [0.22249318, 0.71688701, 0.06061981],
+    [0.01392392, 0.09708132, 0.71409936],
+];
+
+const XYZ_D50_TO_LINEAR_SRGB: Matrix = [
+    [3.13413585, -1.61738600, -0.49066222],
+    [-0.97879548, 1.91625438, 0.03344287],
+    [0.07195539, -0.22897676, 1.40538604],
+];
+
+const D50: [f32; 3] = [0.964_295_7, 1.0, 0.825_104_6];
+
+const LAB_EPSILON: f32 = 216.0 / 24389.0;
+const LAB_KAPPA: f32 = 24389.0 / 27.0;
+
+impl From<Rgba> for Lab {
+    fn from(value: Rgba) -> Self {
+        let linear = LinearRgba::from(value);
+        let xyz = mul(&LINEAR_SRGB_TO_XYZ_D50, [linear.r, linear.g, linear.b]);
+        let [fx, fy, fz] = std::array::from_fn(|i| {
+            let v = xyz[i] / D50[i];
+            if v > LAB_EPSILON {
+                v.cbrt()
+            } else {
+                (LAB_KAPPA * v + 16.0) / 116.0
+            }
+        });
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+            alpha: value.a,
+        }
+    }
+}
+
+impl From<Lab> for Rgba {
+    fn from(value: Lab) -> Self {
+        let fy = (value.l + 16.0) / 116.0;
+        let fx = value.a / 500.0 + fy;
+        let fz = fy - value.b / 200.0;
+        let f = |v: f32| {
+            let v3 = v * v * v;
+            if v3 > LAB_EPSILON {
+                v3
+            } else {
+                (116.0 * v - 16.0) / LAB_KAPPA
+            }
+        };
+        let y = if value.l > LAB_KAPPA * LAB_EPSILON {
+            fy * fy * fy
+        } else {
+            value.l / LAB_KAPPA
+        };
+        let xyz = [f(fx) * D50[0], y * D50[1], f(fz) * D50[2]];
+        let [r, g, b] = mul(&XYZ_D50_TO_LINEAR_SRGB, xyz);
+        LinearRgba::new(r, g, b, value.alpha).into()
+    }
+}
+
+fn to_polar(a: f32, b: f32) -> (f32, f32) {
+    (a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.0))
+}
+
+fn from_polar(c: f32, h: f32) -> (f32, f32) {
+    let (sin, cos) = h.to_radians().sin_cos();
+    (c * cos, c * sin)
+}
+
+impl From<Lab> for Lch {
+    #[inline]
+    fn from(value: Lab) -> Self {
+        let (c, h) = to_polar(value.a, value.b);
+        Self {
+            l: value.l,
+            c,
+            h,
+            alpha: value.alpha,
+        }
+    }
+}
+
+impl From<Lch> for Lab {
+    #[inline]
+    fn from(value: Lch) -> Self {
+        let (a, b) = from_polar(value.c, value.h);
+        Self {
+            l: value.l,
+            a,
+            b,
+            alpha: value.alpha,
+        }
+    }
+}
+
+impl From<Rgba> for Lch {
+    #[inline]
+    fn from(value: Rgba) -> Self {
+        Lab::from(value).into()
+    }
+}
+
+impl From<Lch> for Rgba {
+    #[inline]
+    fn from(value: Lch) -> Self {
+        Lab::from(value).into()
+    }
+}
+
+const LINEAR_SRGB_TO_LMS: Matrix = [
+    [0.4122214708, 0.5363325363, 0.0514459929],
+    [0.2119034982, 0.6806995451, 0.1073969566],
+    [0.0883024619, 0.2817188376, 0.6299787005],
+];
+
+const LMS_TO_OKLAB: Matrix = [
+    [0.2104542553, 0.7936177850, -0.0040720468],
+    [1.9779984951, -2.4285922050, 0.4505937099],
+    [0.0259040371, 0.7827717662, -0.8086757660],
+];
+
+const OKLAB_TO_LMS: Matrix = [
+    [1.0, 0.3963377774, 0.2158037573],
+    [1.0, -0.1055613458, -0.0638541728],
+    [1.0, -0.0894841775, -1.2914855480],
+];
+
+const LMS_TO_LINEAR_SRGB: Matrix = [
+    [4.0767416621, -3.3077115913, 0.2309699292],
+    [-1.2684380046, 2.6097574011, -0.3413193965],
+    [-0.0041960863, -0.7034186147, 1.7076147010],
+];
+
+impl From<Rgba> for Oklab {
+    fn from(value: Rgba) -> Self {
+        let linear = LinearRgba::from(value);
+        let lms = mul(&LINEAR_SRGB_TO_LMS, [linear.r, linear.g, linear.b]).map(f32::cbrt);
+        let [l, a, b] = mul(&LMS_TO_OKLAB, lms);
+        Self {
+            l,
+            a,
+            b,
+            alpha: value.a,
+        }
+    }
+}
+
+impl From<Oklab> for Rgba {
+    fn from(value: Oklab) -> Self {
+        let lms = mul(&OKLAB_TO_LMS, [value.l, value.a, value.b]).map(|v| v * v * v);
+        let [r, g, b] = mul(&LMS_TO_LINEAR_SRGB, lms);
+        LinearRgba::new(r, g, b, value.alpha).into()
+    }
+}
+
+impl From<Oklab> for Oklch {
+    #[inline]
+    fn from(value: Oklab) -> Self {
+        let (c, h) = to_polar(value.a, value.b);
+        Self {
+            l: value.l,
+            c,
+            h,
+            alpha: value.alpha,
+        }
+    }
+}
+
+impl From<Oklch> for Oklab {
+    #[inline]
+    fn from(value: Oklch) -> Self {
+        let (a, b) = from_polar(value.c, value.h);
+        Self {
+            l: value.l,
+            a,
+            b,
+            alpha: value.alpha,
+        }
+    }
+}
+
+impl From<Rgba> for Oklch {
+    #[inline]
+    fn from(value: Rgba) -> Self {
+        Oklab::from(value).into()
+    }
+}
+
+impl From<Oklch> for Rgba {
+    #[inline]
+    fn from(value: Oklch) -> Self {
+        Oklab::from(value).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPACES: [ColorSpace; 8] = [
+        ColorSpace::Srgb,
+        ColorSpace::LinearSrgb,
+        ColorSpace::Hsl,
+        ColorSpace::Hsv,
+        ColorSpace::Lab,
+        ColorSpace::Lch,
+        ColorSpace::Oklab,
+        ColorSpace::Oklch,
+    ];
+
+    fn assert_near<const N: usize>(actual: [f32; N], expected: [f32; N], tolerance: f32) {
+        assert!(
+            actual
+                .iter()
+                .zip(&expected)
+                .all(|(a, e)| (a - e).abs() <= tolerance),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    fn rgba(c: Rgba) -> [f32; 4] {
+        [c.r, c.g, c.b, c.a]
+    }
+
+    #[test]
+    fn converts_to_lab_and_lch_relative_to_d50() {
+        let red = Rgba::new(1.0, 0.0, 0.0, 1.0);
+        let lab = Lab::from(red);
+        assert_near([lab.l, lab.a, lab.b], [54.29, 80.80, 69.89], 0.05);
+        let lch = Lch::from(red);
+        assert_near([lch.l, lch.c, lch.h], [54.29, 106.84, 40.85], 0.05);
+        let white = Lab::from(Rgba::new(1.0, 1.0, 1.0, 0.5));
+        assert_near(
+            [white.l, white.a, white.b, white.alpha],
+            [100.0, 0.0, 0.0, 0.5],
+            0.01,
+        );
+        let c = Rgba::new(0.2, 0.4, 0.6, 1.0);
+        assert_near(rgba(Lab::from(c).into()), rgba(c), 1e-4);
+        assert_near(rgba(Lch::from(c).into()), rgba(c), 1e-4);
+    }
+
+    #[test]
+    fn converts_to_oklab_and_oklch() {
+        let red = Rgba::new(1.0, 0.0, 0.0, 1.0);
+        let lab = Oklab::from(red);
+        assert_near([lab.l, lab.a, lab.b], [0.62796, 0.22486, 0.12585], 1e-4);
+        let lch = Oklch::from(red);
+        assert_near([lch.l, lch.c, lch.h], [0.62796, 0.25768, 29.23], 1e-2);
+        let white = Oklab::from(Rgba::new(1.0, 1.0, 1.0, 1.0));
+        assert_near([white.l, white.a, white.b], [1.0, 0.0, 0.0], 1e-4);
+        let c = Rgba::new(0.2, 0.4, 0.6, 1.0);
+        assert_near(rgba(Oklab::from(c).into()), rgba(c), 1e-4);
+        assert_near(rgba(Oklch::from(c).into()), rgba(c), 1e-4);
+    }
+
+    #[test]
+    fn converts_to_hsl_hsv_and_hwb() {
+        let c = Rgba::new(0.2, 0.4, 0.6, 0.5);
+        let hsl = Hsla::from(c);
+        assert_near(
+            [hsl.h, hsl.s, hsl.l, hsl.alpha],
+            [210.0, 0.5, 0.4, 0.5],
+            1e-4,
+        );
+        let hsv = Hsva::from(c);
+        assert_near([hsv.h, hsv.s, hsv.v], [210.0, 2.0 / 3.0, 0.6], 1e-4);
+        assert_near(rgba(hsl.into()), rgba(c), 1e-5);
+        assert_near(rgba(hsv.into()), rgba(c), 1e-5);
+        assert_near(
+            rgba(Hsla::new(-60.0, 1.0, 0.5, 1.0).into()),
+            [1.0, 0.0, 1.0, 1.0],
+            1e-5,
+        );
+        // HWB is HSV with whiteness (1 - s) * v and blackness 1 - v.
+        let hwb = "hwb(210 20% 40%)".parse::<Rgba>().unwrap();
+        assert_near(rgba(hwb), [0.2, 0.4, 0.6, 1.0], 1e-5);
+        let gray = Hsla::from(Rgba::new(0.5, 0.5, 0.5, 1.0));
+        assert_eq!((gray.h, gray.s), (0.0, 0.0));
+    }
+
+    #[test]
+    fn lerp_returns_the_ends_in_every_space() {
+        let a = Rgba::new(0.9, 0.2, 0.1, 1.0);
+        let b = Rgba::new(0.1, 0.5, 0.8, 0.5);
+        for space in SPACES {
+            assert_near(rgba(a.lerp(b, 0.0, space)), rgba(a), 1e-4);
+            assert_near(rgba(a.lerp(b, 1.0, space)), rgba(b), 1e-4);
+            assert_near(rgba(a.lerp(a, 0.5, space)), rgba(a), 1e-4);
+            assert!((a.lerp(b, 0.5, space).a - 0.75).abs() < 1e-6, "{space:?}");
+        }
+    }
+
+    #[test]
+    fn lerp_midpoints() {
+        let red = Rgba::new(1.0, 0.0, 0.0, 1.0);
+        let blue = Rgba::new(0.0, 0.0, 1.0, 1.0);
+        let black = Rgba::new(0.0, 0.0, 0.0, 1.0);
+        let white = Rgba::new(1.0, 1.0, 1.0, 1.0);
+        assert_near(
+            rgba(red.lerp(blue, 0.5, ColorSpace::Srgb)),
+            [0.5, 0.0, 0.5, 1.0],
+            1e-6,
+        );
+        let gray = black.lerp(white, 0.5, ColorSpace::LinearSrgb);
+        assert_near(rgba(gray), [0.7354, 0.7354, 0.7354, 1.0], 1e-4);
+        let gray = Lab::from(black.lerp(white, 0.5, ColorSpace::Lab));
+        assert_near([gray.l, gray.a, gray.b], [50.0, 0.0, 0.0], 1e-2);
+        let gray = Oklab::from(black.lerp(white, 0.5, ColorSpace::Oklab));
+        assert_near([gray.l, gray.a, gray.b], [0.5, 0.0, 0.0], 1e-4);
+        // Red and blue are 120 degrees apart through magenta in HSL.
+        let mid = Hsla::from(red.lerp(blue, 0.5, ColorSpace::Hsl));
+        assert_near([mid.h, mid.s, mid.l], [300.0, 1.0, 0.5], 1e-3);
+    }
+
+    #[test]
+    fn lerp_premultiplies_alpha() {
+        let red = Rgba::new(1.0, 0.0, 0.0, 1.0);
+        let clear_blue = Rgba::new(0.0, 0.0, 1.0, 0.0);
+        for space in [ColorSpace::Srgb, ColorSpace::LinearSrgb, ColorSpace::Oklab] {
+            // A transparent color contributes no color of its own.
+            let mid = red.lerp(clear_blue, 0.5, space);
+            assert_near(rgba(mid), [1.0, 0.0, 0.0, 0.5], 1e-4);
+        }
+        let straight = red.lerp_with(clear_blue, 0.5, ColorSpace::Srgb, false);
+        assert_near(rgba(straight), [0.5, 0.0, 0.5, 0.5], 1e-6);
+        let mid = Rgba::new(1.0, 0.0, 0.0, 0.25).lerp(
+            Rgba::new(0.0, 0.0, 1.0, 0.75),
+            0.5,
+            ColorSpace::Srgb,
+        );
+        assert_near(rgba(mid), [0.25, 0.0, 0.75, 0.5], 1e-6);
+        let gone = red.with_alpha(0.0).lerp(clear_blue, 0.5, ColorSpace::Srgb);
+        assert_eq!(gone.a, 0.0);
+    }
+
+    #[test]
+    fn lerp_takes_the_shorter_hue_arc() {
+        let a = Rgba::from(Hsla::new(350.0, 1.0, 0.5, 1.0));
+        let b = Rgba::from(Hsla::new(10.0, 1.0, 0.5, 1.0));
+        for space in [ColorSpace::Hsl, ColorSpace::Hsv] {
+            assert_near(rgba(a.lerp(b, 0.5, space)), [1.0, 0.0, 0.0, 1.0], 1e-4);
+            assert_near(rgba(b.lerp(a, 0.5, space)), [1.0, 0.0, 0.0, 1.0], 1e-4);
+        }
+        let a = Rgba::from(Lch::new(60.0, 30.0, 340.0, 1.0));
+        let b = Rgba::from(Lch::new(60.0, 30.0, 20.0, 1.0));
+        let h = Lch::from(a.lerp(b, 0.5, ColorSpace::Lch)).h;
+        assert!(!(0.5..=359.5).contains(&h), "{h}");
+        let a = Rgba::from(Oklch::new(0.7, 0.1, 340.0, 1.0));
+        let b = Rgba::from(Oklch::new(0.7, 0.1, 20.0, 1.0));
+        let h = Oklch::from(b.lerp(a, 0.25, ColorSpace::Oklch)).h;
+        assert!((h - 10.0).abs() < 0.5, "{h}");
+        // A gray has no hue, so the other color's hue is kept.
+        let white = Rgba::new(1.0, 1.0, 1.0, 1.0);
+        let blue = Rgba::new(0.0, 0.0, 1.0, 1.0);
+        let mid = Hsla::from(white.lerp(blue, 0.5, ColorSpace::Hsl));
+        assert_near([mid.h, mid.s, mid.l], [240.0, 0.5, 0.75], 1e-3);
+    }
+}
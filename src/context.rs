@@ -3,7 +3,7 @@ pub mod d3d11;
 pub mod d3d12;
 
 use crate::*;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use windows::Win32::{
     Graphics::Direct2D::*,
     Graphics::DirectWrite::*,
@@ -68,6 +68,7 @@ pub struct Context<T: Backend> {
     pub(crate) font_file_loader: Arc<FontFileLoader>,
     pub(crate) wic_imaging_factory: IWICImagingFactory2,
     pub(crate) default_text_format: TextFormat,
+    pub(crate) brush_cache: Arc<Mutex<BrushCache<ID2D1Brush>>>,
 }
 
 impl<T> Context<T>
@@ -98,14 +99,21 @@ where
             font_file_loader,
             wic_imaging_factory,
             default_text_format,
+            brush_cache: Arc::new(Mutex::new(BrushCache::default())),
         })
     }
 
     #[inline]
     pub fn set_dpi(&mut self, dpi_x: f32, dpi_y: f32) {
+        let (mut old_x, mut old_y) = (0.0, 0.0);
         unsafe {
+            self.d2d1_device_context.GetDpi(&mut old_x, &mut old_y);
             self.d2d1_device_context.SetDpi(dpi_x, dpi_y);
         }
+        // Pattern and conic brushes are rasterized at the DPI they were created with.
+        if (old_x, old_y) != (dpi_x, dpi_y) {
+            self.clear_brush_cache();
+        }
     }
 
     #[inline]
@@ -119,24 +127,60 @@ where
         self.default_text_format = format.clone();
     }
 
+    #[inline]
+    pub fn clear_brush_cache(&self) {
+        self.brush_cache.lock().unwrap().clear();
+    }
+
     #[inline]
     pub fn draw<R>(
         &self,
         target: &T::RenderTarget,
         f: impl FnOnce(DrawCommand<T>) -> R,
     ) -> Result<R> {
-        let ctx = &self.d2d1_device_context;
         self.backend.begin_draw(target);
+        let (ret, draw_error) = self.draw_bitmap(target.bitmap(), f);
+        self.backend.end_draw(target)?;
+        self.end_frame(ret, draw_error)
+    }
+
+    #[inline]
+    pub fn draw_offscreen<R>(
+        &self,
+        target: &OffscreenTarget,
+        f: impl FnOnce(DrawCommand<T>) -> R,
+    ) -> Result<R> {
+        let (ret, draw_error) = self.draw_bitmap(target.bitmap(), f);
+        self.end_frame(ret, draw_error)
+    }
+
+    // The target is unbound even when `EndDraw` fails, and its error comes after the
+    // errors of the drawing itself.
+    fn draw_bitmap<R>(
+        &self,
+        bitmap: &ID2D1Bitmap1,
+        f: impl FnOnce(DrawCommand<T>) -> R,
+    ) -> (R, Option<Error>) {
+        let ctx = &self.d2d1_device_context;
         unsafe {
-            ctx.SetTarget(target.bitmap());
+            ctx.SetTarget(bitmap);
             ctx.BeginDraw();
         }
-        let ret = f(DrawCommand::new(self));
+        let state = DrawState::default();
+        let ret = f(DrawCommand::new(self, &state));
+        let draw_error = state.finish(ctx);
+        let end_draw = unsafe { ctx.EndDraw(None, None) };
         unsafe {
-            ctx.EndDraw(None, None)?;
             ctx.SetTarget(None);
         }
-        self.backend.end_draw(target)?;
+        (ret, draw_error.or(end_draw.err().map(Error::from)))
+    }
+
+    fn end_frame<R>(&self, ret: R, draw_error: Option<Error>) -> Result<R> {
+        let brush_error = self.brush_cache.lock().unwrap().end_frame();
+        if let Some(e) = draw_error.or(brush_error) {
+            return Err(e);
+        }
         Ok(ret)
     }
 }
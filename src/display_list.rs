@@ -0,0 +1,579 @@
+use crate::*;
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shape {
+    Rect(Rect<f32>),
+    RoundedRect(RoundedRect),
+    Circle(Circle),
+    Ellipse(Ellipse),
+    Line(Line),
+    Path(PathFigure),
+}
+
+impl From<Rect<f32>> for Shape {
+    #[inline]
+    fn from(value: Rect<f32>) -> Self {
+        Self::Rect(value)
+    }
+}
+
+impl From<RoundedRect> for Shape {
+    #[inline]
+    fn from(value: RoundedRect) -> Self {
+        Self::RoundedRect(value)
+    }
+}
+
+impl From<Circle> for Shape {
+    #[inline]
+    fn from(value: Circle) -> Self {
+        Self::Circle(value)
+    }
+}
+
+impl From<Ellipse> for Shape {
+    #[inline]
+    fn from(value: Ellipse) -> Self {
+        Self::Ellipse(value)
+    }
+}
+
+impl From<Line> for Shape {
+    #[inline]
+    fn from(value: Line) -> Self {
+        Self::Line(value)
+    }
+}
+
+impl From<PathFigure> for Shape {
+    #[inline]
+    fn from(value: PathFigure) -> Self {
+        Self::Path(value)
+    }
+}
+
+impl Fill for Shape {
+    #[inline]
+    fn fill(&self, sink: &mut dyn GeometrySink) {
+        match self {
+            Self::Rect(shape) => shape.fill(sink),
+            Self::RoundedRect(shape) => shape.fill(sink),
+            Self::Circle(shape) => shape.fill(sink),
+            Self::Ellipse(shape) => shape.fill(sink),
+            Self::Line(_) => {}
+            Self::Path(shape) => shape.fill(sink),
+        }
+    }
+}
+
+impl Stroke for Shape {
+    #[inline]
+    fn stroke(&self, sink: &mut dyn GeometrySink) {
+        match self {
+            Self::Rect(shape) => shape.stroke(sink),
+            Self::RoundedRect(shape) => shape.stroke(sink),
+            Self::Circle(shape) => shape.stroke(sink),
+            Self::Ellipse(shape) => shape.stroke(sink),
+            Self::Line(shape) => shape.stroke(sink),
+            Self::Path(shape) => shape.stroke(sink),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DashPattern {
+    Solid,
+    Dash,
+    Dot,
+    DashDot,
+    DashDotDot,
+    Custom(Vec<f32>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DashDesc {
+    pub cap: CapStyle,
+    pub pattern: DashPattern,
+    pub offset: f32,
+}
+
+// An owned `StrokeStyleProperties`, so that recorded strokes do not borrow custom dashes.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StrokeDesc {
+    pub start_cap: CapStyle,
+    pub end_cap: CapStyle,
+    pub line_join: LineJoin,
+    pub dash: Option<DashDesc>,
+}
+
+impl StrokeDesc {
+    pub fn properties(&self) -> StrokeStyleProperties<'_> {
+        StrokeStyleProperties {
+            start_cap: self.start_cap,
+            end_cap: self.end_cap,
+            line_join: self.line_join,
+            dash: self.dash.as_ref().map(|dash| Dash {
+                cap: dash.cap,
+                style: match &dash.pattern {
+                    DashPattern::Solid => DashStyle::Solid,
+                    DashPattern::Dash => DashStyle::Dash,
+                    DashPattern::Dot => DashStyle::Dot,
+                    DashPattern::DashDot => DashStyle::DashDot,
+                    DashPattern::DashDotDot => DashStyle::DashDotDot,
+                    DashPattern::Custom(dashes) => DashStyle::Custom(dashes),
+                },
+                offset: dash.offset,
+            }),
+        }
+    }
+}
+
+impl From<&StrokeStyleProperties<'_>> for StrokeDesc {
+    fn from(value: &StrokeStyleProperties<'_>) -> Self {
+        Self {
+            start_cap: value.start_cap,
+            end_cap: value.end_cap,
+            line_join: value.line_join,
+            dash: value.dash.as_ref().map(|dash| DashDesc {
+                cap: dash.cap,
+                pattern: match dash.style {
+                    DashStyle::Solid => DashPattern::Solid,
+                    DashStyle::Dash => DashPattern::Dash,
+                    DashStyle::Dot => DashPattern::Dot,
+                    DashStyle::DashDot => DashPattern::DashDot,
+                    DashStyle::DashDotDot => DashPattern::DashDotDot,
+                    DashStyle::Custom(dashes) => DashPattern::Custom(dashes.to_vec()),
+                },
+                offset: dash.offset,
+            }),
+        }
+    }
+}
+
+// Images are recorded by index into the slice given to `DisplayList::replay`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageId(pub usize);
+
+impl ImageId {
+    #[inline]
+    pub fn get<T>(self, images: &[T]) -> Result<&T> {
+        images.get(self.0).ok_or(Error::ImageNotFound(self.0))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DisplayCommand {
+    Clear(Rgba),
+    Fill {
+        shape: Shape,
+        brush: BrushDesc,
+    },
+    Stroke {
+        shape: Shape,
+        brush: BrushDesc,
+        width: f32,
+        style: Option<StrokeDesc>,
+    },
+    DrawText {
+        text: String,
+        position: Point<f32>,
+        font: FontDesc,
+        brush: BrushDesc,
+    },
+    DrawImage {
+        image: ImageId,
+        src_rect: Option<Rect<f32>>,
+        dest_rect: Rect<f32>,
+        opacity: Option<f32>,
+        interpolation: Interpolation,
+    },
+    PushClip(Rect<f32>),
+    PopClip,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayList {
+    commands: Vec<DisplayCommand>,
+}
+
+impl DisplayList {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn clear(&mut self, color: impl Into<Rgba>) {
+        self.commands.push(DisplayCommand::Clear(color.into()));
+    }
+
+    #[inline]
+    pub fn fill(&mut self, shape: impl Into<Shape>, brush: impl Into<BrushDesc>) {
+        self.commands.push(DisplayCommand::Fill {
+            shape: shape.into(),
+            brush: brush.into(),
+        });
+    }
+
+    #[inline]
+    pub fn stroke(
+        &mut self,
+        shape: impl Into<Shape>,
+        brush: impl Into<BrushDesc>,
+        width: f32,
+        style: Option<&StrokeStyleProperties>,
+    ) {
+        self.commands.push(DisplayCommand::Stroke {
+            shape: shape.into(),
+            brush: brush.into(),
+            width,
+            style: style.map(StrokeDesc::from),
+        });
+    }
+
+    #[inline]
+    pub fn draw_text(
+        &mut self,
+        text: impl Into<String>,
+        position: impl Into<Point<f32>>,
+        font: &FontDesc,
+        brush: impl Into<BrushDesc>,
+    ) {
+        self.commands.push(DisplayCommand::DrawText {
+            text: text.into(),
+            position: position.into(),
+            font: font.clone(),
+            brush: brush.into(),
+        });
+    }
+
+    #[inline]
+    pub fn draw_image(
+        &mut self,
+        image: ImageId,
+        src_rect: Option<Rect<f32>>,
+        dest_rect: impl Into<Rect<f32>>,
+        opacity: Option<f32>,
+        interpolation: Interpolation,
+    ) {
+        self.commands.push(DisplayCommand::DrawImage {
+            image,
+            src_rect,
+            dest_rect: dest_rect.into(),
+            opacity,
+            interpolation,
+        });
+    }
+
+    #[inline]
+    pub fn push_clip(&mut self, rect: impl Into<Rect<f32>>) {
+        self.commands.push(DisplayCommand::PushClip(rect.into()));
+    }
+
+    #[inline]
+    pub fn pop_clip(&mut self) {
+        self.commands.push(DisplayCommand::PopClip);
+    }
+
+    #[inline]
+    pub fn push(&mut self, command: DisplayCommand) {
+        self.commands.push(command);
+    }
+
+    #[inline]
+    pub fn commands(&self) -> &[DisplayCommand] {
+        &self.commands
+    }
+
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, DisplayCommand> {
+        self.commands.iter()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    // Yields the index and both sides of every position at which the lists differ,
+    // with `None` past the end of the shorter list.
+    pub fn diff<'a>(
+        &'a self,
+        other: &'a DisplayList,
+    ) -> impl Iterator<
+        Item = (
+            usize,
+            Option<&'a DisplayCommand>,
+            Option<&'a DisplayCommand>,
+        ),
+    > + 'a {
+        let len = self.commands.len().max(other.commands.len());
+        (0..len).filter_map(|i| {
+            let a = self.commands.get(i);
+            let b = other.commands.get(i);
+            (a != b).then_some((i, a, b))
+        })
+    }
+
+    // Text is drawn with the system font of the recorded family. An `ImageId` without an
+    // image in `images` fails the replay.
+    #[cfg(windows)]
+    pub fn replay<T>(&self, cmd: &DrawCommand<T>, images: &[Image]) -> Result<()>
+    where
+        T: Backend,
+    {
+        let mut format: Option<(&FontDesc, TextFormat)> = None;
+        let mut styles: Vec<(&StrokeDesc, StrokeStyle)> = Vec::new();
+        for command in &self.commands {
+            match command {
+                DisplayCommand::Clear(color) => cmd.clear(*color),
+                DisplayCommand::Fill { shape, brush } => cmd.fill(shape, brush),
+                DisplayCommand::Stroke {
+                    shape,
+                    brush,
+                    width,
+                    style,
+                } => {
+                    // Direct2D stroke styles are immutable, so one is made per distinct desc.
+                    let style = match style {
+                        Some(desc) => match styles.iter().position(|(d, _)| *d == desc) {
+                            Some(i) => Some(&styles[i].1),
+                            None => {
+                                let style = StrokeStyle::new(cmd.context(), &desc.properties())?;
+                                styles.push((desc, style));
+                                styles.last().map(|(_, style)| style)
+                            }
+                        },
+                        None => None,
+                    };
+                    cmd.stroke(shape, brush, *width, style);
+                }
+                DisplayCommand::DrawText {
+                    text,
+                    position,
+                    font,
+                    brush,
+                } => {
+                    // Consecutive texts usually share a font, so its format is reused.
+                    let format = match &mut format {
+                        Some((desc, format)) if *desc == font => format,
+                        _ => &format.insert((font, font.text_format(cmd.context())?)).1,
+                    };
+                    let layout = TextLayout::new(cmd.context())
+                        .text(text)
+                        .format(format)
+                        .build()?;
+                    cmd.draw_text(&layout, *position, brush)?;
+                }
+                DisplayCommand::DrawImage {
+                    image,
+                    src_rect,
+                    dest_rect,
+                    opacity,
+                    interpolation,
+                } => {
+                    let image = image.get(images)?;
+                    cmd.draw_image(image, *src_rect, *dest_rect, *opacity, *interpolation);
+                }
+                DisplayCommand::PushClip(rect) => cmd.push_clip(*rect),
+                DisplayCommand::PopClip => cmd.pop_clip(),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a DisplayList {
+    type Item = &'a DisplayCommand;
+    type IntoIter = std::slice::Iter<'a, DisplayCommand>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.iter()
+    }
+}
+
+impl IntoIterator for DisplayList {
+    type Item = DisplayCommand;
+    type IntoIter = std::vec::IntoIter<DisplayCommand>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.into_iter()
+    }
+}
+
+impl FromIterator<DisplayCommand> for DisplayList {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = DisplayCommand>>(iter: I) -> Self {
+        Self {
+            commands: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<DisplayCommand> for DisplayList {
+    #[inline]
+    fn extend<I: IntoIterator<Item = DisplayCommand>>(&mut self, iter: I) {
+        self.commands.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_commands_in_order() {
+        let mut list = DisplayList::new();
+        list.clear((0.0, 0.0, 0.0, 1.0));
+        list.push_clip((0.0, 0.0, 100.0, 100.0));
+        list.fill(Rect::new(10.0, 10.0, 20.0, 20.0), Rgba::RED);
+        list.stroke(
+            Circle::new((50.0, 50.0), 10.0),
+            Rgba::BLUE,
+            2.0,
+            Some(&StrokeStyleProperties {
+                dash: Some(Dash {
+                    style: DashStyle::Custom(&[1.0, 2.0]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        );
+        list.draw_text(
+            "pnte",
+            (1.0, 2.0),
+            &FontDesc::new("Arial", 12.0),
+            Rgba::WHITE,
+        );
+        list.draw_image(
+            ImageId(0),
+            None,
+            (0.0, 0.0, 8.0, 8.0),
+            None,
+            Interpolation::Linear,
+        );
+        list.pop_clip();
+        assert_eq!(
+            list.commands(),
+            &[
+                DisplayCommand::Clear(Rgba::new(0.0, 0.0, 0.0, 1.0)),
+                DisplayCommand::PushClip(Rect::new(0.0, 0.0, 100.0, 100.0)),
+                DisplayCommand::Fill {
+                    shape: Shape::Rect(Rect::new(10.0, 10.0, 20.0, 20.0)),
+                    brush: BrushDesc::Solid(Rgba::RED),
+                },
+                DisplayCommand::Stroke {
+                    shape: Shape::Circle(Circle::new((50.0, 50.0), 10.0)),
+                    brush: BrushDesc::Solid(Rgba::BLUE),
+                    width: 2.0,
+                    style: Some(StrokeDesc {
+                        start_cap: CapStyle::Flat,
+                        end_cap: CapStyle::Flat,
+                        line_join: LineJoin::Miter,
+                        dash: Some(DashDesc {
+                            cap: CapStyle::Flat,
+                            pattern: DashPattern::Custom(vec![1.0, 2.0]),
+                            offset: 0.0,
+                        }),
+                    }),
+                },
+                DisplayCommand::DrawText {
+                    text: "pnte".to_string(),
+                    position: Point::new(1.0, 2.0),
+                    font: FontDesc::new("Arial", 12.0),
+                    brush: BrushDesc::Solid(Rgba::WHITE),
+                },
+                DisplayCommand::DrawImage {
+                    image: ImageId(0),
+                    src_rect: None,
+                    dest_rect: Rect::new(0.0, 0.0, 8.0, 8.0),
+                    opacity: None,
+                    interpolation: Interpolation::Linear,
+                },
+                DisplayCommand::PopClip,
+            ]
+        );
+    }
+
+    #[test]
+    fn records_paths_and_the_font_of_each_text() {
+        let triangle = PathFigure::new((0.0, 0.0))
+            .line_to((4.0, 0.0))
+            .line_to((2.0, 3.0))
+            .close();
+        let title = FontDesc::new("Georgia", 24.0).weight(700.0);
+        let body = FontDesc::new("Segoe UI", 12.0).italic(true);
+        let mut list = DisplayList::new();
+        list.fill(triangle.clone(), Rgba::RED);
+        list.draw_text("title", (0.0, 0.0), &title, Rgba::BLACK);
+        list.draw_text("body", (0.0, 30.0), &body, Rgba::BLACK);
+        let fonts = list
+            .iter()
+            .filter_map(|command| match command {
+                DisplayCommand::DrawText { text, font, .. } => Some((text.as_str(), font)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(fonts, [("title", &title), ("body", &body)]);
+        let DisplayCommand::Fill { shape, .. } = &list.commands()[0] else {
+            panic!("{:?}", list.commands()[0]);
+        };
+        assert_eq!(shape, &Shape::Path(triangle.clone()));
+        let mut sink = RecordingSink::new();
+        shape.fill(&mut sink);
+        shape.stroke(&mut sink);
+        assert_eq!(
+            sink.primitives(),
+            [Primitive::Path(triangle.clone()), Primitive::Path(triangle)]
+        );
+    }
+
+    #[test]
+    fn diff_reports_changed_and_missing_commands() {
+        let mut a = DisplayList::new();
+        a.clear(Rgba::BLACK);
+        a.fill(Rect::new(0.0, 0.0, 1.0, 1.0), Rgba::RED);
+        let mut b = a.clone();
+        assert_eq!(a.diff(&b).count(), 0);
+        b.push(DisplayCommand::Clear(Rgba::WHITE));
+        b.push_clip((0.0, 0.0, 1.0, 1.0));
+        let diff: Vec<_> = a.diff(&b).map(|(i, x, _)| (i, x.is_some())).collect();
+        assert_eq!(diff, [(2, false), (3, false)]);
+    }
+
+    #[test]
+    fn looks_up_images_by_id() {
+        let images = ["a", "b"];
+        assert_eq!(ImageId(1).get(&images).unwrap(), &"b");
+        assert!(matches!(
+            ImageId(2).get(&images),
+            Err(Error::ImageNotFound(2))
+        ));
+    }
+
+    #[test]
+    fn builds_on_another_thread() {
+        let list = std::thread::spawn(|| {
+            let mut list = DisplayList::new();
+            list.fill(Circle::new((0.0, 0.0), 1.0), Rgba::RED);
+            list
+        })
+        .join()
+        .unwrap();
+        assert_eq!(list.len(), 1);
+    }
+}
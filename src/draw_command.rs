@@ -1,46 +1,114 @@
 use crate::*;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::mem::ManuallyDrop;
 use windows::Win32::Graphics::{Direct2D::Common::*, Direct2D::*};
 
-pub trait Fill {
-    fn fill(&self, dc: &ID2D1DeviceContext5, brush: &ID2D1Brush);
+pub trait Clip {
+    fn clip_geometry(&self, factory: &ID2D1Factory6) -> Result<ID2D1Geometry>;
+
+    #[inline]
+    fn clip_rect(&self) -> Option<Rect<f32>> {
+        None
+    }
 }
 
-pub trait Stroke {
-    fn stroke(
-        &self,
-        dc: &ID2D1DeviceContext5,
-        brush: &ID2D1Brush,
-        width: f32,
-        style: Option<&ID2D1StrokeStyle1>,
-    );
+// Draws primitives straight to the device context, filling them or, when `stroke` is set,
+// outlining them with its width and style.
+struct Direct2DSink<'a> {
+    dc: &'a ID2D1DeviceContext5,
+    factory: &'a ID2D1Factory6,
+    brush: &'a ID2D1Brush,
+    stroke: Option<(f32, Option<&'a ID2D1StrokeStyle>)>,
+    state: &'a DrawState,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-#[repr(i32)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub enum CapStyle {
-    Flat = D2D1_CAP_STYLE_FLAT.0,
-    Square = D2D1_CAP_STYLE_SQUARE.0,
-    Round = D2D1_CAP_STYLE_ROUND.0,
-    Triangle = D2D1_CAP_STYLE_TRIANGLE.0,
+impl Direct2DSink<'_> {
+    fn geometry(&self, geometry: &ID2D1Geometry) {
+        unsafe {
+            match self.stroke {
+                Some((width, style)) => self.dc.DrawGeometry(geometry, self.brush, width, style),
+                None => self.dc.FillGeometry(geometry, self.brush, None),
+            }
+        }
+    }
+}
+
+impl GeometrySink for Direct2DSink<'_> {
+    fn rect(&mut self, rect: &Rect<f32>) {
+        let rect = (*rect).into();
+        unsafe {
+            match self.stroke {
+                Some((width, style)) => self.dc.DrawRectangle(&rect, self.brush, width, style),
+                None => self.dc.FillRectangle(&rect, self.brush),
+            }
+        }
+    }
+
+    fn rounded_rect(&mut self, rounded_rect: &RoundedRect) {
+        let rounded_rect = (*rounded_rect).into();
+        unsafe {
+            match self.stroke {
+                Some((width, style)) => {
+                    self.dc
+                        .DrawRoundedRectangle(&rounded_rect, self.brush, width, style)
+                }
+                None => self.dc.FillRoundedRectangle(&rounded_rect, self.brush),
+            }
+        }
+    }
+
+    fn ellipse(&mut self, ellipse: &Ellipse) {
+        let ellipse = (*ellipse).into();
+        unsafe {
+            match self.stroke {
+                Some((width, style)) => self.dc.DrawEllipse(&ellipse, self.brush, width, style),
+                None => self.dc.FillEllipse(&ellipse, self.brush),
+            }
+        }
+    }
+
+    fn line(&mut self, line: &Line) {
+        if let Some((width, style)) = self.stroke {
+            unsafe {
+                self.dc
+                    .DrawLine(line.0.into(), line.1.into(), self.brush, width, style);
+            }
+        }
+    }
+
+    // Like Direct2D's own failures, a geometry that cannot be created is reported when
+    // drawing ends.
+    fn path(&mut self, figure: &PathFigure) {
+        match create_path_geometry(self.factory, figure) {
+            Ok(geometry) => self.geometry(&geometry.into()),
+            Err(e) => self.state.record_error(e),
+        }
+    }
+
+    fn native_geometry(&mut self, geometry: &dyn Any) -> bool {
+        match geometry.downcast_ref::<ID2D1PathGeometry>() {
+            Some(geometry) => {
+                self.geometry(geometry);
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 impl From<CapStyle> for D2D1_CAP_STYLE {
     #[inline]
     fn from(value: CapStyle) -> Self {
-        D2D1_CAP_STYLE(value as i32)
+        match value {
+            CapStyle::Flat => D2D1_CAP_STYLE_FLAT,
+            CapStyle::Square => D2D1_CAP_STYLE_SQUARE,
+            CapStyle::Round => D2D1_CAP_STYLE_ROUND,
+            CapStyle::Triangle => D2D1_CAP_STYLE_TRIANGLE,
+        }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub enum LineJoin {
-    Miter,
-    Bevel,
-    Round,
-    MiterOrBevel(f32),
-}
-
 impl LineJoin {
     fn value(&self) -> (D2D1_LINE_JOIN, f32) {
         match self {
@@ -52,17 +120,6 @@ impl LineJoin {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub enum DashStyle<'a> {
-    Solid,
-    Dash,
-    Dot,
-    DashDot,
-    DashDotDot,
-    Custom(&'a [f32]),
-}
-
 impl DashStyle<'_> {
     fn value(&self) -> (D2D1_DASH_STYLE, Option<&[f32]>) {
         match self {
@@ -76,46 +133,6 @@ impl DashStyle<'_> {
     }
 }
 
-#[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Dash<'a> {
-    pub cap: CapStyle,
-    pub style: DashStyle<'a>,
-    pub offset: f32,
-}
-
-impl Default for Dash<'_> {
-    #[inline]
-    fn default() -> Self {
-        Self {
-            cap: CapStyle::Flat,
-            style: DashStyle::Solid,
-            offset: 0.0,
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct StrokeStyleProperties<'a> {
-    pub start_cap: CapStyle,
-    pub end_cap: CapStyle,
-    pub line_join: LineJoin,
-    pub dash: Option<Dash<'a>>,
-}
-
-impl Default for StrokeStyleProperties<'_> {
-    #[inline]
-    fn default() -> Self {
-        Self {
-            start_cap: CapStyle::Flat,
-            end_cap: CapStyle::Flat,
-            line_join: LineJoin::Miter,
-            dash: None,
-        }
-    }
-}
-
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct StrokeStyle(ID2D1StrokeStyle1);
 
@@ -151,13 +168,159 @@ impl StrokeStyle {
     }
 }
 
+impl From<AntialiasMode> for D2D1_ANTIALIAS_MODE {
+    #[inline]
+    fn from(value: AntialiasMode) -> Self {
+        match value {
+            AntialiasMode::PerPrimitive => D2D1_ANTIALIAS_MODE_PER_PRIMITIVE,
+            AntialiasMode::Aliased => D2D1_ANTIALIAS_MODE_ALIASED,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct LayerParams<'a> {
+    pub content_bounds: Option<Rect<f32>>,
+    pub mask: Option<&'a Path>,
+    pub mask_antialias: AntialiasMode,
+    pub mask_transform: Matrix3x2,
+    pub opacity: f32,
+    pub opacity_brush: Option<&'a dyn Brush>,
+}
+
+impl<'a> LayerParams<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn content_bounds(mut self, bounds: impl Into<Rect<f32>>) -> Self {
+        self.content_bounds = Some(bounds.into());
+        self
+    }
+
+    #[inline]
+    pub fn mask(mut self, mask: &'a Path) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    #[inline]
+    pub fn mask_antialias(mut self, mode: AntialiasMode) -> Self {
+        self.mask_antialias = mode;
+        self
+    }
+
+    #[inline]
+    pub fn mask_transform(mut self, transform: impl Into<Matrix3x2>) -> Self {
+        self.mask_transform = transform.into();
+        self
+    }
+
+    #[inline]
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    #[inline]
+    pub fn opacity_brush(mut self, brush: &'a dyn Brush) -> Self {
+        self.opacity_brush = Some(brush);
+        self
+    }
+}
+
+impl Default for LayerParams<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            content_bounds: None,
+            mask: None,
+            mask_antialias: AntialiasMode::PerPrimitive,
+            mask_transform: Matrix3x2::IDENTITY,
+            opacity: 1.0,
+            opacity_brush: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Pushed {
+    AxisAlignedClip,
+    GeometryClip,
+    Layer,
+}
+
+impl Pushed {
+    fn state_kind(self) -> StateKind {
+        match self {
+            Self::AxisAlignedClip | Self::GeometryClip => StateKind::Clip,
+            Self::Layer => StateKind::Layer,
+        }
+    }
+
+    fn pop(self, dc: &ID2D1DeviceContext5) {
+        unsafe {
+            match self {
+                Self::AxisAlignedClip => dc.PopAxisAlignedClip(),
+                Self::GeometryClip | Self::Layer => dc.PopLayer(),
+            }
+        }
+    }
+}
+
+// `clip` is the bounds of all clips pushed so far, in target coordinates.
+#[derive(Clone, Copy, Debug)]
+struct PushedState {
+    kind: Pushed,
+    clip: Option<Rect<f32>>,
+}
+
+// Clips and layers pushed during `Context::draw`. The first imbalance is kept so that it
+// can be reported instead of the error `EndDraw` would return, and so is the first error
+// of a drawing operation that has no `Result` of its own.
+#[derive(Default)]
+pub(crate) struct DrawState {
+    stack: RefCell<Vec<PushedState>>,
+    unbalanced: Cell<Option<(StateKind, isize)>>,
+    error: RefCell<Option<Error>>,
+}
+
+impl DrawState {
+    fn record(&self, kind: StateKind, depth: isize) {
+        if self.unbalanced.get().is_none() {
+            self.unbalanced.set(Some((kind, depth)));
+        }
+    }
+
+    fn record_error(&self, e: Error) {
+        self.error.borrow_mut().get_or_insert(e);
+    }
+
+    pub(crate) fn finish(&self, dc: &ID2D1DeviceContext5) -> Option<Error> {
+        let mut stack = self.stack.borrow_mut();
+        if let Some(top) = stack.last() {
+            self.record(top.kind.state_kind(), stack.len() as isize);
+        }
+        while let Some(state) = stack.pop() {
+            state.kind.pop(dc);
+        }
+        self.unbalanced
+            .take()
+            .map(|(kind, depth)| Error::UnbalancedState { kind, depth })
+            .or_else(|| self.error.take())
+    }
+}
+
 pub struct DrawCommand<'a, T: Backend> {
     ctx: &'a Context<T>,
+    state: &'a DrawState,
 }
 
 impl<'a, T: Backend> DrawCommand<'a, T> {
-    pub(crate) fn new(ctx: &'a Context<T>) -> Self {
-        Self { ctx }
+    pub(crate) fn new(ctx: &'a Context<T>, state: &'a DrawState) -> Self {
+        Self { ctx, state }
     }
 
     #[inline]
@@ -174,21 +337,38 @@ impl<'a, T: Backend> DrawCommand<'a, T> {
     }
 
     #[inline]
-    pub fn fill(&self, object: &impl Fill, brush: &impl Brush) {
-        let dc = &self.ctx.d2d1_device_context;
-        object.fill(dc, brush.handle());
+    pub fn fill(&self, object: &impl Fill, brush: &impl AsBrush) {
+        if let Some(brush) = brush.as_brush(self.ctx) {
+            object.fill(&mut self.sink(&brush, None));
+        }
     }
 
     #[inline]
     pub fn stroke(
         &self,
         object: &impl Stroke,
-        brush: &impl Brush,
+        brush: &impl AsBrush,
         width: f32,
         stroke_style: Option<&StrokeStyle>,
     ) {
-        let dc = &self.ctx.d2d1_device_context;
-        object.stroke(dc, brush.handle(), width, stroke_style.map(|s| &s.0));
+        if let Some(brush) = brush.as_brush(self.ctx) {
+            let stroke = (width, stroke_style.map(|s| (&s.0).into()));
+            object.stroke(&mut self.sink(&brush, Some(stroke)));
+        }
+    }
+
+    fn sink<'b>(
+        &'b self,
+        brush: &'b ID2D1Brush,
+        stroke: Option<(f32, Option<&'b ID2D1StrokeStyle>)>,
+    ) -> Direct2DSink<'b> {
+        Direct2DSink {
+            dc: &self.ctx.d2d1_device_context,
+            factory: self.ctx.backend.d2d1_factory(),
+            brush,
+            stroke,
+            state: self.state,
+        }
     }
 
     #[inline]
@@ -196,8 +376,11 @@ impl<'a, T: Backend> DrawCommand<'a, T> {
         &self,
         text: impl Text,
         position: impl Into<Point<f32>>,
-        brush: &impl Brush,
+        brush: &impl AsBrush,
     ) -> Result<()> {
+        let Some(brush) = brush.as_brush(self.ctx) else {
+            return Ok(());
+        };
         unsafe {
             let dc = &self.ctx.d2d1_device_context;
             let position: Point<f32> = position.into();
@@ -205,7 +388,7 @@ impl<'a, T: Backend> DrawCommand<'a, T> {
                 position.into(),
                 text.layout(self.ctx, &self.ctx.default_text_format)?
                     .handle(),
-                brush.handle(),
+                &*brush,
                 None,
                 0,
                 D2D1_DRAW_TEXT_OPTIONS_ENABLE_COLOR_FONT | D2D1_DRAW_TEXT_OPTIONS_CLIP,
@@ -241,17 +424,211 @@ impl<'a, T: Backend> DrawCommand<'a, T> {
     #[inline]
     pub fn push_clip(&self, rect: impl Into<Rect<f32>>) {
         let rect: Rect<f32> = rect.into();
-        let dc = &self.ctx.d2d1_device_context;
-        unsafe {
-            dc.PushAxisAlignedClip(&rect.into(), D2D1_ANTIALIAS_MODE_PER_PRIMITIVE);
+        self.push_axis_aligned_clip(rect, AntialiasMode::PerPrimitive);
+    }
+
+    pub fn push_clip_with(&self, clip: &impl Clip, antialias: AntialiasMode) -> Result<()> {
+        if let Some(rect) = clip.clip_rect() {
+            self.push_axis_aligned_clip(rect, antialias);
+            return Ok(());
         }
+        let geometry = clip.clip_geometry(self.ctx.backend.d2d1_factory())?;
+        let transform = self.transform();
+        let bounds: Rect<f32> = unsafe { geometry.GetBounds(Some(&transform.into()))?.into() };
+        self.push_layer_parameters(D2D1_LAYER_PARAMETERS1 {
+            contentBounds: INFINITE_RECT.into(),
+            geometricMask: ManuallyDrop::new(Some(geometry)),
+            maskAntialiasMode: antialias.into(),
+            maskTransform: Matrix3x2::IDENTITY.into(),
+            opacity: 1.0,
+            opacityBrush: ManuallyDrop::new(None),
+            layerOptions: D2D1_LAYER_OPTIONS1_NONE,
+        });
+        self.push_state(Pushed::GeometryClip, Some(bounds));
+        Ok(())
     }
 
     #[inline]
     pub fn pop_clip(&self) {
+        self.pop_state(StateKind::Clip);
+    }
+
+    #[inline]
+    pub fn clipped<R>(&self, rect: impl Into<Rect<f32>>, f: impl FnOnce(&Self) -> R) -> R {
+        let scope = self.clip_scope(rect);
+        f(&scope)
+    }
+
+    #[inline]
+    pub fn clipped_with<R>(
+        &self,
+        clip: &impl Clip,
+        antialias: AntialiasMode,
+        f: impl FnOnce(&Self) -> R,
+    ) -> Result<R> {
+        let scope = self.clip_scope_with(clip, antialias)?;
+        Ok(f(&scope))
+    }
+
+    #[inline]
+    pub fn clip_scope(&self, rect: impl Into<Rect<f32>>) -> DrawScope<'_, 'a, T> {
+        self.push_clip(rect);
+        DrawScope::new(self, StateKind::Clip)
+    }
+
+    #[inline]
+    pub fn clip_scope_with(
+        &self,
+        clip: &impl Clip,
+        antialias: AntialiasMode,
+    ) -> Result<DrawScope<'_, 'a, T>> {
+        self.push_clip_with(clip, antialias)?;
+        Ok(DrawScope::new(self, StateKind::Clip))
+    }
+
+    #[inline]
+    pub fn clip_depth(&self) -> usize {
+        self.depth(StateKind::Clip)
+    }
+
+    #[inline]
+    pub fn layer_depth(&self) -> usize {
+        self.depth(StateKind::Layer)
+    }
+
+    pub fn clip_bounds(&self) -> Option<Rect<f32>> {
+        let clip = self.state.stack.borrow().last()?.clip?;
+        let inverse = self.transform().invert()?;
+        Some(inverse.transform_rect(clip))
+    }
+
+    pub fn push_layer(&self, params: &LayerParams) {
+        self.push_layer_parameters(D2D1_LAYER_PARAMETERS1 {
+            contentBounds: params.content_bounds.unwrap_or(INFINITE_RECT).into(),
+            geometricMask: ManuallyDrop::new(params.mask.map(|mask| mask.handle().clone().into())),
+            maskAntialiasMode: params.mask_antialias.into(),
+            maskTransform: params.mask_transform.into(),
+            opacity: params.opacity,
+            opacityBrush: ManuallyDrop::new(
+                params.opacity_brush.map(|brush| brush.handle().clone()),
+            ),
+            layerOptions: D2D1_LAYER_OPTIONS1_NONE,
+        });
+        self.push_state(Pushed::Layer, None);
+    }
+
+    #[inline]
+    pub fn pop_layer(&self) {
+        self.pop_state(StateKind::Layer);
+    }
+
+    #[inline]
+    pub fn layer_scope(&self, params: &LayerParams) -> DrawScope<'_, 'a, T> {
+        self.push_layer(params);
+        DrawScope::new(self, StateKind::Layer)
+    }
+
+    #[inline]
+    pub fn with_layer<R>(&self, params: &LayerParams, f: impl FnOnce(&Self) -> R) -> R {
+        let scope = self.layer_scope(params);
+        f(&scope)
+    }
+
+    fn transform(&self) -> Matrix3x2 {
+        let mut transform = Default::default();
+        unsafe {
+            self.ctx.d2d1_device_context.GetTransform(&mut transform);
+        }
+        transform.into()
+    }
+
+    fn push_axis_aligned_clip(&self, rect: Rect<f32>, antialias: AntialiasMode) {
+        let bounds = self.transform().transform_rect(rect);
         let dc = &self.ctx.d2d1_device_context;
         unsafe {
-            dc.PopAxisAlignedClip();
+            dc.PushAxisAlignedClip(&rect.into(), antialias.into());
         }
+        self.push_state(Pushed::AxisAlignedClip, Some(bounds));
     }
+
+    fn push_layer_parameters(&self, params: D2D1_LAYER_PARAMETERS1) {
+        let dc = &self.ctx.d2d1_device_context;
+        unsafe {
+            dc.PushLayer(&params, None);
+        }
+        ManuallyDrop::into_inner(params.geometricMask);
+        ManuallyDrop::into_inner(params.opacityBrush);
+    }
+
+    fn depth(&self, kind: StateKind) -> usize {
+        self.state
+            .stack
+            .borrow()
+            .iter()
+            .filter(|state| state.kind.state_kind() == kind)
+            .count()
+    }
+
+    fn pop_state(&self, kind: StateKind) {
+        let mut stack = self.state.stack.borrow_mut();
+        let depth = stack.len() as isize;
+        let Some(top) = stack.pop() else {
+            self.state.record(kind, -1);
+            return;
+        };
+        if top.kind.state_kind() != kind {
+            self.state.record(top.kind.state_kind(), depth);
+        }
+        top.kind.pop(&self.ctx.d2d1_device_context);
+    }
+
+    fn push_state(&self, kind: Pushed, clip: Option<Rect<f32>>) {
+        let mut stack = self.state.stack.borrow_mut();
+        let parent = stack.last().and_then(|state| state.clip);
+        let clip = match (parent, clip) {
+            (Some(a), Some(b)) => Some(intersect(a, b)),
+            (a, b) => a.or(b),
+        };
+        stack.push(PushedState { kind, clip });
+    }
+}
+
+pub struct DrawScope<'b, 'a, T: Backend> {
+    cmd: &'b DrawCommand<'a, T>,
+    kind: StateKind,
+}
+
+impl<'b, 'a, T: Backend> DrawScope<'b, 'a, T> {
+    fn new(cmd: &'b DrawCommand<'a, T>, kind: StateKind) -> Self {
+        Self { cmd, kind }
+    }
+}
+
+impl<'a, T: Backend> std::ops::Deref for DrawScope<'_, 'a, T> {
+    type Target = DrawCommand<'a, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.cmd
+    }
+}
+
+impl<T: Backend> Drop for DrawScope<'_, '_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.cmd.pop_state(self.kind);
+    }
+}
+
+const INFINITE_RECT: Rect<f32> = Rect::new(-f32::MAX, -f32::MAX, f32::MAX, f32::MAX);
+
+fn intersect(a: Rect<f32>, b: Rect<f32>) -> Rect<f32> {
+    let left = a.left.max(b.left);
+    let top = a.top.max(b.top);
+    Rect::new(
+        left,
+        top,
+        a.right.min(b.right).max(left),
+        a.bottom.min(b.bottom).max(top),
+    )
 }
@@ -1,15 +1,65 @@
+#[cfg(windows)]
 use windows::Win32::Foundation::{D2DERR_RECREATE_TARGET, E_NOINTERFACE};
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StateKind {
+    Clip,
+    Layer,
+}
+
+impl std::fmt::Display for StateKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Clip => write!(f, "clip"),
+            Self::Layer => write!(f, "layer"),
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("need to recreate the target")]
     NeedRecreateTarget,
     #[error("No interface")]
     NoInterface,
+    #[error("cannot convert a negative value to an unsigned integer")]
+    NegativeToUnsigned,
+    #[error("cannot convert NaN to an unsigned integer")]
+    NanToUnsigned,
+    #[error("cannot parse \"{0}\" as a color")]
+    ParseColor(String),
+    #[error("unbalanced {kind} push/pop (depth {depth})")]
+    UnbalancedState { kind: StateKind, depth: isize },
+    #[error("expected {expected} pixels but got {actual}")]
+    PixelCount { expected: usize, actual: usize },
+    #[error("image {0} not found")]
+    ImageNotFound(usize),
+    #[error("the region is out of bounds")]
+    OutOfBounds,
+    #[error("font \"{0}\" not found")]
+    FontNotFound(String),
+    #[error("invalid font data")]
+    InvalidFont,
+    #[error("invalid image data")]
+    InvalidImage,
+    #[error("unsupported image format")]
+    UnsupportedImage,
+    #[error("the image is too large for its format")]
+    ImageTooLarge,
+    #[error("stride {0} is shorter than a row")]
+    InvalidStride(u32),
+    #[error("expected at least {expected} bytes but got {actual}")]
+    ByteCount { expected: usize, actual: usize },
+    #[error("the animation has no frames")]
+    NoFrames,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[cfg(windows)]
     #[error(transparent)]
     Api(windows::core::Error),
 }
 
+#[cfg(windows)]
 impl From<windows::core::Error> for Error {
     #[inline]
     fn from(value: windows::core::Error) -> Self {
@@ -0,0 +1,68 @@
+#[cfg(windows)]
+use crate::*;
+
+#[derive(Clone, Debug)]
+pub enum Font<'a, 'b> {
+    System(&'b str),
+    File(&'a std::path::Path, &'b str),
+    Memory(&'a [u8], &'b str),
+}
+
+// The parts of a `TextFormat` that describe how text looks, for targets that do not go
+// through DirectWrite.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontDesc {
+    pub family: String,
+    pub size: f32,
+    pub weight: f32,
+    pub italic: bool,
+}
+
+impl FontDesc {
+    #[inline]
+    pub fn new(family: impl Into<String>, size: impl Into<f32>) -> Self {
+        Self {
+            family: family.into(),
+            size: size.into(),
+            weight: 400.0,
+            italic: false,
+        }
+    }
+
+    #[inline]
+    pub fn weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    #[inline]
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    // Looks the family up among the system fonts.
+    #[cfg(windows)]
+    pub fn text_format<T: Backend>(&self, ctx: &Context<T>) -> Result<TextFormat> {
+        TextFormat::new(ctx)
+            .font(Font::System(&self.family))
+            .size(self.size)
+            .weight(self.weight.clamp(1.0, 1000.0))
+            .italic(self.italic)
+            .build()
+    }
+}
+
+#[cfg(windows)]
+impl From<&TextFormat> for FontDesc {
+    #[inline]
+    fn from(value: &TextFormat) -> Self {
+        Self {
+            family: value.font_name().to_string(),
+            size: value.size(),
+            weight: value.weight(),
+            italic: value.italic(),
+        }
+    }
+}
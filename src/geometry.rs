@@ -1,4 +1,85 @@
-use windows::Win32::Graphics::Direct2D::Common::*;
+#[cfg(windows)]
+mod win32;
+
+use crate::{Error, Result};
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+    impl Sealed for i32 {}
+    impl Sealed for u32 {}
+}
+
+pub trait Scalar: Copy + private::Sealed {
+    fn to_f64(self) -> f64;
+    fn from_f64(value: f64) -> Result<Self>;
+}
+
+impl Scalar for f32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Result<Self> {
+        Ok(value.clamp(f32::MIN as f64, f32::MAX as f64) as f32)
+    }
+}
+
+impl Scalar for f64 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Result<Self> {
+        Ok(value)
+    }
+}
+
+impl Scalar for i32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Result<Self> {
+        Ok(value as i32)
+    }
+}
+
+impl Scalar for u32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Result<Self> {
+        if value.is_nan() {
+            return Err(Error::NanToUnsigned);
+        }
+        if value < 0.0 {
+            return Err(Error::NegativeToUnsigned);
+        }
+        Ok(value as u32)
+    }
+}
+
+#[inline]
+fn convert<T: Scalar, U: Scalar>(value: T, f: fn(f64) -> f64) -> Result<U> {
+    U::from_f64(f(value.to_f64()))
+}
+
+#[inline]
+fn identity(value: f64) -> f64 {
+    value
+}
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(C)]
@@ -22,43 +103,29 @@ impl<T> Point<T> {
     }
 }
 
-impl From<Point<f32>> for D2D_POINT_2F {
+impl<T: Scalar> Point<T> {
     #[inline]
-    fn from(value: Point<f32>) -> Self {
-        Self {
-            x: value.x,
-            y: value.y,
-        }
+    pub fn cast<U: Scalar>(self) -> Result<Point<U>> {
+        self.convert(identity)
     }
-}
 
-impl From<Point<u32>> for D2D_POINT_2U {
     #[inline]
-    fn from(value: Point<u32>) -> Self {
-        Self {
-            x: value.x,
-            y: value.y,
-        }
+    pub fn round<U: Scalar>(self) -> Result<Point<U>> {
+        self.convert(f64::round)
     }
-}
 
-impl From<D2D_POINT_2F> for Point<f32> {
     #[inline]
-    fn from(value: D2D_POINT_2F) -> Self {
-        Self {
-            x: value.x,
-            y: value.y,
-        }
+    pub fn floor<U: Scalar>(self) -> Result<Point<U>> {
+        self.convert(f64::floor)
     }
-}
 
-impl From<D2D_POINT_2U> for Point<u32> {
     #[inline]
-    fn from(value: D2D_POINT_2U) -> Self {
-        Self {
-            x: value.x,
-            y: value.y,
-        }
+    pub fn ceil<U: Scalar>(self) -> Result<Point<U>> {
+        self.convert(f64::ceil)
+    }
+
+    fn convert<U: Scalar>(self, f: fn(f64) -> f64) -> Result<Point<U>> {
+        Ok(Point::new(convert(self.x, f)?, convert(self.y, f)?))
     }
 }
 
@@ -86,43 +153,29 @@ impl<T> Size<T> {
     }
 }
 
-impl From<Size<f32>> for D2D_SIZE_F {
+impl<T: Scalar> Size<T> {
     #[inline]
-    fn from(value: Size<f32>) -> Self {
-        Self {
-            width: value.width,
-            height: value.height,
-        }
+    pub fn cast<U: Scalar>(self) -> Result<Size<U>> {
+        self.convert(identity)
     }
-}
 
-impl From<Size<u32>> for D2D_SIZE_U {
     #[inline]
-    fn from(value: Size<u32>) -> Self {
-        Self {
-            width: value.width,
-            height: value.height,
-        }
+    pub fn round<U: Scalar>(self) -> Result<Size<U>> {
+        self.convert(f64::round)
     }
-}
 
-impl From<D2D_SIZE_F> for Size<f32> {
     #[inline]
-    fn from(value: D2D_SIZE_F) -> Self {
-        Self {
-            width: value.width,
-            height: value.height,
-        }
+    pub fn floor<U: Scalar>(self) -> Result<Size<U>> {
+        self.convert(f64::floor)
     }
-}
 
-impl From<D2D_SIZE_U> for Size<u32> {
     #[inline]
-    fn from(value: D2D_SIZE_U) -> Self {
-        Self {
-            width: value.width,
-            height: value.height,
-        }
+    pub fn ceil<U: Scalar>(self) -> Result<Size<U>> {
+        self.convert(f64::ceil)
+    }
+
+    fn convert<U: Scalar>(self, f: fn(f64) -> f64) -> Result<Size<U>> {
+        Ok(Size::new(convert(self.width, f)?, convert(self.height, f)?))
     }
 }
 
@@ -202,51 +255,39 @@ impl<T> Rect<T> {
     }
 }
 
-impl From<Rect<f32>> for D2D_RECT_F {
+impl<T: Scalar> Rect<T> {
     #[inline]
-    fn from(value: Rect<f32>) -> Self {
-        Self {
-            left: value.left,
-            top: value.top,
-            right: value.right,
-            bottom: value.bottom,
-        }
+    pub fn cast<U: Scalar>(self) -> Result<Rect<U>> {
+        self.convert(identity, identity)
     }
-}
 
-impl From<Rect<u32>> for D2D_RECT_U {
     #[inline]
-    fn from(value: Rect<u32>) -> Self {
-        Self {
-            left: value.left,
-            top: value.top,
-            right: value.right,
-            bottom: value.bottom,
-        }
+    pub fn round<U: Scalar>(self) -> Result<Rect<U>> {
+        self.convert(f64::round, f64::round)
     }
-}
 
-impl From<D2D_RECT_F> for Rect<f32> {
     #[inline]
-    fn from(value: D2D_RECT_F) -> Self {
-        Self {
-            left: value.left,
-            top: value.top,
-            right: value.right,
-            bottom: value.bottom,
-        }
+    pub fn floor<U: Scalar>(self) -> Result<Rect<U>> {
+        self.convert(f64::floor, f64::floor)
     }
-}
 
-impl From<D2D_RECT_U> for Rect<u32> {
     #[inline]
-    fn from(value: D2D_RECT_U) -> Self {
-        Self {
-            left: value.left,
-            top: value.top,
-            right: value.right,
-            bottom: value.bottom,
-        }
+    pub fn ceil<U: Scalar>(self) -> Result<Rect<U>> {
+        self.convert(f64::ceil, f64::ceil)
+    }
+
+    #[inline]
+    pub fn round_out<U: Scalar>(self) -> Result<Rect<U>> {
+        self.convert(f64::floor, f64::ceil)
+    }
+
+    fn convert<U: Scalar>(self, lt: fn(f64) -> f64, rb: fn(f64) -> f64) -> Result<Rect<U>> {
+        Ok(Rect::new(
+            convert(self.left, lt)?,
+            convert(self.top, lt)?,
+            convert(self.right, rb)?,
+            convert(self.bottom, rb)?,
+        ))
     }
 }
 
@@ -281,23 +322,29 @@ impl<T> Vector<T> {
     }
 }
 
-impl From<Vector<f32>> for D2D_VECTOR_2F {
+impl<T: Scalar> Vector<T> {
     #[inline]
-    fn from(value: Vector<f32>) -> Self {
-        Self {
-            x: value.x,
-            y: value.y,
-        }
+    pub fn cast<U: Scalar>(self) -> Result<Vector<U>> {
+        self.convert(identity)
     }
-}
 
-impl From<D2D_VECTOR_2F> for Vector<f32> {
     #[inline]
-    fn from(value: D2D_VECTOR_2F) -> Self {
-        Self {
-            x: value.x,
-            y: value.y,
-        }
+    pub fn round<U: Scalar>(self) -> Result<Vector<U>> {
+        self.convert(f64::round)
+    }
+
+    #[inline]
+    pub fn floor<U: Scalar>(self) -> Result<Vector<U>> {
+        self.convert(f64::floor)
+    }
+
+    #[inline]
+    pub fn ceil<U: Scalar>(self) -> Result<Vector<U>> {
+        self.convert(f64::ceil)
+    }
+
+    fn convert<U: Scalar>(self, f: fn(f64) -> f64) -> Result<Vector<U>> {
+        Ok(Vector::new(convert(self.x, f)?, convert(self.y, f)?))
     }
 }
 
@@ -310,3 +357,219 @@ impl<T> From<(T, T)> for Vector<T> {
         }
     }
 }
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Matrix3x2 {
+    pub m11: f32,
+    pub m12: f32,
+    pub m21: f32,
+    pub m22: f32,
+    pub m31: f32,
+    pub m32: f32,
+}
+
+impl Matrix3x2 {
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(m11: f32, m12: f32, m21: f32, m22: f32, m31: f32, m32: f32) -> Self {
+        Self {
+            m11,
+            m12,
+            m21,
+            m22,
+            m31,
+            m32,
+        }
+    }
+
+    #[inline]
+    pub const fn translation(x: f32, y: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    #[inline]
+    pub const fn scale(x: f32, y: f32) -> Self {
+        Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    #[inline]
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    #[inline]
+    pub fn skew(angle_x: f32, angle_y: f32) -> Self {
+        Self::new(
+            1.0,
+            angle_y.to_radians().tan(),
+            angle_x.to_radians().tan(),
+            1.0,
+            0.0,
+            0.0,
+        )
+    }
+
+    #[inline]
+    pub fn then(self, other: Self) -> Self {
+        Self::new(
+            self.m11 * other.m11 + self.m12 * other.m21,
+            self.m11 * other.m12 + self.m12 * other.m22,
+            self.m21 * other.m11 + self.m22 * other.m21,
+            self.m21 * other.m12 + self.m22 * other.m22,
+            self.m31 * other.m11 + self.m32 * other.m21 + other.m31,
+            self.m31 * other.m12 + self.m32 * other.m22 + other.m32,
+        )
+    }
+
+    #[inline]
+    pub fn determinant(&self) -> f32 {
+        self.m11 * self.m22 - self.m12 * self.m21
+    }
+
+    #[inline]
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(Self::new(
+            self.m22 / det,
+            -self.m12 / det,
+            -self.m21 / det,
+            self.m11 / det,
+            (self.m21 * self.m32 - self.m22 * self.m31) / det,
+            (self.m12 * self.m31 - self.m11 * self.m32) / det,
+        ))
+    }
+
+    #[inline]
+    pub fn transform_point(&self, point: impl Into<Point<f32>>) -> Point<f32> {
+        let p: Point<f32> = point.into();
+        Point::new(
+            p.x * self.m11 + p.y * self.m21 + self.m31,
+            p.x * self.m12 + p.y * self.m22 + self.m32,
+        )
+    }
+
+    #[inline]
+    pub fn transform_rect(&self, rect: impl Into<Rect<f32>>) -> Rect<f32> {
+        let rect: Rect<f32> = rect.into();
+        let points = [
+            self.transform_point((rect.left, rect.top)),
+            self.transform_point((rect.right, rect.top)),
+            self.transform_point((rect.left, rect.bottom)),
+            self.transform_point((rect.right, rect.bottom)),
+        ];
+        points.iter().skip(1).fold(
+            Rect::new(points[0].x, points[0].y, points[0].x, points[0].y),
+            |r, p| {
+                Rect::new(
+                    r.left.min(p.x),
+                    r.top.min(p.y),
+                    r.right.max(p.x),
+                    r.bottom.max(p.y),
+                )
+            },
+        )
+    }
+
+    #[inline]
+    pub fn transform_vector(&self, vector: impl Into<Vector<f32>>) -> Vector<f32> {
+        let v: Vector<f32> = vector.into();
+        Vector::new(
+            v.x * self.m11 + v.y * self.m21,
+            v.x * self.m12 + v.y * self.m22,
+        )
+    }
+}
+
+impl Default for Matrix3x2 {
+    #[inline]
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl std::ops::Mul for Matrix3x2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        self.then(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn casts_saturate_and_reject_negative_unsigned() {
+        assert_eq!(
+            Point::new(1.7f32, -1.7).cast::<i32>().unwrap(),
+            Point::new(1, -1)
+        );
+        assert_eq!(
+            Point::new(1.5f32, -1.5).round::<i32>().unwrap(),
+            Point::new(2, -2)
+        );
+        assert_eq!(
+            Size::new(1e10f64, -1e10).cast::<i32>().unwrap(),
+            Size::new(i32::MAX, i32::MIN)
+        );
+        assert_eq!(
+            Size::new(f32::NAN, 1.0).cast::<i32>().unwrap(),
+            Size::new(0, 1)
+        );
+        assert_eq!(
+            Size::new(1e10f64, 0.0).cast::<u32>().unwrap(),
+            Size::new(u32::MAX, 0)
+        );
+        assert_eq!(
+            Vector::new(1e300f64, -1e300).cast::<f32>().unwrap(),
+            Vector::new(f32::MAX, f32::MIN)
+        );
+        assert_eq!(
+            Point::new(0.5f32, 2.9).cast::<u32>().unwrap(),
+            Point::new(0, 2)
+        );
+        assert!(matches!(
+            Point::new(-0.5f32, 2.9).cast::<u32>(),
+            Err(Error::NegativeToUnsigned)
+        ));
+        assert!(matches!(
+            Size::new(f32::NAN, 1.0).cast::<u32>(),
+            Err(Error::NanToUnsigned)
+        ));
+        assert_eq!(
+            Size::new(-0.4f32, 0.0).round::<u32>().unwrap(),
+            Size::new(0, 0)
+        );
+        assert!(matches!(
+            Point::new(-1.0f32, 0.0).cast::<u32>(),
+            Err(Error::NegativeToUnsigned)
+        ));
+        assert!(matches!(
+            Size::new(0.0f32, -0.2).floor::<u32>(),
+            Err(Error::NegativeToUnsigned)
+        ));
+    }
+
+    #[test]
+    fn rect_rounding_directions() {
+        let rect = Rect::new(0.5f32, -1.5, 2.2, 3.7);
+        assert_eq!(rect.round_out::<i32>().unwrap(), Rect::new(0, -2, 3, 4));
+        assert_eq!(rect.floor::<i32>().unwrap(), Rect::new(0, -2, 2, 3));
+        assert_eq!(rect.ceil::<i32>().unwrap(), Rect::new(1, -1, 3, 4));
+        assert_eq!(rect.round::<i32>().unwrap(), Rect::new(1, -2, 2, 4));
+        assert_eq!(rect.cast::<i32>().unwrap(), Rect::new(0, -1, 2, 3));
+        assert!(rect.round_out::<u32>().is_err());
+        assert_eq!(
+            Rect::new(0.1f32, 0.1, 9.9, 9.9).round_out::<u32>().unwrap(),
+            Rect::new(0, 0, 10, 10)
+        );
+    }
+}
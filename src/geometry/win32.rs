@@ -0,0 +1,238 @@
+use super::*;
+use windows::Win32::Foundation::{POINT, RECT, SIZE};
+use windows::Win32::Graphics::Direct2D::Common::*;
+
+impl From<Point<f32>> for D2D_POINT_2F {
+    #[inline]
+    fn from(value: Point<f32>) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<Point<u32>> for D2D_POINT_2U {
+    #[inline]
+    fn from(value: Point<u32>) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<D2D_POINT_2F> for Point<f32> {
+    #[inline]
+    fn from(value: D2D_POINT_2F) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<D2D_POINT_2U> for Point<u32> {
+    #[inline]
+    fn from(value: D2D_POINT_2U) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<Point<i32>> for POINT {
+    #[inline]
+    fn from(value: Point<i32>) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<POINT> for Point<i32> {
+    #[inline]
+    fn from(value: POINT) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<Size<f32>> for D2D_SIZE_F {
+    #[inline]
+    fn from(value: Size<f32>) -> Self {
+        Self {
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+impl From<Size<u32>> for D2D_SIZE_U {
+    #[inline]
+    fn from(value: Size<u32>) -> Self {
+        Self {
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+impl From<D2D_SIZE_F> for Size<f32> {
+    #[inline]
+    fn from(value: D2D_SIZE_F) -> Self {
+        Self {
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+impl From<D2D_SIZE_U> for Size<u32> {
+    #[inline]
+    fn from(value: D2D_SIZE_U) -> Self {
+        Self {
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+impl From<Size<i32>> for SIZE {
+    #[inline]
+    fn from(value: Size<i32>) -> Self {
+        Self {
+            cx: value.width,
+            cy: value.height,
+        }
+    }
+}
+
+impl From<SIZE> for Size<i32> {
+    #[inline]
+    fn from(value: SIZE) -> Self {
+        Self {
+            width: value.cx,
+            height: value.cy,
+        }
+    }
+}
+
+impl From<Rect<f32>> for D2D_RECT_F {
+    #[inline]
+    fn from(value: Rect<f32>) -> Self {
+        Self {
+            left: value.left,
+            top: value.top,
+            right: value.right,
+            bottom: value.bottom,
+        }
+    }
+}
+
+impl From<Rect<u32>> for D2D_RECT_U {
+    #[inline]
+    fn from(value: Rect<u32>) -> Self {
+        Self {
+            left: value.left,
+            top: value.top,
+            right: value.right,
+            bottom: value.bottom,
+        }
+    }
+}
+
+impl From<D2D_RECT_F> for Rect<f32> {
+    #[inline]
+    fn from(value: D2D_RECT_F) -> Self {
+        Self {
+            left: value.left,
+            top: value.top,
+            right: value.right,
+            bottom: value.bottom,
+        }
+    }
+}
+
+impl From<D2D_RECT_U> for Rect<u32> {
+    #[inline]
+    fn from(value: D2D_RECT_U) -> Self {
+        Self {
+            left: value.left,
+            top: value.top,
+            right: value.right,
+            bottom: value.bottom,
+        }
+    }
+}
+
+impl From<Rect<i32>> for RECT {
+    #[inline]
+    fn from(value: Rect<i32>) -> Self {
+        Self {
+            left: value.left,
+            top: value.top,
+            right: value.right,
+            bottom: value.bottom,
+        }
+    }
+}
+
+impl From<RECT> for Rect<i32> {
+    #[inline]
+    fn from(value: RECT) -> Self {
+        Self {
+            left: value.left,
+            top: value.top,
+            right: value.right,
+            bottom: value.bottom,
+        }
+    }
+}
+
+impl From<Vector<f32>> for D2D_VECTOR_2F {
+    #[inline]
+    fn from(value: Vector<f32>) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<D2D_VECTOR_2F> for Vector<f32> {
+    #[inline]
+    fn from(value: D2D_VECTOR_2F) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<Matrix3x2> for windows_numerics::Matrix3x2 {
+    #[inline]
+    fn from(value: Matrix3x2) -> Self {
+        Self {
+            M11: value.m11,
+            M12: value.m12,
+            M21: value.m21,
+            M22: value.m22,
+            M31: value.m31,
+            M32: value.m32,
+        }
+    }
+}
+
+impl From<windows_numerics::Matrix3x2> for Matrix3x2 {
+    #[inline]
+    fn from(value: windows_numerics::Matrix3x2) -> Self {
+        Self::new(
+            value.M11, value.M12, value.M21, value.M22, value.M31, value.M32,
+        )
+    }
+}
@@ -0,0 +1,568 @@
+use crate::*;
+
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "Vec<GradientStop>")
+)]
+pub struct GradientStops(Vec<GradientStop>);
+
+impl GradientStops {
+    pub fn new<G>(stops: &[G]) -> Self
+    where
+        G: Into<GradientStop> + Clone,
+    {
+        let mut stops: Vec<GradientStop> = stops
+            .iter()
+            .cloned()
+            .map(|stop| stop.into())
+            .filter(|stop| stop.position.is_finite())
+            .collect();
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Self(stops)
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[GradientStop] {
+        &self.0
+    }
+
+    #[inline]
+    pub fn color_at(&self, t: f32) -> Rgba {
+        self.interpolate(t, &GradientOptions::default())
+    }
+
+    pub fn interpolate(&self, t: f32, options: &GradientOptions) -> Rgba {
+        let stops = &self.0;
+        let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+            return Rgba::TRANSPARENT;
+        };
+        // NaN would compare false everywhere and reach past the first stop.
+        if !t.is_finite() || t <= first.position {
+            return first.color;
+        }
+        if t >= last.position {
+            return last.color;
+        }
+        let i = stops.partition_point(|stop| stop.position <= t);
+        let (a, b) = (&stops[i - 1], &stops[i]);
+        let f = (t - a.position) / (b.position - a.position);
+        options.mix(a.color, b.color, f)
+    }
+
+    #[inline]
+    pub fn sample(&self, t: f32, mode: GradientMode) -> Rgba {
+        self.color_at(mode.apply(t))
+    }
+
+    #[inline]
+    pub fn bake(&self, len: usize) -> Vec<Rgba8> {
+        self.bake_with(len, &GradientOptions::default())
+    }
+
+    pub fn bake_with(&self, len: usize, options: &GradientOptions) -> Vec<Rgba8> {
+        let d = len.saturating_sub(1).max(1) as f32;
+        (0..len)
+            .map(|i| self.interpolate(i as f32 / d, options).into())
+            .collect()
+    }
+
+    // Direct2D and SVG can only interpolate in sRGB or linear RGB, so perceptual gradients
+    // are approximated by dense stops that are interpolated in Oklab beforehand.
+    #[cfg(any(windows, feature = "svg"))]
+    pub(crate) fn expand(&self, options: &GradientOptions) -> Vec<GradientStop> {
+        if options.color_space != GradientColorSpace::Perceptual {
+            return self.0.clone();
+        }
+        self.subdivide(options)
+    }
+
+    #[cfg(any(windows, feature = "svg", feature = "pdf"))]
+    pub(crate) fn subdivide(&self, options: &GradientOptions) -> Vec<GradientStop> {
+        const DIVISIONS: usize = 16;
+        let mut stops = Vec::with_capacity(self.0.len() * DIVISIONS);
+        for pair in self.0.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            // A hard stop stays a single break instead of many stops at one position.
+            if a.position == b.position {
+                stops.push(*a);
+                continue;
+            }
+            for i in 0..DIVISIONS {
+                let f = i as f32 / DIVISIONS as f32;
+                stops.push(GradientStop::new(
+                    a.position + (b.position - a.position) * f,
+                    options.mix(a.color, b.color, f),
+                ));
+            }
+        }
+        stops.extend(self.0.last().cloned());
+        stops
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GradientColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+    Perceptual,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BufferPrecision {
+    #[default]
+    Unorm8,
+    Float16,
+    Float32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlphaInterpolation {
+    Straight,
+    #[default]
+    Premultiplied,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientOptions {
+    pub color_space: GradientColorSpace,
+    pub precision: BufferPrecision,
+    pub alpha: AlphaInterpolation,
+}
+
+impl GradientOptions {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn color_space(mut self, color_space: GradientColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    #[inline]
+    pub fn precision(mut self, precision: BufferPrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    #[inline]
+    pub fn alpha(mut self, alpha: AlphaInterpolation) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    fn mix(&self, a: Rgba, b: Rgba, t: f32) -> Rgba {
+        let space = match self.color_space {
+            GradientColorSpace::Srgb => ColorSpace::Srgb,
+            GradientColorSpace::Linear => ColorSpace::LinearSrgb,
+            GradientColorSpace::Perceptual => ColorSpace::Oklab,
+        };
+        a.lerp_with(b, t, space, self.alpha == AlphaInterpolation::Premultiplied)
+    }
+}
+
+impl<G> From<&[G]> for GradientStops
+where
+    G: Into<GradientStop> + Clone,
+{
+    #[inline]
+    fn from(value: &[G]) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Vec<GradientStop>> for GradientStops {
+    #[inline]
+    fn from(value: Vec<GradientStop>) -> Self {
+        Self::new(&value)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientStop {
+    pub position: f32,
+    pub color: Rgba,
+}
+
+impl GradientStop {
+    #[inline]
+    pub fn new(position: f32, color: impl Into<Rgba>) -> Self {
+        Self {
+            position,
+            color: color.into(),
+        }
+    }
+}
+
+impl<C> From<(f32, C)> for GradientStop
+where
+    C: Into<Rgba>,
+{
+    #[inline]
+    fn from(value: (f32, C)) -> Self {
+        Self {
+            position: value.0,
+            color: value.1.into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GradientMode {
+    Clamp,
+    Mirror,
+    Wrap,
+}
+
+impl GradientMode {
+    #[inline]
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Self::Clamp => t.clamp(0.0, 1.0),
+            Self::Wrap => t.rem_euclid(1.0),
+            Self::Mirror => {
+                let t = t.rem_euclid(2.0);
+                if t > 1.0 { 2.0 - t } else { t }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GradientShape {
+    Linear {
+        start: Point<f32>,
+        end: Point<f32>,
+    },
+    Radial {
+        ellipse: Ellipse,
+        offset: Vector<f32>,
+    },
+    // A sweep wider than a full turn covers the circle once, as every point has one angle.
+    Conic {
+        center: Point<f32>,
+        start_angle: f32,
+        end_angle: f32,
+    },
+}
+
+impl GradientShape {
+    pub fn position(&self, point: impl Into<Point<f32>>) -> Option<f32> {
+        let p: Point<f32> = point.into();
+        match *self {
+            Self::Linear { start, end } => {
+                let dx = end.x - start.x;
+                let dy = end.y - start.y;
+                let len2 = dx * dx + dy * dy;
+                if len2 <= 0.0 {
+                    return None;
+                }
+                Some(((p.x - start.x) * dx + (p.y - start.y) * dy) / len2)
+            }
+            Self::Radial { ellipse, offset } => {
+                if ellipse.radius_x <= 0.0 || ellipse.radius_y <= 0.0 {
+                    return None;
+                }
+                // Work in the space where the ellipse is the unit circle and find how far
+                // along the ray from the focal point through `p` the circle is reached.
+                let mut fx = offset.x / ellipse.radius_x;
+                let mut fy = offset.y / ellipse.radius_y;
+                let f2 = fx * fx + fy * fy;
+                if f2 >= 1.0 {
+                    let scale = (1.0 - 1e-3) / f2.sqrt();
+                    fx *= scale;
+                    fy *= scale;
+                }
+                let qx = (p.x - ellipse.center.x) / ellipse.radius_x;
+                let qy = (p.y - ellipse.center.y) / ellipse.radius_y;
+                let dx = qx - fx;
+                let dy = qy - fy;
+                let dd = dx * dx + dy * dy;
+                if dd <= 0.0 {
+                    return Some(0.0);
+                }
+                let fd = fx * dx + fy * dy;
+                let ff = fx * fx + fy * fy;
+                let k = (-fd + (fd * fd - dd * (ff - 1.0)).sqrt()) / dd;
+                Some(1.0 / k)
+            }
+            Self::Conic {
+                center,
+                start_angle,
+                end_angle,
+            } => {
+                // Angles are in degrees, clockwise from the positive x axis as y points down.
+                let sweep = end_angle - start_angle;
+                if sweep == 0.0 || !sweep.is_finite() {
+                    return None;
+                }
+                let angle = (p.y - center.y).atan2(p.x - center.x).to_degrees();
+                let delta = if sweep > 0.0 {
+                    angle - start_angle
+                } else {
+                    start_angle - angle
+                };
+                Some(delta.rem_euclid(360.0) / sweep.abs().min(360.0))
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub mode: GradientMode,
+    pub stops: GradientStops,
+    pub options: GradientOptions,
+}
+
+impl Gradient {
+    #[inline]
+    pub fn linear<G>(
+        start: impl Into<Point<f32>>,
+        end: impl Into<Point<f32>>,
+        mode: GradientMode,
+        stops: &[G],
+    ) -> Self
+    where
+        G: Into<GradientStop> + Clone,
+    {
+        Self {
+            shape: GradientShape::Linear {
+                start: start.into(),
+                end: end.into(),
+            },
+            mode,
+            stops: GradientStops::new(stops),
+            options: GradientOptions::default(),
+        }
+    }
+
+    #[inline]
+    pub fn radial<G>(
+        ellipse: impl Into<Ellipse>,
+        offset: impl Into<Vector<f32>>,
+        mode: GradientMode,
+        stops: &[G],
+    ) -> Self
+    where
+        G: Into<GradientStop> + Clone,
+    {
+        Self {
+            shape: GradientShape::Radial {
+                ellipse: ellipse.into(),
+                offset: offset.into(),
+            },
+            mode,
+            stops: GradientStops::new(stops),
+            options: GradientOptions::default(),
+        }
+    }
+
+    #[inline]
+    pub fn conic<G>(
+        center: impl Into<Point<f32>>,
+        start_angle: f32,
+        end_angle: f32,
+        mode: GradientMode,
+        stops: &[G],
+    ) -> Self
+    where
+        G: Into<GradientStop> + Clone,
+    {
+        Self {
+            shape: GradientShape::Conic {
+                center: center.into(),
+                start_angle,
+                end_angle,
+            },
+            mode,
+            stops: GradientStops::new(stops),
+            options: GradientOptions::default(),
+        }
+    }
+
+    #[inline]
+    pub fn options(mut self, options: GradientOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    #[inline]
+    pub fn color_at(&self, point: impl Into<Point<f32>>) -> Rgba {
+        match self.shape.position(point) {
+            Some(t) => self.stops.interpolate(self.mode.apply(t), &self.options),
+            None => self
+                .stops
+                .as_slice()
+                .last()
+                .map_or(Rgba::TRANSPARENT, |stop| stop.color),
+        }
+    }
+
+    pub fn rasterize(&self, bounds: impl Into<Rect<f32>>, size: impl Into<Size<u32>>) -> Vec<Rgba> {
+        let bounds: Rect<f32> = bounds.into();
+        let size: Size<u32> = size.into();
+        let step_x = (bounds.right - bounds.left) / size.width as f32;
+        let step_y = (bounds.bottom - bounds.top) / size.height as f32;
+        let mut pixels = Vec::with_capacity(size.width as usize * size.height as usize);
+        for y in 0..size.height {
+            let py = bounds.top + (y as f32 + 0.5) * step_y;
+            for x in 0..size.width {
+                let px = bounds.left + (x as f32 + 0.5) * step_x;
+                pixels.push(self.color_at((px, py)));
+            }
+        }
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgba = Rgba::new(1.0, 0.0, 0.0, 1.0);
+    const BLUE: Rgba = Rgba::new(0.0, 0.0, 1.0, 1.0);
+
+    #[test]
+    fn sorts_stops_and_drops_non_finite_positions() {
+        let stops = GradientStops::new(&[(1.0, BLUE), (f32::NAN, RED), (0.0, RED)]);
+        let positions = stops
+            .as_slice()
+            .iter()
+            .map(|stop| stop.position)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [0.0, 1.0]);
+        assert_eq!(stops.as_slice()[0].color, RED);
+        assert_eq!(GradientStops::from(stops.as_slice().to_vec()), stops);
+    }
+
+    #[test]
+    fn clamps_outside_the_stops() {
+        let stops = GradientStops::new(&[(0.25, RED), (0.75, BLUE)]);
+        assert_eq!(stops.color_at(-1.0), RED);
+        assert_eq!(stops.color_at(0.25), RED);
+        assert_eq!(stops.color_at(0.75), BLUE);
+        assert_eq!(stops.color_at(2.0), BLUE);
+        assert_eq!(stops.color_at(0.5), Rgba::new(0.5, 0.0, 0.5, 1.0));
+        assert_eq!(stops.color_at(f32::NAN), RED);
+        assert_eq!(stops.color_at(f32::INFINITY), RED);
+        assert_eq!(GradientStops::default().color_at(0.5), Rgba::TRANSPARENT);
+    }
+
+    #[test]
+    fn applies_each_mode() {
+        assert_eq!(GradientMode::Clamp.apply(-0.5), 0.0);
+        assert_eq!(GradientMode::Clamp.apply(0.25), 0.25);
+        assert_eq!(GradientMode::Clamp.apply(1.5), 1.0);
+        assert_eq!(GradientMode::Wrap.apply(1.25), 0.25);
+        assert_eq!(GradientMode::Wrap.apply(-0.25), 0.75);
+        assert_eq!(GradientMode::Mirror.apply(1.25), 0.75);
+        assert_eq!(GradientMode::Mirror.apply(-0.25), 0.25);
+        assert_eq!(GradientMode::Mirror.apply(2.25), 0.25);
+        let stops = GradientStops::new(&[(0.0, RED), (1.0, BLUE)]);
+        assert_eq!(stops.sample(1.0, GradientMode::Wrap), RED);
+        assert_eq!(stops.sample(1.0, GradientMode::Mirror), BLUE);
+    }
+
+    fn conic(start_angle: f32, end_angle: f32) -> GradientShape {
+        GradientShape::Conic {
+            center: Point::new(0.0, 0.0),
+            start_angle,
+            end_angle,
+        }
+    }
+
+    fn assert_position(shape: &GradientShape, point: (f32, f32), expected: f32) {
+        let t = shape.position(point).unwrap();
+        assert!((t - expected).abs() < 1e-5, "{point:?}: {t} != {expected}");
+    }
+
+    #[test]
+    fn maps_conic_angles_to_positions() {
+        // Angles run clockwise as y points down.
+        let full = conic(0.0, 360.0);
+        assert_position(&full, (1.0, 0.0), 0.0);
+        assert_position(&full, (0.0, 1.0), 0.25);
+        assert_position(&full, (-1.0, 0.0), 0.5);
+        assert_position(&full, (0.0, -1.0), 0.75);
+        let quarter = conic(90.0, 180.0);
+        assert_position(&quarter, (0.0, 1.0), 0.0);
+        assert_position(&quarter, (-1.0, 1.0), 0.5);
+        assert_position(&quarter, (-1.0, 0.0), 1.0);
+        assert_position(&quarter, (1.0, 0.0), 3.0);
+        // A negative sweep runs counterclockwise.
+        let reversed = conic(0.0, -90.0);
+        assert_position(&reversed, (1.0, -1.0), 0.5);
+        assert_position(&reversed, (0.0, -1.0), 1.0);
+        assert_position(&conic(0.0, 720.0), (-1.0, 0.0), 0.5);
+        assert_position(&conic(-720.0, 0.0), (0.0, 1.0), 0.25);
+        assert_eq!(conic(45.0, 45.0).position((1.0, 0.0)), None);
+        assert_eq!(conic(0.0, f32::NAN).position((1.0, 0.0)), None);
+    }
+
+    #[test]
+    fn conic_gradients_repeat_past_the_sweep() {
+        let stops = [(0.0, RED), (1.0, BLUE)];
+        let color =
+            |mode, point| Gradient::conic((0.0, 0.0), 0.0, 90.0, mode, &stops).color_at(point);
+        // Straight left is two sweeps away, straight up three.
+        assert_eq!(color(GradientMode::Clamp, (-1.0, 0.0)), BLUE);
+        assert_eq!(color(GradientMode::Wrap, (-1.0, 0.0)), RED);
+        assert_eq!(color(GradientMode::Mirror, (-1.0, 0.0)), RED);
+        assert_eq!(color(GradientMode::Clamp, (0.0, -1.0)), BLUE);
+        assert_eq!(color(GradientMode::Wrap, (0.0, -1.0)), RED);
+        assert_eq!(color(GradientMode::Mirror, (0.0, -1.0)), BLUE);
+        let mid = Rgba::new(0.5, 0.0, 0.5, 1.0);
+        assert_eq!(color(GradientMode::Wrap, (-1.0, -1.0)), mid);
+        assert_eq!(color(GradientMode::Mirror, (-1.0, -1.0)), mid);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializing_sorts_stops() {
+        let stops: GradientStops = serde_json::from_str(
+            r#"[{"position": 1.0, "color": "blue"}, {"position": 0.0, "color": "red"}]"#,
+        )
+        .unwrap();
+        assert_eq!(stops, GradientStops::new(&[(0.0, RED), (1.0, BLUE)]));
+    }
+
+    #[cfg(any(windows, feature = "svg", feature = "pdf"))]
+    #[test]
+    fn subdivides_perceptual_stops_and_keeps_hard_breaks() {
+        let stops = GradientStops::new(&[(0.0, RED), (0.5, RED), (0.5, BLUE), (1.0, BLUE)]);
+        let options = GradientOptions::new().color_space(GradientColorSpace::Perceptual);
+        let expanded = stops.subdivide(&options);
+        assert_eq!(expanded.len(), 16 + 1 + 16 + 1);
+        assert!(expanded.windows(2).all(|w| w[0].position <= w[1].position));
+        let at_break = expanded
+            .iter()
+            .filter(|stop| stop.position == 0.5)
+            .map(|stop| stop.color)
+            .collect::<Vec<_>>();
+        assert_eq!(at_break.len(), 2);
+        assert_eq!(at_break[0], RED);
+        assert!((at_break[1].b - 1.0).abs() < 1e-4 && at_break[1].r.abs() < 1e-4);
+        let stops = GradientStops::new(&[(0.0, RED), (1.0, BLUE)]);
+        let expanded = stops.subdivide(&options);
+        let mid = expanded[8];
+        assert_eq!(mid.position, 0.5);
+        assert_eq!(mid.color, stops.interpolate(0.5, &options));
+        assert_ne!(mid.color, stops.color_at(0.5));
+    }
+}
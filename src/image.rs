@@ -1,60 +1,291 @@
 use crate::*;
+#[cfg(windows)]
 use std::path::Path;
-use windows::Win32::{Foundation::GENERIC_READ, Graphics::Direct2D::*, Graphics::Imaging::*};
-use windows::core::{GUID, HSTRING, Interface};
+#[cfg(windows)]
+use windows::Win32::{Graphics::Direct2D::*, Graphics::Imaging::*};
+#[cfg(windows)]
+use windows::core::{GUID, Interface};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-#[repr(i32)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Interpolation {
-    NearestNeighbor = D2D1_INTERPOLATION_MODE_NEAREST_NEIGHBOR.0,
-    Linear = D2D1_INTERPOLATION_MODE_LINEAR.0,
-    Cubic = D2D1_INTERPOLATION_MODE_CUBIC.0,
-    MultiSampleLinear = D2D1_INTERPOLATION_MODE_DEFINITION_MULTI_SAMPLE_LINEAR.0,
-    Anisotropic = D2D1_INTERPOLATION_MODE_ANISOTROPIC.0,
-    HighQualityCubic = D2D1_INTERPOLATION_MODE_HIGH_QUALITY_CUBIC.0,
+    NearestNeighbor,
+    Linear,
+    Cubic,
+    MultiSampleLinear,
+    Anisotropic,
+    HighQualityCubic,
 }
 
+// Byte layouts of four bytes per pixel, with straight or premultiplied alpha.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PixelFormat {
+    Rgba8,
+    // What Direct2D renders into, so reading it back needs no conversion.
+    Bgra8Premultiplied,
+    Rgba8Premultiplied,
+    Bgra8,
+}
+
+impl PixelFormat {
+    #[inline]
+    pub const fn channel_order(self) -> ChannelOrder {
+        match self {
+            Self::Rgba8 | Self::Rgba8Premultiplied => ChannelOrder::Rgba,
+            Self::Bgra8 | Self::Bgra8Premultiplied => ChannelOrder::Bgra,
+        }
+    }
+
+    #[inline]
+    pub const fn is_premultiplied(self) -> bool {
+        matches!(self, Self::Rgba8Premultiplied | Self::Bgra8Premultiplied)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageOptions {
+    // Turns the image upright according to its EXIF orientation.
+    pub auto_orient: bool,
+}
+
+impl ImageOptions {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn auto_orient(mut self, auto_orient: bool) -> Self {
+        self.auto_orient = auto_orient;
+        self
+    }
+}
+
+impl Default for ImageOptions {
+    #[inline]
+    fn default() -> Self {
+        Self { auto_orient: true }
+    }
+}
+
+// Checks that `rect` lies within `size` pixels and returns its size.
+pub(crate) fn region_size(rect: Rect<u32>, size: Size<u32>) -> Result<Size<u32>> {
+    if rect.left > rect.right
+        || rect.top > rect.bottom
+        || rect.right > size.width
+        || rect.bottom > size.height
+    {
+        return Err(Error::OutOfBounds);
+    }
+    Ok(Size::new(rect.right - rect.left, rect.bottom - rect.top))
+}
+
+#[cfg(windows)]
 impl From<Interpolation> for D2D1_INTERPOLATION_MODE {
     fn from(value: Interpolation) -> Self {
-        Self(value as i32)
+        match value {
+            Interpolation::NearestNeighbor => D2D1_INTERPOLATION_MODE_NEAREST_NEIGHBOR,
+            Interpolation::Linear => D2D1_INTERPOLATION_MODE_LINEAR,
+            Interpolation::Cubic => D2D1_INTERPOLATION_MODE_CUBIC,
+            Interpolation::MultiSampleLinear => D2D1_INTERPOLATION_MODE_MULTI_SAMPLE_LINEAR,
+            Interpolation::Anisotropic => D2D1_INTERPOLATION_MODE_ANISOTROPIC,
+            Interpolation::HighQualityCubic => D2D1_INTERPOLATION_MODE_HIGH_QUALITY_CUBIC,
+        }
+    }
+}
+
+// WIC reads straight from `data`, so the decoder must not outlive it.
+#[cfg(windows)]
+pub(crate) fn wic_decoder<T>(ctx: &Context<T>, data: &[u8]) -> Result<IWICBitmapDecoder>
+where
+    T: Backend,
+{
+    unsafe {
+        let stream = ctx.wic_imaging_factory.CreateStream()?;
+        stream.InitializeFromMemory(data)?;
+        Ok(ctx.wic_imaging_factory.CreateDecoderFromStream(
+            &stream,
+            &GUID::zeroed(),
+            WICDecodeMetadataCacheOnDemand,
+        )?)
     }
 }
 
+#[cfg(windows)]
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Image(ID2D1Bitmap1);
 
+#[cfg(windows)]
 impl Image {
-    pub fn from_file<T>(ctx: &Context<T>, path: impl AsRef<Path>) -> Result<Self>
+    #[inline]
+    pub(crate) fn new(bitmap: ID2D1Bitmap1) -> Self {
+        Self(bitmap)
+    }
+
+    fn converter<T>(ctx: &Context<T>, frame: &IWICBitmapFrameDecode) -> Result<IWICFormatConverter>
     where
         T: Backend,
     {
-        let factory = &ctx.wic_imaging_factory;
         unsafe {
-            let decoder = factory.CreateDecoderFromFilename(
-                &HSTRING::from(path.as_ref().to_string_lossy().as_ref()),
-                Some(&GUID::zeroed()),
-                GENERIC_READ,
-                WICDecodeMetadataCacheOnDemand,
-            )?;
-            let frame = decoder.GetFrame(0)?;
-            let converter = factory.CreateFormatConverter()?;
+            let converter = ctx.wic_imaging_factory.CreateFormatConverter()?;
             converter.Initialize(
-                &frame,
+                frame,
                 &GUID_WICPixelFormat32bppPBGRA,
                 WICBitmapDitherTypeNone,
                 None,
                 1.0,
                 WICBitmapPaletteTypeMedianCut,
             )?;
+            Ok(converter)
+        }
+    }
+
+    pub(crate) fn from_frame<T>(ctx: &Context<T>, frame: &IWICBitmapFrameDecode) -> Result<Self>
+    where
+        T: Backend,
+    {
+        unsafe {
             let bitmap = ctx
                 .d2d1_device_context
-                .CreateBitmapFromWicBitmap(&converter, None)?
+                .CreateBitmapFromWicBitmap(&Self::converter(ctx, frame)?, None)?
                 .cast()?;
             Ok(Self(bitmap))
         }
     }
 
+    // Orientation is applied to the decoded pixels, so a turned frame takes one more copy.
+    fn from_oriented_frame<T>(
+        ctx: &Context<T>,
+        frame: &IWICBitmapFrameDecode,
+        orientation: Orientation,
+    ) -> Result<Self>
+    where
+        T: Backend,
+    {
+        if orientation == Orientation::Normal {
+            return Self::from_frame(ctx, frame);
+        }
+        let converter = Self::converter(ctx, frame)?;
+        let (mut width, mut height) = (0, 0);
+        unsafe { converter.GetSize(&mut width, &mut height)? };
+        let mut data = vec![0; width as usize * height as usize * 4];
+        unsafe { converter.CopyPixels(std::ptr::null(), width * 4, &mut data)? };
+        let buffer = PixelBuffer::new((width, height), PixelFormat::Bgra8Premultiplied, data)?;
+        Self::from_pixel_buffer(ctx, &buffer.oriented(orientation))
+    }
+
+    #[inline]
+    pub fn from_file<T>(ctx: &Context<T>, path: impl AsRef<Path>) -> Result<Self>
+    where
+        T: Backend,
+    {
+        Self::from_file_with_options(ctx, path, ImageOptions::default())
+    }
+
+    #[inline]
+    pub fn from_file_with_options<T>(
+        ctx: &Context<T>,
+        path: impl AsRef<Path>,
+        options: ImageOptions,
+    ) -> Result<Self>
+    where
+        T: Backend,
+    {
+        Self::from_bytes_with_options(ctx, &std::fs::read(path)?, options)
+    }
+
+    #[inline]
+    pub fn from_bytes<T>(ctx: &Context<T>, data: &[u8]) -> Result<Self>
+    where
+        T: Backend,
+    {
+        Self::from_bytes_with_options(ctx, data, ImageOptions::default())
+    }
+
+    // QOI goes through `PixelBuffer::decode`; WIC sniffs and decodes everything else.
+    // The orientation comes from `ImageMetadata`, so formats it cannot read stay as stored.
+    pub fn from_bytes_with_options<T>(
+        ctx: &Context<T>,
+        data: &[u8],
+        options: ImageOptions,
+    ) -> Result<Self>
+    where
+        T: Backend,
+    {
+        let orientation = if options.auto_orient {
+            ImageMetadata::read(data).map_or(Orientation::Normal, |metadata| metadata.orientation)
+        } else {
+            Orientation::Normal
+        };
+        // WIC decodes everything but QOI, and applies the gamma and color profile of a PNG.
+        if ImageFormat::detect(data) == Some(ImageFormat::Qoi) {
+            let buffer = PixelBuffer::decode(data)?;
+            return Self::from_pixel_buffer(ctx, &buffer.oriented(orientation));
+        }
+        let decoder = wic_decoder(ctx, data)?;
+        let frame = unsafe { decoder.GetFrame(0)? };
+        Self::from_oriented_frame(ctx, &frame, orientation)
+    }
+
+    // `stride` is the distance in bytes between the starts of two rows.
+    #[inline]
+    pub fn from_pixels<T>(
+        ctx: &Context<T>,
+        size: impl Into<Size<u32>>,
+        stride: u32,
+        format: PixelFormat,
+        data: &[u8],
+    ) -> Result<Self>
+    where
+        T: Backend,
+    {
+        Self::from_pixel_buffer(ctx, &PixelBuffer::with_stride(size, stride, format, data)?)
+    }
+
+    pub fn from_pixel_buffer<T>(ctx: &Context<T>, buffer: &PixelBuffer) -> Result<Self>
+    where
+        T: Backend,
+    {
+        let buffer = buffer.to_format(PixelFormat::Bgra8Premultiplied);
+        let size = buffer.size();
+        let bitmap = unsafe {
+            ctx.d2d1_device_context.CreateBitmap(
+                size.into(),
+                Some(buffer.data().as_ptr() as _),
+                size.width * 4,
+                &bitmap_properties(D2D1_BITMAP_OPTIONS_NONE),
+            )?
+        };
+        Ok(Self(bitmap))
+    }
+
+    // Replaces the pixels in `rect`, for example with the next frame of a video.
+    pub fn update_region(
+        &self,
+        rect: impl Into<Rect<u32>>,
+        stride: u32,
+        format: PixelFormat,
+        data: &[u8],
+    ) -> Result<()> {
+        let rect: Rect<u32> = rect.into();
+        let size = region_size(rect, self.pixel_size())?;
+        let buffer = PixelBuffer::with_stride(size, stride, format, data)?
+            .to_format(PixelFormat::Bgra8Premultiplied);
+        if buffer.data().is_empty() {
+            return Ok(());
+        }
+        unsafe {
+            self.0.CopyFromMemory(
+                Some(&rect.into()),
+                buffer.data().as_ptr() as _,
+                size.width * 4,
+            )?;
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn size(&self) -> Size<f32> {
         unsafe { self.0.GetSize().into() }
@@ -65,6 +296,43 @@ impl Image {
         unsafe { self.0.GetPixelSize().into() }
     }
 
+    // Reads the pixels back through the device of `ctx`, which must have created the image.
+    pub fn pixel_buffer<T>(&self, ctx: &Context<T>) -> Result<PixelBuffer>
+    where
+        T: Backend,
+    {
+        let size = self.pixel_size();
+        let format = PixelFormat::Bgra8Premultiplied;
+        let pixels = read_bitmap(
+            &ctx.d2d1_device_context,
+            &self.0,
+            Rect::new(0, 0, size.width, size.height),
+            format,
+        )?;
+        PixelBuffer::new(size, format, pixels)
+    }
+
+    #[inline]
+    pub fn encode<T>(&self, ctx: &Context<T>, format: ImageFormat) -> Result<Vec<u8>>
+    where
+        T: Backend,
+    {
+        self.pixel_buffer(ctx)?.encode(format)
+    }
+
+    #[inline]
+    pub fn save<T>(
+        &self,
+        ctx: &Context<T>,
+        path: impl AsRef<Path>,
+        format: ImageFormat,
+    ) -> Result<()>
+    where
+        T: Backend,
+    {
+        self.pixel_buffer(ctx)?.save(path, format)
+    }
+
     pub(crate) fn handle(&self) -> &ID2D1Bitmap1 {
         &self.0
     }
@@ -1,30 +1,77 @@
+mod animation;
+#[cfg(windows)]
 mod brush;
+mod brush_desc;
+mod codec;
 mod color;
+#[cfg(windows)]
 mod com;
+#[cfg(windows)]
 mod context;
+mod display_list;
+#[cfg(windows)]
 mod draw_command;
 mod error;
+mod font_desc;
 mod geometry;
+mod gradient;
 mod image;
+mod metadata;
+#[cfg(windows)]
+mod offscreen;
 mod path;
+mod pattern;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+mod pixel_buffer;
 mod shape;
+mod sink;
+#[cfg(feature = "software")]
+pub mod software;
+mod style;
+#[cfg(feature = "svg")]
+pub mod svg;
+#[cfg(windows)]
 mod text;
 
+pub use animation::*;
+#[cfg(windows)]
 pub use brush::*;
+pub use brush_desc::*;
 pub use color::*;
+#[cfg(windows)]
 pub use com::*;
+#[cfg(windows)]
 pub use context::*;
+pub use display_list::*;
+#[cfg(windows)]
 pub use draw_command::*;
 pub use error::*;
+pub use font_desc::*;
 pub use geometry::*;
+pub use gradient::*;
 pub use image::*;
+pub use metadata::*;
+#[cfg(windows)]
+pub use offscreen::*;
 pub use path::*;
+pub use pattern::*;
+pub use pixel_buffer::*;
 pub use shape::*;
+pub use sink::*;
+pub use style::*;
+#[cfg(windows)]
 pub use text::*;
 
+#[cfg(windows)]
 pub use context::d2d1;
+#[cfg(windows)]
 pub use context::d2d1::Direct2D;
+#[cfg(windows)]
 pub use context::d3d11;
+#[cfg(windows)]
 pub use context::d3d11::Direct3D11;
+#[cfg(windows)]
 pub use context::d3d12;
+#[cfg(windows)]
 pub use context::d3d12::Direct3D12;
@@ -0,0 +1,154 @@
+use crate::codec::{bmp_metadata, gif_metadata, jpeg_metadata, png_metadata, qoi_metadata};
+use crate::*;
+use std::path::Path;
+
+// How the stored pixels turn to show the image upright, from the EXIF orientation tag.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    #[default]
+    Normal,
+    FlipHorizontal,
+    Rotate180,
+    FlipVertical,
+    // Mirrored across the diagonal from the top-left corner.
+    Transpose,
+    // Rotated 90 degrees clockwise.
+    Rotate90,
+    // Mirrored across the diagonal from the top-right corner.
+    Transverse,
+    Rotate270,
+}
+
+impl Orientation {
+    // Values outside 1 to 8 are treated as upright, as viewers do.
+    #[inline]
+    pub const fn from_exif(value: u16) -> Self {
+        match value {
+            2 => Self::FlipHorizontal,
+            3 => Self::Rotate180,
+            4 => Self::FlipVertical,
+            5 => Self::Transpose,
+            6 => Self::Rotate90,
+            7 => Self::Transverse,
+            8 => Self::Rotate270,
+            _ => Self::Normal,
+        }
+    }
+
+    // Whether width and height trade places.
+    #[inline]
+    pub const fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Self::Transpose | Self::Rotate90 | Self::Transverse | Self::Rotate270
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorModel {
+    Gray,
+    GrayAlpha,
+    Rgb,
+    Rgba,
+    Indexed,
+    Cmyk,
+}
+
+// The pixel layout in the file, before decoding converts it to RGBA.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourcePixelFormat {
+    pub model: ColorModel,
+    // Bits per channel, or per index for indexed color.
+    pub bit_depth: u8,
+}
+
+impl SourcePixelFormat {
+    #[inline]
+    pub const fn new(model: ColorModel, bit_depth: u8) -> Self {
+        Self { model, bit_depth }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageMetadata {
+    // The stored size, before orientation is applied.
+    pub size: Size<u32>,
+    pub pixel_format: SourcePixelFormat,
+    // Horizontal and vertical resolution, when the file records it in physical units.
+    pub dpi: Option<(f32, f32)>,
+    pub has_color_profile: bool,
+    pub orientation: Orientation,
+}
+
+impl ImageMetadata {
+    // Reads the headers of PNG, JPEG, BMP, GIF and QOI data without decoding any pixels.
+    pub fn read(data: &[u8]) -> Result<Self> {
+        match ImageFormat::detect(data) {
+            Some(ImageFormat::Png) => png_metadata(data),
+            Some(ImageFormat::Bmp) => bmp_metadata(data),
+            Some(ImageFormat::Qoi) => qoi_metadata(data),
+            None if data.starts_with(&[0xff, 0xd8]) => jpeg_metadata(data),
+            None if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") => {
+                gif_metadata(data)
+            }
+            None => Err(Error::UnsupportedImage),
+        }
+    }
+
+    #[inline]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read(&std::fs::read(path)?)
+    }
+
+    // The size once the image is turned upright.
+    #[inline]
+    pub fn oriented_size(&self) -> Size<u32> {
+        if self.orientation.swaps_axes() {
+            Size::new(self.size.height, self.size.width)
+        } else {
+            self.size
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_encoded_formats() {
+        let buffer = PixelBuffer::from_rgba8((3, 2), &[Rgba8::new(1, 2, 3, 4); 6]).unwrap();
+        let bmp = ImageMetadata::read(&buffer.encode(ImageFormat::Bmp).unwrap()).unwrap();
+        assert_eq!(bmp.size, Size::new(3, 2));
+        assert_eq!(
+            bmp.pixel_format,
+            SourcePixelFormat::new(ColorModel::Rgba, 8)
+        );
+        let (x, _) = bmp.dpi.unwrap();
+        assert!((x - 96.0).abs() < 0.1);
+        let qoi = ImageMetadata::read(&buffer.encode(ImageFormat::Qoi).unwrap()).unwrap();
+        assert_eq!(
+            qoi.pixel_format,
+            SourcePixelFormat::new(ColorModel::Rgba, 8)
+        );
+        assert_eq!(qoi.dpi, None);
+        let png = ImageMetadata::read(&buffer.encode(ImageFormat::Png).unwrap()).unwrap();
+        assert_eq!(png.orientation, Orientation::Normal);
+        assert!(!png.has_color_profile);
+        let gif = ImageMetadata::read(b"GIF89a\x05\x00\x07\x00\x81\x00\x00").unwrap();
+        assert_eq!(gif.size, Size::new(5, 7));
+        assert_eq!(
+            gif.pixel_format,
+            SourcePixelFormat::new(ColorModel::Indexed, 2)
+        );
+        assert!(matches!(
+            ImageMetadata::read(b"II*\0"),
+            Err(Error::UnsupportedImage)
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::*;
+use windows::Win32::Graphics::{
+    Direct2D::Common::*, Direct2D::*, Dxgi::Common::DXGI_FORMAT_B8G8R8A8_UNORM,
+};
+
+pub(crate) fn bitmap_properties(options: D2D1_BITMAP_OPTIONS) -> D2D1_BITMAP_PROPERTIES1 {
+    D2D1_BITMAP_PROPERTIES1 {
+        pixelFormat: D2D1_PIXEL_FORMAT {
+            format: DXGI_FORMAT_B8G8R8A8_UNORM,
+            alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+        },
+        bitmapOptions: options,
+        dpiX: 96.0,
+        dpiY: 96.0,
+        ..Default::default()
+    }
+}
+
+// Copies `rect` of `bitmap` through a CPU-readable bitmap. Rows are tightly packed.
+pub(crate) fn read_bitmap(
+    device_context: &ID2D1DeviceContext5,
+    bitmap: &ID2D1Bitmap1,
+    rect: Rect<u32>,
+    format: PixelFormat,
+) -> Result<Vec<u8>> {
+    let size = region_size(rect, unsafe { bitmap.GetPixelSize().into() })?;
+    if size.width == 0 || size.height == 0 {
+        return Ok(Vec::new());
+    }
+    let row = size.width as usize * 4;
+    let mut pixels = Vec::with_capacity(row * size.height as usize);
+    unsafe {
+        let readback = device_context.CreateBitmap(
+            size.into(),
+            None,
+            0,
+            &bitmap_properties(D2D1_BITMAP_OPTIONS_CPU_READ | D2D1_BITMAP_OPTIONS_CANNOT_DRAW),
+        )?;
+        readback.CopyFromBitmap(Some(&D2D_POINT_2U::default()), bitmap, Some(&rect.into()))?;
+        let mapped = readback.Map(D2D1_MAP_OPTIONS_READ)?;
+        for y in 0..size.height as usize {
+            pixels.extend_from_slice(std::slice::from_raw_parts(
+                mapped.bits.add(y * mapped.pitch as usize),
+                row,
+            ));
+        }
+        readback.Unmap()?;
+    }
+    convert_pixels(&mut pixels, PixelFormat::Bgra8Premultiplied, format);
+    Ok(pixels)
+}
+
+// A bitmap to draw into with `Context::draw_offscreen`, whatever the backend.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OffscreenTarget {
+    bitmap: ID2D1Bitmap1,
+    device_context: ID2D1DeviceContext5,
+}
+
+impl OffscreenTarget {
+    #[inline]
+    pub fn read_pixels(&self, format: PixelFormat) -> Result<Vec<u8>> {
+        let Size { width, height } = self.pixel_size();
+        self.read_pixels_in(Rect::new(0, 0, width, height), format)
+    }
+
+    // Rows are tightly packed, four bytes per pixel.
+    #[inline]
+    pub fn read_pixels_in(
+        &self,
+        rect: impl Into<Rect<u32>>,
+        format: PixelFormat,
+    ) -> Result<Vec<u8>> {
+        read_bitmap(&self.device_context, &self.bitmap, rect.into(), format)
+    }
+
+    // Keeps the premultiplied pixels as they are, so nothing is lost before encoding.
+    #[inline]
+    pub fn pixel_buffer(&self) -> Result<PixelBuffer> {
+        let format = PixelFormat::Bgra8Premultiplied;
+        PixelBuffer::new(self.pixel_size(), format, self.read_pixels(format)?)
+    }
+
+    // Shares the pixels, so later draws into the target show in the image too. The image
+    // cannot be drawn into its own target.
+    #[inline]
+    pub fn image(&self) -> Image {
+        Image::new(self.bitmap.clone())
+    }
+}
+
+impl Target for OffscreenTarget {
+    #[inline]
+    fn bitmap(&self) -> &ID2D1Bitmap1 {
+        &self.bitmap
+    }
+
+    #[inline]
+    fn size(&self) -> Size<f32> {
+        unsafe { self.bitmap.GetSize().into() }
+    }
+
+    #[inline]
+    fn pixel_size(&self) -> Size<u32> {
+        unsafe { self.bitmap.GetPixelSize().into() }
+    }
+}
+
+impl<T> Context<T>
+where
+    T: Backend,
+{
+    // The target starts out transparent.
+    pub fn create_offscreen_target(&self, size: impl Into<Size<u32>>) -> Result<OffscreenTarget> {
+        let size: Size<u32> = size.into();
+        let bitmap = unsafe {
+            self.d2d1_device_context.CreateBitmap(
+                size.into(),
+                None,
+                0,
+                &bitmap_properties(D2D1_BITMAP_OPTIONS_TARGET),
+            )?
+        };
+        Ok(OffscreenTarget {
+            bitmap,
+            device_context: self.d2d1_device_context.clone(),
+        })
+    }
+}
@@ -1,7 +1,8 @@
 use crate::*;
+#[cfg(windows)]
 use windows::Win32::Graphics::{Direct2D::Common::*, Direct2D::*};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(C)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct QuadraticBezierSegment {
@@ -19,6 +20,7 @@ impl QuadraticBezierSegment {
     }
 }
 
+#[cfg(windows)]
 impl From<QuadraticBezierSegment> for D2D1_QUADRATIC_BEZIER_SEGMENT {
     #[inline]
     fn from(value: QuadraticBezierSegment) -> Self {
@@ -29,7 +31,7 @@ impl From<QuadraticBezierSegment> for D2D1_QUADRATIC_BEZIER_SEGMENT {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(C)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CubicBezierSegment {
@@ -53,6 +55,7 @@ impl CubicBezierSegment {
     }
 }
 
+#[cfg(windows)]
 impl From<CubicBezierSegment> for D2D1_BEZIER_SEGMENT {
     #[inline]
     fn from(value: CubicBezierSegment) -> Self {
@@ -65,146 +68,326 @@ impl From<CubicBezierSegment> for D2D1_BEZIER_SEGMENT {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-#[repr(i32)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PathEnd {
-    Open = D2D1_FIGURE_END_OPEN.0,
-    Closed = D2D1_FIGURE_END_CLOSED.0,
+    Open,
+    Closed,
 }
 
+#[cfg(windows)]
 impl From<PathEnd> for D2D1_FIGURE_END {
     #[inline]
     fn from(value: PathEnd) -> Self {
-        Self(value as i32)
+        match value {
+            PathEnd::Open => D2D1_FIGURE_END_OPEN,
+            PathEnd::Closed => D2D1_FIGURE_END_CLOSED,
+        }
     }
 }
 
-pub struct PathBuilder {
-    geometry: ID2D1PathGeometry,
-    sink: ID2D1GeometrySink,
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PathSegment {
+    Line(Point<f32>),
+    QuadraticBezier(QuadraticBezierSegment),
+    CubicBezier(CubicBezierSegment),
 }
 
-impl PathBuilder {
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathFigure {
+    pub start: Point<f32>,
+    pub segments: Vec<PathSegment>,
+    pub end: PathEnd,
+}
+
+impl PathFigure {
+    #[inline]
+    pub fn new(start: impl Into<Point<f32>>) -> Self {
+        Self {
+            start: start.into(),
+            segments: vec![],
+            end: PathEnd::Open,
+        }
+    }
+
     #[inline]
-    pub fn line_to(self, point: impl Into<Point<f32>>) -> Self {
-        let point: Point<f32> = point.into();
-        unsafe { self.sink.AddLine(point.into()) }
+    pub fn line_to(mut self, point: impl Into<Point<f32>>) -> Self {
+        self.segments.push(PathSegment::Line(point.into()));
         self
     }
 
     #[inline]
-    pub fn add_lines(self, points: &[Point<f32>]) -> Self {
-        unsafe {
-            let lines =
-                std::slice::from_raw_parts(points.as_ptr() as *const D2D_POINT_2F, points.len());
-            self.sink.AddLines(lines);
-        }
+    pub fn add_lines(mut self, points: &[Point<f32>]) -> Self {
+        self.segments
+            .extend(points.iter().map(|&point| PathSegment::Line(point)));
         self
     }
 
     #[inline]
     pub fn quadratic_bezier_to(
-        self,
+        mut self,
         ctrl: impl Into<Point<f32>>,
         to: impl Into<Point<f32>>,
     ) -> Self {
         let segment = QuadraticBezierSegment::new(ctrl, to);
-        unsafe {
-            self.sink.AddQuadraticBezier(&segment.into());
-        }
+        self.segments.push(PathSegment::QuadraticBezier(segment));
         self
     }
 
     #[inline]
-    pub fn add_quadratic_beziers(self, segments: &[QuadraticBezierSegment]) -> Self {
-        unsafe {
-            let segments = std::slice::from_raw_parts(
-                segments.as_ptr() as *const D2D1_QUADRATIC_BEZIER_SEGMENT,
-                segments.len(),
-            );
-            self.sink.AddQuadraticBeziers(segments);
-        }
+    pub fn add_quadratic_beziers(mut self, segments: &[QuadraticBezierSegment]) -> Self {
+        self.segments.extend(
+            segments
+                .iter()
+                .map(|&segment| PathSegment::QuadraticBezier(segment)),
+        );
         self
     }
 
     #[inline]
     pub fn cubic_bezier_to(
-        self,
+        mut self,
         c0: impl Into<Point<f32>>,
         c1: impl Into<Point<f32>>,
         to: impl Into<Point<f32>>,
     ) -> Self {
         let segment = CubicBezierSegment::new(c0, c1, to);
-        unsafe {
-            self.sink.AddBezier(&segment.into());
-        }
+        self.segments.push(PathSegment::CubicBezier(segment));
         self
     }
 
     #[inline]
-    pub fn add_cubic_beziers(self, segments: &[CubicBezierSegment]) -> Self {
-        unsafe {
-            let segments = std::slice::from_raw_parts(
-                segments.as_ptr() as *const D2D1_BEZIER_SEGMENT,
-                segments.len(),
-            );
-            self.sink.AddBeziers(segments);
-        }
+    pub fn add_cubic_beziers(mut self, segments: &[CubicBezierSegment]) -> Self {
+        self.segments.extend(
+            segments
+                .iter()
+                .map(|&segment| PathSegment::CubicBezier(segment)),
+        );
         self
     }
 
     #[inline]
-    pub fn build(self, end: PathEnd) -> Result<Path> {
-        unsafe {
-            self.sink.EndFigure(end.into());
-            self.sink.Close()?;
+    pub fn close(mut self) -> Self {
+        self.end = PathEnd::Closed;
+        self
+    }
+}
+
+impl Fill for PathFigure {
+    #[inline]
+    fn fill(&self, sink: &mut dyn GeometrySink) {
+        sink.path(self);
+    }
+}
+
+impl Stroke for PathFigure {
+    #[inline]
+    fn stroke(&self, sink: &mut dyn GeometrySink) {
+        sink.path(self);
+    }
+}
+
+#[cfg(windows)]
+pub(crate) fn create_path_geometry(
+    factory: &ID2D1Factory6,
+    figure: &PathFigure,
+) -> Result<ID2D1PathGeometry> {
+    unsafe {
+        let geometry = factory.CreatePathGeometry()?;
+        let sink = geometry.Open()?;
+        sink.SetFillMode(D2D1_FILL_MODE_WINDING);
+        sink.BeginFigure(figure.start.into(), D2D1_FIGURE_BEGIN_FILLED);
+        for segment in &figure.segments {
+            match *segment {
+                PathSegment::Line(to) => sink.AddLine(to.into()),
+                PathSegment::QuadraticBezier(s) => sink.AddQuadraticBezier(&s.into()),
+                PathSegment::CubicBezier(s) => sink.AddBezier(&s.into()),
+            }
         }
-        Ok(Path(self.geometry))
+        sink.EndFigure(figure.end.into());
+        sink.Close()?;
+        Ok(geometry.into())
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
-pub struct Path(ID2D1PathGeometry);
+#[cfg(windows)]
+pub struct PathBuilder {
+    factory: ID2D1Factory6,
+    figure: PathFigure,
+}
+
+#[cfg(windows)]
+impl PathBuilder {
+    #[inline]
+    pub fn line_to(mut self, point: impl Into<Point<f32>>) -> Self {
+        self.figure = self.figure.line_to(point);
+        self
+    }
+
+    #[inline]
+    pub fn add_lines(mut self, points: &[Point<f32>]) -> Self {
+        self.figure = self.figure.add_lines(points);
+        self
+    }
+
+    #[inline]
+    pub fn quadratic_bezier_to(
+        mut self,
+        ctrl: impl Into<Point<f32>>,
+        to: impl Into<Point<f32>>,
+    ) -> Self {
+        self.figure = self.figure.quadratic_bezier_to(ctrl, to);
+        self
+    }
 
+    #[inline]
+    pub fn add_quadratic_beziers(mut self, segments: &[QuadraticBezierSegment]) -> Self {
+        self.figure = self.figure.add_quadratic_beziers(segments);
+        self
+    }
+
+    #[inline]
+    pub fn cubic_bezier_to(
+        mut self,
+        c0: impl Into<Point<f32>>,
+        c1: impl Into<Point<f32>>,
+        to: impl Into<Point<f32>>,
+    ) -> Self {
+        self.figure = self.figure.cubic_bezier_to(c0, c1, to);
+        self
+    }
+
+    #[inline]
+    pub fn add_cubic_beziers(mut self, segments: &[CubicBezierSegment]) -> Self {
+        self.figure = self.figure.add_cubic_beziers(segments);
+        self
+    }
+
+    #[inline]
+    pub fn build(mut self, end: PathEnd) -> Result<Path> {
+        self.figure.end = end;
+        Path::new(&self.factory, self.figure)
+    }
+}
+
+#[cfg(windows)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Path {
+    geometry: ID2D1PathGeometry,
+    figure: PathFigure,
+}
+
+#[cfg(windows)]
 impl Path {
     #[inline]
     pub fn builder<T>(ctx: &Context<T>, start: impl Into<Point<f32>>) -> Result<PathBuilder>
     where
         T: Backend,
     {
-        let geometry = unsafe { ctx.backend.d2d1_factory().CreatePathGeometry()? };
-        let start: Point<f32> = start.into();
-        let sink = unsafe { geometry.Open()? };
-        unsafe {
-            sink.BeginFigure(start.into(), D2D1_FIGURE_BEGIN_FILLED);
-            sink.SetFillMode(D2D1_FILL_MODE_WINDING);
-        }
         Ok(PathBuilder {
-            geometry: geometry.into(),
-            sink,
+            factory: ctx.backend.d2d1_factory().clone(),
+            figure: PathFigure::new(start),
+        })
+    }
+
+    #[inline]
+    pub fn from_figure<T>(ctx: &Context<T>, figure: &PathFigure) -> Result<Self>
+    where
+        T: Backend,
+    {
+        Self::new(ctx.backend.d2d1_factory(), figure.clone())
+    }
+
+    fn new(factory: &ID2D1Factory6, figure: PathFigure) -> Result<Self> {
+        Ok(Self {
+            geometry: create_path_geometry(factory, &figure)?,
+            figure,
         })
     }
+
+    #[inline]
+    pub fn figure(&self) -> &PathFigure {
+        &self.figure
+    }
+
+    pub(crate) fn handle(&self) -> &ID2D1PathGeometry {
+        &self.geometry
+    }
 }
 
+// The geometry is offered first, so that Direct2D draws it without building it again.
+#[cfg(windows)]
 impl Fill for Path {
     #[inline]
-    fn fill(&self, dc: &ID2D1DeviceContext5, brush: &ID2D1Brush) {
-        unsafe {
-            dc.FillGeometry(&self.0, brush, None);
+    fn fill(&self, sink: &mut dyn GeometrySink) {
+        if !sink.native_geometry(&self.geometry) {
+            sink.path(self.figure());
         }
     }
 }
 
+#[cfg(windows)]
 impl Stroke for Path {
     #[inline]
-    fn stroke(
-        &self,
-        dc: &ID2D1DeviceContext5,
-        brush: &ID2D1Brush,
-        width: f32,
-        style: Option<&ID2D1StrokeStyle1>,
-    ) {
-        unsafe {
-            dc.DrawGeometry(&self.0, brush, width, style.map(|s| s.into()));
+    fn stroke(&self, sink: &mut dyn GeometrySink) {
+        if !sink.native_geometry(&self.geometry) {
+            sink.path(self.figure());
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Clip for Path {
+    #[inline]
+    fn clip_geometry(&self, _factory: &ID2D1Factory6) -> Result<ID2D1Geometry> {
+        Ok(self.geometry.clone().into())
+    }
+}
+
+#[cfg(all(test, windows))]
+mod tests {
+    use super::*;
+    use std::any::Any;
+
+    // Takes native geometries like `Direct2DSink`, whose `path` is the only place drawing
+    // builds one.
+    #[derive(Default)]
+    struct NativeSink {
+        geometries: usize,
+        paths: usize,
+    }
+
+    impl GeometrySink for NativeSink {
+        fn rect(&mut self, _rect: &Rect<f32>) {}
+        fn rounded_rect(&mut self, _rounded_rect: &RoundedRect) {}
+        fn ellipse(&mut self, _ellipse: &Ellipse) {}
+
+        fn path(&mut self, _figure: &PathFigure) {
+            self.paths += 1;
+        }
+
+        fn native_geometry(&mut self, geometry: &dyn Any) -> bool {
+            self.geometries += 1;
+            geometry.is::<ID2D1PathGeometry>()
         }
     }
+
+    #[test]
+    fn draws_paths_with_their_built_geometry() {
+        let factory: ID2D1Factory6 =
+            unsafe { D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, None).unwrap() };
+        let figure = PathFigure::new((0.0, 0.0))
+            .line_to((4.0, 0.0))
+            .line_to((2.0, 3.0))
+            .close();
+        let path = Path::new(&factory, figure.clone()).unwrap();
+        let mut sink = NativeSink::default();
+        path.fill(&mut sink);
+        path.stroke(&mut sink);
+        assert_eq!((sink.geometries, sink.paths), (2, 0));
+        let mut sink = RecordingSink::new();
+        path.fill(&mut sink);
+        assert_eq!(sink.primitives(), [Primitive::Path(figure)]);
+    }
 }
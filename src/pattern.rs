@@ -0,0 +1,222 @@
+use crate::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HatchStyle {
+    Diagonal,
+    Crosshatch,
+    Dots,
+    Grid,
+}
+
+pub(crate) const MAX_TILE_SIZE: u32 = 4096;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+    pub style: HatchStyle,
+    pub spacing: f32,
+    pub line_width: f32,
+    pub angle: f32,
+    pub foreground: Rgba,
+    pub background: Rgba,
+}
+
+impl Pattern {
+    #[inline]
+    pub fn new(style: HatchStyle) -> Self {
+        Self {
+            style,
+            spacing: 8.0,
+            line_width: 1.0,
+            angle: 0.0,
+            foreground: Rgba::BLACK,
+            background: Rgba::TRANSPARENT,
+        }
+    }
+
+    #[inline]
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    #[inline]
+    pub fn line_width(mut self, line_width: f32) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    #[inline]
+    pub fn angle(mut self, angle: f32) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    #[inline]
+    pub fn foreground(mut self, color: impl Into<Rgba>) -> Self {
+        self.foreground = color.into();
+        self
+    }
+
+    #[inline]
+    pub fn background(mut self, color: impl Into<Rgba>) -> Self {
+        self.background = color.into();
+        self
+    }
+
+    // Spacing below one unit, including zero, negative and NaN, is drawn as one unit so that
+    // the tile keeps its pixels and an invertible transform.
+    #[inline]
+    fn tile_spacing(&self) -> f32 {
+        if self.spacing >= 1.0 {
+            self.spacing
+        } else {
+            1.0
+        }
+    }
+
+    // A tile is at most `MAX_TILE_SIZE` pixels wide; wider spacings get coarser pixels.
+    #[inline]
+    pub fn tile_size(&self, scale: f32) -> Size<u32> {
+        let n = (self.tile_spacing() * scale)
+            .round()
+            .max(1.0)
+            .min(MAX_TILE_SIZE as f32) as u32;
+        Size::new(n, n)
+    }
+
+    // The tile is one `spacing` square drawn upright; diagonal styles are the upright
+    // ones turned by 45 degrees, on top of `angle`.
+    #[inline]
+    pub fn tile_transform(&self, scale: f32) -> Matrix3x2 {
+        let n = self.tile_size(scale).width as f32;
+        let angle = match self.style {
+            HatchStyle::Diagonal | HatchStyle::Crosshatch => self.angle + 45.0,
+            HatchStyle::Dots | HatchStyle::Grid => self.angle,
+        };
+        let spacing = self.tile_spacing();
+        Matrix3x2::scale(spacing / n, spacing / n).then(Matrix3x2::rotation(angle))
+    }
+
+    pub fn render_tile(&self, scale: f32) -> Vec<Rgba> {
+        let n = self.tile_size(scale).width;
+        let spacing = self.tile_spacing();
+        let pixel = spacing / n as f32;
+        let half = spacing / 2.0;
+        let coverage = |d: f32| ((self.line_width / 2.0 - d) / pixel + 0.5).clamp(0.0, 1.0);
+        let background = self.background.premultiply();
+        let mut pixels = Vec::with_capacity(n as usize * n as usize);
+        for y in 0..n {
+            let dy = ((y as f32 + 0.5) * pixel - half).abs();
+            for x in 0..n {
+                let dx = ((x as f32 + 0.5) * pixel - half).abs();
+                let c = match self.style {
+                    HatchStyle::Diagonal => coverage(dy),
+                    HatchStyle::Crosshatch | HatchStyle::Grid => coverage(dx).max(coverage(dy)),
+                    HatchStyle::Dots => coverage(dx.hypot(dy)),
+                };
+                let foreground = self
+                    .foreground
+                    .with_alpha(self.foreground.a * c)
+                    .premultiply();
+                pixels.push(
+                    foreground
+                        .composite(background, CompositeMode::SourceOver)
+                        .unpremultiply(),
+                );
+            }
+        }
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Marks the pixels that are mostly foreground.
+    fn mask(pattern: &Pattern) -> Vec<&'static str> {
+        let n = pattern.tile_size(1.0).width as usize;
+        pattern
+            .render_tile(1.0)
+            .chunks(n)
+            .map(
+                |row| match row.iter().map(|c| c.a > 0.5).collect::<Vec<_>>()[..] {
+                    [false, false, true, false, false] => "..#..",
+                    [true, true, true, true, true] => "#####",
+                    [false, false, false, false, false] => ".....",
+                    _ => "?",
+                },
+            )
+            .collect()
+    }
+
+    #[test]
+    fn renders_each_style_centered_in_the_tile() {
+        let pattern = |style| Pattern::new(style).spacing(5.0);
+        let cross = ["..#..", "..#..", "#####", "..#..", "..#.."];
+        assert_eq!(mask(&pattern(HatchStyle::Grid)), cross);
+        // Crosshatch and diagonal tiles are upright and turned by the transform.
+        assert_eq!(mask(&pattern(HatchStyle::Crosshatch)), cross);
+        assert_eq!(
+            mask(&pattern(HatchStyle::Diagonal)),
+            [".....", ".....", "#####", ".....", "....."]
+        );
+        assert_eq!(
+            mask(&pattern(HatchStyle::Dots).line_width(1.5)),
+            [".....", ".....", "..#..", ".....", "....."]
+        );
+    }
+
+    #[test]
+    fn composites_over_the_background() {
+        let pattern = Pattern::new(HatchStyle::Diagonal)
+            .spacing(4.0)
+            .line_width(2.0)
+            .foreground(Rgba::new(1.0, 0.0, 0.0, 0.5))
+            .background(Rgba::new(0.0, 0.0, 1.0, 1.0));
+        let tile = pattern.render_tile(1.0);
+        assert_eq!(tile.len(), 16);
+        assert_eq!(tile[0], Rgba::new(0.0, 0.0, 1.0, 1.0));
+        assert_eq!(tile[4], Rgba::new(0.5, 0.0, 0.5, 1.0));
+        assert_eq!(tile[8], tile[4]);
+        assert_eq!(tile[12], tile[0]);
+    }
+
+    #[test]
+    fn scales_the_tile_to_device_pixels() {
+        let pattern = Pattern::new(HatchStyle::Diagonal).spacing(4.0).angle(15.0);
+        assert_eq!(pattern.tile_size(2.5), Size::new(10, 10));
+        let transform = pattern.tile_transform(2.5);
+        let corner = transform.transform_point((10.0, 0.0));
+        assert!((corner.x.hypot(corner.y) - 4.0).abs() < 1e-5);
+        assert!((corner.y.atan2(corner.x).to_degrees() - 60.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn clamps_degenerate_spacing() {
+        for spacing in [0.0, -4.0, f32::NAN, 0.25] {
+            let pattern = Pattern::new(HatchStyle::Grid).spacing(spacing);
+            assert_eq!(pattern.tile_size(2.0), Size::new(2, 2), "{spacing}");
+            assert!(pattern.tile_transform(2.0).invert().is_some(), "{spacing}");
+            assert!(
+                pattern
+                    .render_tile(2.0)
+                    .iter()
+                    .all(|c| [c.r, c.g, c.b, c.a].iter().all(|v| v.is_finite())),
+                "{spacing}"
+            );
+        }
+    }
+
+    #[test]
+    fn limits_the_tile_size() {
+        let pattern = Pattern::new(HatchStyle::Grid).spacing(1e6);
+        let n = MAX_TILE_SIZE;
+        assert_eq!(pattern.tile_size(1.0), Size::new(n, n));
+        assert_eq!(pattern.tile_size(f32::INFINITY), Size::new(n, n));
+        let corner = pattern.tile_transform(1.0).transform_point((n as f32, 0.0));
+        assert!((corner.x.hypot(corner.y) - 1e6).abs() < 1.0);
+    }
+}
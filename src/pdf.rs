@@ -0,0 +1,1563 @@
+mod font;
+
+use crate::codec::compress;
+use crate::*;
+use font::FontFile;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+
+// PDF numbers have no exponent; four decimals are well below what any device resolves.
+struct Num(f32);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.0.is_finite() || self.0.abs() < 0.00005 {
+            return f.write_str("0");
+        }
+        let s = format!("{:.4}", self.0);
+        f.write_str(s.trim_end_matches('0').trim_end_matches('.'))
+    }
+}
+
+fn nums(values: impl IntoIterator<Item = f32>) -> String {
+    values
+        .into_iter()
+        .map(|v| Num(v).to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn matrix(m: Matrix3x2) -> String {
+    nums([m.m11, m.m12, m.m21, m.m22, m.m31, m.m32])
+}
+
+// Pages are described with y pointing down, as everywhere else in pnte.
+fn flip(height: f32) -> Matrix3x2 {
+    Matrix3x2::new(1.0, 0.0, 0.0, -1.0, 0.0, height)
+}
+
+fn normalize(rect: Rect<f32>) -> Rect<f32> {
+    Rect::new(
+        rect.left.min(rect.right),
+        rect.top.min(rect.bottom),
+        rect.left.max(rect.right),
+        rect.top.max(rect.bottom),
+    )
+}
+
+fn rgb(color: Rgba) -> [f32; 3] {
+    [color.r, color.g, color.b].map(|c| c.clamp(0.0, 1.0))
+}
+
+fn line_cap(cap: CapStyle) -> u8 {
+    match cap {
+        CapStyle::Flat => 0,
+        // PDF has no triangle caps; round ones reach as far past the end.
+        CapStyle::Round | CapStyle::Triangle => 1,
+        CapStyle::Square => 2,
+    }
+}
+
+fn stroke_operators(width: f32, style: Option<&StrokeStyleProperties>) -> String {
+    let mut out = format!("{} w\n", Num(width));
+    let Some(style) = style else {
+        // Direct2D's miter limit when no stroke style is given.
+        out.push_str("10 M\n");
+        return out;
+    };
+    match style.line_join {
+        // `StrokeStyle` gives plain miters a limit of 1, which always bevels in PDF.
+        LineJoin::Miter | LineJoin::Bevel => out.push_str("2 j\n"),
+        LineJoin::Round => out.push_str("1 j\n"),
+        LineJoin::MiterOrBevel(limit) => {
+            let _ = writeln!(out, "0 j {} M", Num(limit.max(1.0)));
+        }
+    }
+    let pattern = style
+        .dash
+        .as_ref()
+        .map_or(&[][..], |dash| dash.style.pattern());
+    let period: f32 = pattern.iter().map(|d| d.max(0.0)).sum();
+    match &style.dash {
+        Some(dash) if period > 0.0 && period.is_finite() => {
+            let dashes = nums(pattern.iter().map(|d| d.max(0.0) * width));
+            let _ = writeln!(
+                out,
+                "{} J [{dashes}] {} d",
+                line_cap(dash.cap),
+                Num(dash.offset * width)
+            );
+        }
+        _ => {
+            let _ = writeln!(out, "{} J", line_cap(style.start_cap));
+        }
+    }
+    out
+}
+
+// Helvetica is one of the fonts every PDF reader has, encoded as WinAnsi.
+fn win_ansi(c: char) -> u8 {
+    match c {
+        ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+        '€' => 0x80,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        _ => b'?',
+    }
+}
+
+// Writes every primitive as path construction operators, to be painted together.
+struct Operators<'a> {
+    out: &'a mut String,
+}
+
+impl GeometrySink for Operators<'_> {
+    fn rect(&mut self, rect: &Rect<f32>) {
+        let _ = writeln!(
+            self.out,
+            "{} re",
+            nums([
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top
+            ])
+        );
+    }
+
+    fn rounded_rect(&mut self, rounded_rect: &RoundedRect) {
+        let mut figures = FigureSink::new();
+        figures.rounded_rect(rounded_rect);
+        figures
+            .figures()
+            .iter()
+            .for_each(|figure| self.path(figure));
+    }
+
+    fn ellipse(&mut self, ellipse: &Ellipse) {
+        let mut figures = FigureSink::new();
+        figures.ellipse(ellipse);
+        figures
+            .figures()
+            .iter()
+            .for_each(|figure| self.path(figure));
+    }
+
+    fn path(&mut self, figure: &PathFigure) {
+        let mut current = figure.start;
+        let _ = writeln!(self.out, "{} m", nums([current.x, current.y]));
+        for segment in &figure.segments {
+            let _ = match segment {
+                PathSegment::Line(p) => {
+                    current = *p;
+                    writeln!(self.out, "{} l", nums([p.x, p.y]))
+                }
+                // PDF only has cubic curves, which hold quadratic ones exactly.
+                PathSegment::QuadraticBezier(s) => {
+                    let toward = |p: Point<f32>| {
+                        Point::new(
+                            p.x + (s.ctrl.x - p.x) * 2.0 / 3.0,
+                            p.y + (s.ctrl.y - p.y) * 2.0 / 3.0,
+                        )
+                    };
+                    let (c0, c1) = (toward(current), toward(s.to));
+                    current = s.to;
+                    writeln!(
+                        self.out,
+                        "{} c",
+                        nums([c0.x, c0.y, c1.x, c1.y, s.to.x, s.to.y])
+                    )
+                }
+                PathSegment::CubicBezier(s) => {
+                    current = s.to;
+                    writeln!(
+                        self.out,
+                        "{} c",
+                        nums([s.c0.x, s.c0.y, s.c1.x, s.c1.y, s.to.x, s.to.y])
+                    )
+                }
+            };
+        }
+        if figure.end == PathEnd::Closed {
+            self.out.push_str("h\n");
+        }
+    }
+}
+
+// A font that text can be drawn with, valid for the writer that made it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PdfFont(usize);
+
+impl PdfFont {
+    // Needs no embedding, but only covers WinAnsi; other characters are drawn as '?'.
+    pub const HELVETICA: Self = Self(0);
+}
+
+// Pixels added to a writer, which can be drawn on any of its pages.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PdfImage {
+    index: usize,
+    size: Size<u32>,
+}
+
+impl PdfImage {
+    #[inline]
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+}
+
+struct EmbeddedFont {
+    family: String,
+    file: FontFile,
+    object: usize,
+    used: BTreeMap<u16, char>,
+}
+
+struct ImageData {
+    size: Size<u32>,
+    rgb: Vec<u8>,
+    smask: Option<usize>,
+    // Resource names, without and with interpolation.
+    names: [Option<String>; 2],
+}
+
+struct Page {
+    size: Size<f32>,
+    content: String,
+}
+
+// What a brush paints with once its objects are written, so that they are written once
+// per brush and page size.
+#[derive(Clone)]
+enum Paint {
+    Nothing,
+    Solid(Rgba),
+    Pattern { name: String, mask: Option<String> },
+}
+
+// Builds a PDF document from drawing operations, in the order they are made.
+pub struct PdfWriter {
+    objects: Vec<Vec<u8>>,
+    pages: Vec<Page>,
+    fonts: Vec<EmbeddedFont>,
+    images: Vec<ImageData>,
+    alphas: HashMap<(bool, u32), String>,
+    paints: Vec<(BrushDesc, Size<f32>, Paint)>,
+    resources: BTreeMap<&'static str, String>,
+    next_name: usize,
+    clip_depth: usize,
+}
+
+impl PdfWriter {
+    const CATALOG: usize = 1;
+    const PAGES: usize = 2;
+    const RESOURCES: usize = 3;
+
+    pub fn new(page_size: impl Into<Size<f32>>) -> Self {
+        let mut writer = Self {
+            objects: vec![Vec::new(); 3],
+            pages: Vec::new(),
+            fonts: Vec::new(),
+            images: Vec::new(),
+            alphas: HashMap::new(),
+            paints: Vec::new(),
+            resources: BTreeMap::new(),
+            next_name: 0,
+            clip_depth: 0,
+        };
+        let helvetica = writer.object(
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        );
+        writer
+            .resources
+            .insert("Font", format!("/F0 {helvetica} 0 R "));
+        writer.new_page(page_size);
+        writer
+    }
+
+    // Clips that are still pushed on the current page are closed.
+    pub fn new_page(&mut self, size: impl Into<Size<f32>>) {
+        self.close_clips();
+        self.pages.push(Page {
+            size: size.into(),
+            content: String::new(),
+        });
+    }
+
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    #[inline]
+    pub fn page_size(&self) -> Size<f32> {
+        self.page().size
+    }
+
+    // `Font::File` and `Font::Memory` are embedded whole; system fonts cannot be looked up
+    // without the system, so `Font::System` fails with `Error::FontNotFound`.
+    pub fn load_font(&mut self, font: &Font) -> Result<PdfFont> {
+        let (file, family) = match font {
+            Font::System(name) => return Err(Error::FontNotFound(name.to_string())),
+            Font::File(path, name) => (FontFile::parse(&std::fs::read(path)?, name)?, name),
+            Font::Memory(data, name) => (FontFile::parse(data, name)?, name),
+        };
+        let object = self.object(Vec::new());
+        self.fonts.push(EmbeddedFont {
+            family: family.to_string(),
+            file,
+            object,
+            used: BTreeMap::new(),
+        });
+        let font = PdfFont(self.fonts.len());
+        self.add_resource("Font", &format!("F{}", font.0), object);
+        Ok(font)
+    }
+
+    pub fn add_image(&mut self, size: impl Into<Size<u32>>, pixels: &[Rgba8]) -> Result<PdfImage> {
+        let size: Size<u32> = size.into();
+        let expected = size.width as usize * size.height as usize;
+        if pixels.len() != expected {
+            return Err(Error::PixelCount {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+        let smask = pixels.iter().any(|p| p.a < 255).then(|| {
+            let alpha = pixels.iter().map(|p| p.a).collect::<Vec<_>>();
+            self.stream(
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceGray /BitsPerComponent 8 /Filter /FlateDecode",
+                    size.width, size.height
+                ),
+                &compress(&alpha),
+            )
+        });
+        let rgb = pixels
+            .iter()
+            .flat_map(|p| [p.r, p.g, p.b])
+            .collect::<Vec<_>>();
+        self.images.push(ImageData {
+            size,
+            rgb: compress(&rgb),
+            smask,
+            names: [None, None],
+        });
+        Ok(PdfImage {
+            index: self.images.len() - 1,
+            size,
+        })
+    }
+
+    // PDF cannot replace what is underneath, so outside of clips everything drawn on the
+    // page so far is dropped; inside a clip the color is painted over it.
+    pub fn clear(&mut self, color: impl Into<Rgba>) {
+        let color = color.into();
+        if self.clip_depth == 0 {
+            self.page_mut().content.clear();
+        }
+        let size = self.page().size;
+        self.fill(
+            &Rect::new(0.0, 0.0, size.width, size.height),
+            &BrushDesc::Solid(color),
+        );
+    }
+
+    pub fn fill(&mut self, object: &impl Fill, brush: &BrushDesc) {
+        let mut path = String::new();
+        object.fill(&mut Operators { out: &mut path });
+        if path.is_empty() {
+            return;
+        }
+        let Some(paint) = self.paint(brush, false) else {
+            return;
+        };
+        let _ = write!(self.page_mut().content, "q\n{paint}{path}f\nQ\n");
+    }
+
+    pub fn stroke(
+        &mut self,
+        object: &impl Stroke,
+        brush: &BrushDesc,
+        width: f32,
+        style: Option<&StrokeStyleProperties>,
+    ) {
+        let mut path = String::new();
+        object.stroke(&mut Operators { out: &mut path });
+        if path.is_empty() {
+            return;
+        }
+        let Some(paint) = self.paint(brush, true) else {
+            return;
+        };
+        let operators = stroke_operators(width, style);
+        let _ = write!(self.page_mut().content, "q\n{paint}{operators}{path}S\nQ\n");
+    }
+
+    // `position` is the top left of the text, as with `DrawCommand::draw_text`. Lines are
+    // 1.2 times `size` apart.
+    pub fn draw_text(
+        &mut self,
+        text: &str,
+        position: impl Into<Point<f32>>,
+        font: &PdfFont,
+        size: f32,
+        brush: &BrushDesc,
+    ) {
+        let position: Point<f32> = position.into();
+        let Some(paint) = self.paint(brush, false) else {
+            return;
+        };
+        let (ascent, lines) = match self.fonts.get_mut(font.0.wrapping_sub(1)) {
+            Some(embedded) => {
+                let file = &embedded.file;
+                let lines = text
+                    .split('\n')
+                    .map(|line| {
+                        line.chars()
+                            .map(|c| {
+                                let glyph = file.glyph(c);
+                                embedded.used.entry(glyph).or_insert(c);
+                                format!("{glyph:04X}")
+                            })
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>();
+                (file.ascent as f32 / file.units_per_em, lines)
+            }
+            // Readers usually stand in Arial for Helvetica, so its ascent is used.
+            None => {
+                let lines = text
+                    .split('\n')
+                    .map(|line| {
+                        line.chars()
+                            .map(|c| format!("{:02X}", win_ansi(c)))
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>();
+                (0.905, lines)
+            }
+        };
+        let name = if font.0 <= self.fonts.len() {
+            font.0
+        } else {
+            0
+        };
+        let content = &mut self.page_mut().content;
+        let _ = write!(
+            content,
+            "q\n{paint}BT\n/F{name} {} Tf\n{} TL\n1 0 0 -1 {} Tm\n",
+            Num(size),
+            Num(size * 1.2),
+            nums([position.x, position.y + ascent * size])
+        );
+        for (i, line) in lines.iter().enumerate() {
+            let next = if i > 0 { "T* " } else { "" };
+            let _ = writeln!(content, "{next}<{line}> Tj");
+        }
+        content.push_str("ET\nQ\n");
+    }
+
+    pub fn draw_image(
+        &mut self,
+        image: &PdfImage,
+        src_rect: Option<Rect<f32>>,
+        dest_rect: impl Into<Rect<f32>>,
+        opacity: Option<f32>,
+        interpolation: Interpolation,
+    ) {
+        let dest: Rect<f32> = dest_rect.into();
+        let Some(data) = self.images.get(image.index) else {
+            return;
+        };
+        let (width, height) = (data.size.width as f32, data.size.height as f32);
+        let src = src_rect.unwrap_or(Rect::new(0.0, 0.0, width, height));
+        let (src_width, src_height) = (src.right - src.left, src.bottom - src.top);
+        let opacity = opacity.map_or(1.0, |opacity| opacity.clamp(0.0, 1.0));
+        if src_width == 0.0 || src_height == 0.0 || opacity == 0.0 {
+            return;
+        }
+        let name = self.image_name(image.index, interpolation != Interpolation::NearestNeighbor);
+        let mut content = String::from("q\n");
+        if opacity < 1.0 {
+            content.push_str(&self.alpha(opacity, false));
+        }
+        if src_rect.is_some() {
+            Operators { out: &mut content }.rect(&normalize(dest));
+            content.push_str("W n\n");
+        }
+        // Images fill the unit square from the bottom up; map it onto the pixels and them
+        // onto the destination, flipping if either rect is reversed.
+        let sx = (dest.right - dest.left) / src_width;
+        let sy = (dest.bottom - dest.top) / src_height;
+        let transform = Matrix3x2::new(width, 0.0, 0.0, -height, 0.0, height).then(Matrix3x2::new(
+            sx,
+            0.0,
+            0.0,
+            sy,
+            dest.left - src.left * sx,
+            dest.top - src.top * sy,
+        ));
+        let _ = write!(content, "{} cm\n/{name} Do\nQ\n", matrix(transform));
+        self.page_mut().content.push_str(&content);
+    }
+
+    pub fn push_clip(&mut self, rect: impl Into<Rect<f32>>) {
+        let content = &mut self.page_mut().content;
+        content.push_str("q\n");
+        Operators { out: content }.rect(&rect.into());
+        content.push_str("W n\n");
+        self.clip_depth += 1;
+    }
+
+    #[inline]
+    pub fn pop_clip(&mut self) {
+        if self.clip_depth > 0 {
+            self.page_mut().content.push_str("Q\n");
+            self.clip_depth -= 1;
+        }
+    }
+
+    // The font a family was loaded under with `load_font`, if any.
+    pub fn find_font(&self, family: &str) -> Option<PdfFont> {
+        self.fonts
+            .iter()
+            .position(|font| font.family.eq_ignore_ascii_case(family))
+            .map(|i| PdfFont(i + 1))
+    }
+
+    // Text in the list is drawn with the font loaded for its family, or with Helvetica when
+    // there is none, and images are looked up by `ImageId`, failing the replay for an id
+    // without an image. Weight and italic come from the loaded font file, as PDF cannot
+    // synthesize them.
+    pub fn replay(&mut self, list: &DisplayList, images: &[PdfImage]) -> Result<()> {
+        for command in list {
+            match command {
+                DisplayCommand::Clear(color) => self.clear(*color),
+                DisplayCommand::Fill { shape, brush } => self.fill(shape, brush),
+                DisplayCommand::Stroke {
+                    shape,
+                    brush,
+                    width,
+                    style,
+                } => {
+                    let style = style.as_ref().map(|style| style.properties());
+                    self.stroke(shape, brush, *width, style.as_ref());
+                }
+                DisplayCommand::DrawText {
+                    text,
+                    position,
+                    font,
+                    brush,
+                } => {
+                    let pdf_font = self.find_font(&font.family).unwrap_or(PdfFont::HELVETICA);
+                    self.draw_text(text, *position, &pdf_font, font.size, brush);
+                }
+                DisplayCommand::DrawImage {
+                    image,
+                    src_rect,
+                    dest_rect,
+                    opacity,
+                    interpolation,
+                } => {
+                    let image = image.get(images)?;
+                    self.draw_image(image, *src_rect, *dest_rect, *opacity, *interpolation);
+                }
+                DisplayCommand::PushClip(rect) => self.push_clip(*rect),
+                DisplayCommand::PopClip => self.pop_clip(),
+            }
+        }
+        Ok(())
+    }
+
+    // Clips that are still pushed are closed.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.close_clips();
+        for font in std::mem::take(&mut self.fonts) {
+            self.write_font(font);
+        }
+        let resources = self
+            .resources
+            .iter()
+            .map(|(kind, entries)| format!("/{kind} << {entries}>>"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.objects[Self::RESOURCES - 1] = format!("<< {resources} >>").into_bytes();
+        let mut kids = Vec::new();
+        for page in std::mem::take(&mut self.pages) {
+            let content = format!("{} cm\n{}", matrix(flip(page.size.height)), page.content);
+            let content = self.stream("", content.as_bytes());
+            let page = self.object(
+                format!(
+                    "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {}] /Resources {} 0 R /Contents {content} 0 R >>",
+                    Self::PAGES,
+                    nums([page.size.width, page.size.height]),
+                    Self::RESOURCES
+                )
+                .into_bytes(),
+            );
+            kids.push(format!("{page} 0 R"));
+        }
+        self.objects[Self::PAGES - 1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            kids.len()
+        )
+        .into_bytes();
+        self.objects[Self::CATALOG - 1] =
+            format!("<< /Type /Catalog /Pages {} 0 R >>", Self::PAGES).into_bytes();
+
+        let mut out = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for (i, object) in self.objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend(object);
+            out.extend(b"\nendobj\n");
+        }
+        let xref = out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(table, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            self.objects.len() + 1,
+            Self::CATALOG
+        );
+        out.extend(table.as_bytes());
+        out
+    }
+
+    #[inline]
+    fn page(&self) -> &Page {
+        self.pages.last().unwrap()
+    }
+
+    #[inline]
+    fn page_mut(&mut self) -> &mut Page {
+        self.pages.last_mut().unwrap()
+    }
+
+    fn close_clips(&mut self) {
+        while self.clip_depth > 0 {
+            self.pop_clip();
+        }
+    }
+
+    fn object(&mut self, body: Vec<u8>) -> usize {
+        self.objects.push(body);
+        self.objects.len()
+    }
+
+    fn stream(&mut self, dict: &str, data: &[u8]) -> usize {
+        let mut body = format!("<< {dict} /Length {} >>\nstream\n", data.len()).into_bytes();
+        body.extend(data);
+        body.extend(b"\nendstream");
+        self.object(body)
+    }
+
+    fn add_resource(&mut self, kind: &'static str, name: &str, object: usize) {
+        let _ = write!(
+            self.resources.entry(kind).or_default(),
+            "/{name} {object} 0 R "
+        );
+    }
+
+    fn name(&mut self, prefix: &str) -> String {
+        self.next_name += 1;
+        format!("{prefix}{}", self.next_name)
+    }
+
+    fn image_name(&mut self, index: usize, interpolate: bool) -> String {
+        if let Some(name) = &self.images[index].names[interpolate as usize] {
+            return name.clone();
+        }
+        let data = &self.images[index];
+        let mut dict = format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Interpolate {interpolate} /Filter /FlateDecode",
+            data.size.width, data.size.height
+        );
+        if let Some(smask) = data.smask {
+            let _ = write!(dict, " /SMask {smask} 0 R");
+        }
+        let rgb = data.rgb.clone();
+        let object = self.stream(&dict, &rgb);
+        let name = self.name("Im");
+        self.add_resource("XObject", &name, object);
+        self.images[index].names[interpolate as usize] = Some(name.clone());
+        name
+    }
+
+    // Sets the constant alpha for filling, or for stroking.
+    fn alpha(&mut self, alpha: f32, stroke: bool) -> String {
+        let key = (stroke, alpha.to_bits());
+        if let Some(name) = self.alphas.get(&key) {
+            return format!("/{name} gs\n");
+        }
+        let operator = if stroke { "CA" } else { "ca" };
+        let object =
+            self.object(format!("<< /Type /ExtGState /{operator} {} >>", Num(alpha)).into_bytes());
+        let name = self.name("GS");
+        self.add_resource("ExtGState", &name, object);
+        self.alphas.insert(key, name.clone());
+        format!("/{name} gs\n")
+    }
+
+    fn solid(&mut self, color: Rgba, stroke: bool) -> Option<String> {
+        if color.a <= 0.0 {
+            return None;
+        }
+        let operator = if stroke { "RG" } else { "rg" };
+        let mut out = format!("{} {operator}\n", nums(rgb(color)));
+        if color.a < 1.0 {
+            out.push_str(&self.alpha(color.a, stroke));
+        }
+        Some(out)
+    }
+
+    // Returns the operators that paint with `brush`, defining shadings and patterns on its
+    // first use, or `None` when nothing would be visible.
+    fn paint(&mut self, brush: &BrushDesc, stroke: bool) -> Option<String> {
+        if let BrushDesc::Solid(color) = brush {
+            return self.solid(*color, stroke);
+        }
+        let size = self.page().size;
+        let paint = match self
+            .paints
+            .iter()
+            .find(|(desc, page_size, _)| desc == brush && *page_size == size)
+        {
+            Some((_, _, paint)) => paint.clone(),
+            None => {
+                let paint = self.define_paint(brush);
+                self.paints.push((brush.clone(), size, paint.clone()));
+                paint
+            }
+        };
+        match paint {
+            Paint::Nothing => None,
+            Paint::Solid(color) => self.solid(color, stroke),
+            Paint::Pattern { name, mask } => {
+                let mut out = if stroke {
+                    format!("/Pattern CS /{name} SCN\n")
+                } else {
+                    format!("/Pattern cs /{name} scn\n")
+                };
+                if let Some(mask) = mask {
+                    let _ = writeln!(out, "/{mask} gs");
+                }
+                Some(out)
+            }
+        }
+    }
+
+    fn define_paint(&mut self, brush: &BrushDesc) -> Paint {
+        let last_stop = |stops: &GradientStops| {
+            stops
+                .as_slice()
+                .last()
+                .map_or(Rgba::TRANSPARENT, |stop| stop.color)
+        };
+        let (shape, mode, stops, options) = match brush {
+            BrushDesc::Solid(color) => return Paint::Solid(*color),
+            BrushDesc::Linear {
+                start,
+                end,
+                mode,
+                stops,
+                options,
+            } => {
+                if start == end {
+                    return Paint::Solid(last_stop(stops));
+                }
+                let shape = GradientShape::Linear {
+                    start: *start,
+                    end: *end,
+                };
+                (shape, *mode, stops, options)
+            }
+            BrushDesc::Radial {
+                ellipse,
+                offset,
+                mode,
+                stops,
+                options,
+            } => {
+                if ellipse.radius_x <= 0.0 || ellipse.radius_y <= 0.0 {
+                    return Paint::Solid(last_stop(stops));
+                }
+                let shape = GradientShape::Radial {
+                    ellipse: *ellipse,
+                    offset: *offset,
+                };
+                (shape, *mode, stops, options)
+            }
+            // PDF has no conic shadings, so the bounds are tiled with an image of one.
+            BrushDesc::Conic {
+                bounds,
+                center,
+                start_angle,
+                end_angle,
+                mode,
+                stops,
+                options,
+            } => {
+                let bounds = normalize(*bounds);
+                let pixels = |len: f32| (len.ceil() as u32).clamp(1, 4096);
+                let size = Size::new(
+                    pixels(bounds.right - bounds.left),
+                    pixels(bounds.bottom - bounds.top),
+                );
+                let gradient = Gradient {
+                    shape: GradientShape::Conic {
+                        center: *center,
+                        start_angle: *start_angle,
+                        end_angle: *end_angle,
+                    },
+                    mode: *mode,
+                    stops: stops.clone(),
+                    options: *options,
+                };
+                let pixels = gradient
+                    .rasterize(bounds, size)
+                    .into_iter()
+                    .map(Rgba8::from)
+                    .collect::<Vec<_>>();
+                let tile = Size::new(bounds.right - bounds.left, bounds.bottom - bounds.top);
+                let transform = Matrix3x2::translation(bounds.left, bounds.top);
+                return Paint::Pattern {
+                    name: self.image_pattern(size, &pixels, tile, transform),
+                    mask: None,
+                };
+            }
+            BrushDesc::Pattern(pattern) => {
+                let size = pattern.tile_size(1.0);
+                let pixels = pattern
+                    .render_tile(1.0)
+                    .into_iter()
+                    .map(Rgba8::from)
+                    .collect::<Vec<_>>();
+                let tile = Size::new(size.width as f32, size.height as f32);
+                let transform = pattern.tile_transform(1.0);
+                return Paint::Pattern {
+                    name: self.image_pattern(size, &pixels, tile, transform),
+                    mask: None,
+                };
+            }
+        };
+        let mut stops = stops.as_slice().to_vec();
+        let (Some(first), Some(last)) = (stops.first().cloned(), stops.last().cloned()) else {
+            return Paint::Nothing;
+        };
+        let translucent = stops.iter().any(|stop| stop.color.a < 1.0);
+        let varying_alpha = stops.iter().any(|stop| stop.color.a != first.color.a);
+        // Shadings interpolate in sRGB with color and alpha apart; anything else is
+        // approximated by dense stops.
+        if options.color_space != GradientColorSpace::Srgb
+            || (varying_alpha && options.alpha == AlphaInterpolation::Premultiplied)
+        {
+            stops = GradientStops::new(&stops).subdivide(options);
+        }
+        for stop in &mut stops {
+            stop.position = stop.position.clamp(0.0, 1.0);
+        }
+        if first.position > 0.0 {
+            stops.insert(0, GradientStop::new(0.0, first.color));
+        }
+        if last.position < 1.0 {
+            stops.push(GradientStop::new(1.0, last.color));
+        }
+
+        let size = self.page().size;
+        let (kind, coords, domain, unit) = self.shading_geometry(&shape, mode, size);
+        let colors = self.stops_function(&stops, |c| rgb(c).to_vec());
+        let colors = self.repeat(colors, mode, domain);
+        let shading = |color_space: &str, function: usize| {
+            format!(
+                "<< /ShadingType {kind} /ColorSpace /{color_space} /Coords [{coords}] /Domain [{}] /Function {function} 0 R /Extend [true true] >>",
+                nums([domain.0, domain.1])
+            )
+        };
+        let pattern = self.object(
+            format!(
+                "<< /Type /Pattern /PatternType 2 /Shading {} /Matrix [{}] >>",
+                shading("DeviceRGB", colors),
+                matrix(unit.then(flip(size.height)))
+            )
+            .into_bytes(),
+        );
+        let name = self.name("P");
+        self.add_resource("Pattern", &name, pattern);
+        let mut mask = None;
+        if translucent {
+            // The alpha of the stops is a gray shading of its own, used as a soft mask.
+            let alphas = self.stops_function(&stops, |c| vec![c.a.clamp(0.0, 1.0)]);
+            let alphas = self.repeat(alphas, mode, domain);
+            let shading = self.object(shading("DeviceGray", alphas).into_bytes());
+            let form = self.stream(
+                &format!(
+                    "/Type /XObject /Subtype /Form /BBox [0 0 {}] /Group << /S /Transparency /CS /DeviceGray >> /Resources << /Shading << /Sh0 {shading} 0 R >> >>",
+                    nums([size.width, size.height])
+                ),
+                format!("q\n{} cm\n/Sh0 sh\nQ", matrix(unit)).as_bytes(),
+            );
+            let state = self.object(
+                format!(
+                    "<< /Type /ExtGState /SMask << /Type /Mask /S /Luminosity /G {form} 0 R >> >>"
+                )
+                .into_bytes(),
+            );
+            let state_name = self.name("GS");
+            self.add_resource("ExtGState", &state_name, state);
+            mask = Some(state_name);
+        }
+        Paint::Pattern { name, mask }
+    }
+
+    // Returns the shading type, its coordinates, its domain and the transform from shading
+    // space to user space. Repeating gradients get a domain that covers the whole page.
+    fn shading_geometry(
+        &self,
+        shape: &GradientShape,
+        mode: GradientMode,
+        size: Size<f32>,
+    ) -> (u8, String, (f32, f32), Matrix3x2) {
+        let corners = [
+            (0.0, 0.0),
+            (size.width, 0.0),
+            (0.0, size.height),
+            (size.width, size.height),
+        ]
+        .map(|corner| shape.position(corner).unwrap_or(0.0));
+        let domain = |min: f32| {
+            if mode == GradientMode::Clamp {
+                return (0.0, 1.0);
+            }
+            // Past a few dozen repetitions the bands are too thin to matter.
+            let max = corners.iter().fold(1.0_f32, |a, &b| a.max(b)).ceil();
+            let min = min.floor().max(max - 64.0);
+            (min, max.min(min + 64.0))
+        };
+        match *shape {
+            GradientShape::Linear { start, end } => {
+                let (t0, t1) = domain(corners.iter().fold(0.0_f32, |a, &b| a.min(b)));
+                let at = |t: f32| {
+                    [
+                        start.x + (end.x - start.x) * t,
+                        start.y + (end.y - start.y) * t,
+                    ]
+                };
+                let coords = nums(at(t0).into_iter().chain(at(t1)));
+                (2, coords, (t0, t1), Matrix3x2::IDENTITY)
+            }
+            GradientShape::Radial { ellipse, offset } => {
+                // A unit circle stretched onto the ellipse, with the focal point kept inside.
+                let mut fx = offset.x / ellipse.radius_x;
+                let mut fy = offset.y / ellipse.radius_y;
+                let f2 = fx * fx + fy * fy;
+                if f2 >= 1.0 {
+                    let scale = (1.0 - 1e-3) / f2.sqrt();
+                    fx *= scale;
+                    fy *= scale;
+                }
+                let (_, t1) = domain(0.0);
+                let coords = nums([fx, fy, 0.0, fx * (1.0 - t1), fy * (1.0 - t1), t1]);
+                let unit = Matrix3x2::new(
+                    ellipse.radius_x,
+                    0.0,
+                    0.0,
+                    ellipse.radius_y,
+                    ellipse.center.x,
+                    ellipse.center.y,
+                );
+                (3, coords, (0.0, t1), unit)
+            }
+            GradientShape::Conic { .. } => unreachable!(),
+        }
+    }
+
+    // Interpolates between every pair of stops, which cover 0 to 1.
+    fn stops_function(
+        &mut self,
+        stops: &[GradientStop],
+        channels: impl Fn(Rgba) -> Vec<f32>,
+    ) -> usize {
+        let functions = stops
+            .windows(2)
+            .map(|pair| {
+                format!(
+                    "<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>",
+                    nums(channels(pair[0].color)),
+                    nums(channels(pair[1].color))
+                )
+            })
+            .collect::<Vec<_>>();
+        let bounds = nums(stops[1..stops.len() - 1].iter().map(|stop| stop.position));
+        let encode = vec!["0 1"; functions.len()].join(" ");
+        self.object(
+            format!(
+                "<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{bounds}] /Encode [{encode}] >>",
+                functions.join(" ")
+            )
+            .into_bytes(),
+        )
+    }
+
+    // Runs `function` once per unit of `domain`, backwards every other time when mirrored.
+    fn repeat(&mut self, function: usize, mode: GradientMode, domain: (f32, f32)) -> usize {
+        if mode == GradientMode::Clamp {
+            return function;
+        }
+        let (t0, t1) = (domain.0 as i32, domain.1 as i32);
+        let functions = vec![format!("{function} 0 R"); (t1 - t0) as usize].join(" ");
+        let bounds = nums((t0 + 1..t1).map(|t| t as f32));
+        let encode = (t0..t1)
+            .map(|t| {
+                if mode == GradientMode::Mirror && t.rem_euclid(2) == 1 {
+                    "1 0"
+                } else {
+                    "0 1"
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.object(
+            format!(
+                "<< /FunctionType 3 /Domain [{}] /Functions [{functions}] /Bounds [{bounds}] /Encode [{encode}] >>",
+                nums([domain.0, domain.1])
+            )
+            .into_bytes(),
+        )
+    }
+
+    // Tiles user space with an image of `tile` size, placed by `transform`, and returns the
+    // name of the pattern.
+    fn image_pattern(
+        &mut self,
+        size: Size<u32>,
+        pixels: &[Rgba8],
+        tile: Size<f32>,
+        transform: Matrix3x2,
+    ) -> String {
+        // The pixels are rendered for `size`, so their count always matches.
+        let image = self.add_image(size, pixels).unwrap();
+        let image = self.image_name(image.index, true);
+        let (width, height) = (tile.width, tile.height);
+        let object = self.stream(
+            &format!(
+                "/Type /Pattern /PatternType 1 /PaintType 1 /TilingType 1 /BBox [0 0 {0}] /XStep {1} /YStep {2} /Matrix [{3}] /Resources {4} 0 R",
+                nums([width, height]),
+                Num(width),
+                Num(height),
+                matrix(transform.then(flip(self.page().size.height))),
+                Self::RESOURCES
+            ),
+            format!(
+                "q\n{} cm\n/{image} Do\nQ",
+                nums([width, 0.0, 0.0, -height, 0.0, height])
+            )
+            .as_bytes(),
+        );
+        let name = self.name("P");
+        self.add_resource("Pattern", &name, object);
+        name
+    }
+
+    fn write_font(&mut self, font: EmbeddedFont) {
+        let file = &font.file;
+        let scale = |v: f32| v * 1000.0 / file.units_per_em;
+        let data = compress(&file.data);
+        let (subtype, font_file, dict) = if file.cff {
+            (
+                "CIDFontType0",
+                "FontFile3",
+                "/Subtype /OpenType /Filter /FlateDecode".to_string(),
+            )
+        } else {
+            (
+                "CIDFontType2",
+                "FontFile2",
+                format!("/Length1 {} /Filter /FlateDecode", file.data.len()),
+            )
+        };
+        let font_file_object = self.stream(&dict, &data);
+        let name = &file.postscript_name;
+        let flags = if file.italic_angle != 0.0 { 4 | 64 } else { 4 };
+        let descriptor = self.object(
+            format!(
+                "<< /Type /FontDescriptor /FontName /{name} /Flags {flags} /FontBBox [{}] /ItalicAngle {} /Ascent {} /Descent {} /CapHeight {} /StemV 80 /{font_file} {font_file_object} 0 R >>",
+                nums(file.bbox.map(|v| scale(v as f32))),
+                Num(file.italic_angle),
+                Num(scale(file.ascent as f32)),
+                Num(scale(file.descent as f32)),
+                Num(scale(file.cap_height as f32))
+            )
+            .into_bytes(),
+        );
+        let widths = font
+            .used
+            .keys()
+            .map(|&glyph| format!("{glyph} [{}]", Num(scale(file.advance(glyph) as f32))))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let map = if file.cff {
+            ""
+        } else {
+            " /CIDToGIDMap /Identity"
+        };
+        let descendant = self.object(
+            format!(
+                "<< /Type /Font /Subtype /{subtype} /BaseFont /{name} /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> /FontDescriptor {descriptor} 0 R /W [{widths}]{map} >>"
+            )
+            .into_bytes(),
+        );
+        let mut cmap = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n/CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+        );
+        // Glyph 0 stands in for every character the font lacks, so it maps to none.
+        let used = font
+            .used
+            .iter()
+            .filter(|(glyph, _)| **glyph != 0)
+            .collect::<Vec<_>>();
+        for chunk in used.chunks(100) {
+            let _ = writeln!(cmap, "{} beginbfchar", chunk.len());
+            for (glyph, c) in chunk {
+                let unicode = c
+                    .encode_utf16(&mut [0; 2])
+                    .iter()
+                    .map(|unit| format!("{unit:04X}"))
+                    .collect::<String>();
+                let _ = writeln!(cmap, "<{glyph:04X}> <{unicode}>");
+            }
+            cmap.push_str("endbfchar\n");
+        }
+        cmap.push_str("endcmap\nCMapName currentdict /CMapResource defineresource pop\nend\nend");
+        let to_unicode = self.stream("", cmap.as_bytes());
+        self.objects[font.object - 1] = format!(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /{name} /Encoding /Identity-H /DescendantFonts [{descendant} 0 R] /ToUnicode {to_unicode} 0 R >>"
+        )
+        .into_bytes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Splits the document into its objects, checking the cross-reference table on the way.
+    fn objects(pdf: &[u8]) -> Vec<String> {
+        let text = String::from_utf8_lossy(pdf);
+        assert!(text.starts_with("%PDF-1.7\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        let tail = &pdf[pdf.len() - 32..];
+        let tail = String::from_utf8_lossy(tail);
+        let xref: usize = tail
+            .split("startxref\n")
+            .nth(1)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let table = String::from_utf8_lossy(&pdf[xref..]);
+        let mut lines = table.lines();
+        assert_eq!(lines.next(), Some("xref"));
+        let count: usize = lines.next().unwrap()[2..].parse().unwrap();
+        lines.next();
+        (1..count)
+            .map(|i| {
+                let offset: usize = lines.next().unwrap()[..10].parse().unwrap();
+                let object = &pdf[offset..];
+                let end = object.windows(6).position(|w| w == b"endobj").unwrap();
+                let object = String::from_utf8_lossy(&object[..end]).into_owned();
+                let header = format!("{i} 0 obj\n");
+                assert!(object.starts_with(&header));
+                object[header.len()..].to_string()
+            })
+            .collect()
+    }
+
+    fn reference(object: &str, key: &str) -> usize {
+        let start = object.find(&format!("/{key} ")).unwrap() + key.len() + 2;
+        object[start..]
+            .trim_start_matches('[')
+            .split(' ')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    fn page_contents(objects: &[String]) -> Vec<String> {
+        objects
+            .iter()
+            .filter(|object| object.starts_with("<< /Type /Page "))
+            .map(|page| {
+                let content = &objects[reference(page, "Contents") - 1];
+                let start = content.find("stream\n").unwrap() + 7;
+                let end = content.rfind("\nendstream").unwrap();
+                content[start..end].to_string()
+            })
+            .collect()
+    }
+
+    fn render(f: impl FnOnce(&mut PdfWriter)) -> (Vec<String>, String) {
+        let mut writer = PdfWriter::new((100.0, 80.0));
+        f(&mut writer);
+        let objects = objects(&writer.finish());
+        let content = page_contents(&objects).remove(0);
+        (objects, content)
+    }
+
+    fn find<'a>(objects: &'a [String], needle: &str) -> Vec<&'a String> {
+        objects.iter().filter(|o| o.contains(needle)).collect()
+    }
+
+    // A TrueType font with 'A' and 'B' and no outlines, which is all PDF needs to know.
+    fn test_font() -> Vec<u8> {
+        fn be16(values: &[u16]) -> Vec<u8> {
+            values.iter().flat_map(|v| v.to_be_bytes()).collect()
+        }
+        let mut head = vec![0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        head[36..44].copy_from_slice(&be16(&[(-50i16) as u16, (-200i16) as u16, 950, 800]));
+        let mut hhea = vec![0; 36];
+        hhea[4..8].copy_from_slice(&be16(&[800, (-200i16) as u16]));
+        hhea[34..36].copy_from_slice(&3u16.to_be_bytes());
+        let maxp = be16(&[0x0000, 0x5000, 3]);
+        let hmtx = be16(&[250, 0, 500, 0, 600, 0]);
+        let mut cmap = be16(&[0, 1, 3, 1, 0, 12]);
+        cmap.extend(be16(&[4, 32, 0, 4, 4, 1, 0]));
+        cmap.extend(be16(&[0x42, 0xffff, 0, 0x41, 0xffff]));
+        cmap.extend(be16(&[1u16.wrapping_sub(0x41), 1, 0, 0]));
+        let family = be16(&"Test Sans".encode_utf16().collect::<Vec<_>>());
+        let mut name = be16(&[0, 1, 18, 3, 1, 0x409, 1, family.len() as u16, 0]);
+        name.extend(family);
+        let tables: [(&[u8; 4], Vec<u8>); 6] = [
+            (b"cmap", cmap),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"maxp", maxp),
+            (b"name", name),
+        ];
+        let mut out = be16(&[1, 0, tables.len() as u16, 64, 2, 32]);
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, table) in &tables {
+            out.extend(*tag);
+            out.extend([0; 4]);
+            out.extend((offset as u32).to_be_bytes());
+            out.extend((table.len() as u32).to_be_bytes());
+            offset += table.len().next_multiple_of(4);
+        }
+        for (_, table) in &tables {
+            out.extend(table);
+            out.resize(out.len().next_multiple_of(4), 0);
+        }
+        out
+    }
+
+    #[test]
+    fn writes_pages_with_a_valid_xref() {
+        let mut writer = PdfWriter::new((200.0, 100.0));
+        writer.fill(
+            &Rect::new(10.0, 20.0, 40.0, 60.0),
+            &BrushDesc::solid(Rgba::RED),
+        );
+        writer.new_page((50.0, 60.0));
+        writer.fill(
+            &Circle::new((25.0, 30.0), 10.0),
+            &BrushDesc::solid(Rgba::BLACK),
+        );
+        assert_eq!(writer.page_count(), 2);
+        let objects = objects(&writer.finish());
+        let catalog = &objects[0];
+        assert!(catalog.starts_with("<< /Type /Catalog"));
+        let pages = &objects[reference(catalog, "Pages") - 1];
+        assert!(pages.contains("/Count 2"));
+        let sizes = find(&objects, "/Type /Page ")
+            .iter()
+            .map(|page| page.split("/MediaBox ").nth(1).unwrap().split(']').next())
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [Some("[0 0 200 100"), Some("[0 0 50 60")]);
+        let contents = page_contents(&objects);
+        assert_eq!(
+            contents[0],
+            "1 0 0 -1 0 100 cm\nq\n1 0 0 rg\n10 20 30 40 re\nf\nQ\n"
+        );
+        assert!(contents[1].starts_with("1 0 0 -1 0 60 cm\n"));
+        assert_eq!(contents[1].matches(" c\n").count(), 4);
+        assert!(contents[1].contains("h\nf\n"));
+    }
+
+    #[test]
+    fn maps_stroke_style() {
+        let dashes = [3.0, 1.0];
+        let style = StrokeStyleProperties {
+            start_cap: CapStyle::Square,
+            line_join: LineJoin::Round,
+            dash: Some(Dash {
+                cap: CapStyle::Round,
+                style: DashStyle::Custom(&dashes),
+                offset: 1.0,
+            }),
+            ..Default::default()
+        };
+        let (objects, content) = render(|w| {
+            w.stroke(
+                &Line::new((0.0, 0.0), (10.0, 10.0)),
+                &BrushDesc::solid(Rgba::new(0.0, 0.0, 1.0, 0.5)),
+                2.0,
+                Some(&style),
+            );
+            w.stroke(
+                &PathFigure::new((0.0, 0.0)).quadratic_bezier_to((3.0, 3.0), (6.0, 0.0)),
+                &BrushDesc::solid(Rgba::BLACK),
+                1.0,
+                None,
+            );
+        });
+        assert!(
+            content.contains("0 0 1 RG\n/GS1 gs\n2 w\n1 j\n1 J [6 2] 2 d\n0 0 m\n10 10 l\nS\n")
+        );
+        assert!(content.contains("1 w\n10 M\n0 0 m\n2 2 4 2 6 0 c\nS\n"));
+        assert_eq!(find(&objects, "/CA 0.5").len(), 1);
+    }
+
+    #[test]
+    fn gradients_become_shadings() {
+        let (objects, content) = render(|w| {
+            w.fill(
+                &Rect::new(0.0, 0.0, 100.0, 80.0),
+                &BrushDesc::linear(
+                    (0.0, 0.0),
+                    (25.0, 0.0),
+                    GradientMode::Mirror,
+                    &[(0.0, Rgba::RED), (1.0, Rgba::BLUE)],
+                ),
+            );
+            w.fill(
+                &Circle::new((50.0, 40.0), 20.0),
+                &BrushDesc::radial(
+                    Ellipse::new((50.0, 40.0), 20.0, 10.0),
+                    (0.0, 0.0),
+                    GradientMode::Clamp,
+                    &[(0.0, Rgba::WHITE), (1.0, Rgba::TRANSPARENT)],
+                )
+                .options(GradientOptions::new().alpha(AlphaInterpolation::Straight)),
+            );
+        });
+        let axial = find(&objects, "/ShadingType 2");
+        assert_eq!(axial.len(), 1);
+        assert!(axial[0].contains("/Coords [0 0 100 0] /Domain [0 4]"));
+        assert!(axial[0].contains("/Matrix [1 0 0 -1 0 80]"));
+        let repeat = &objects[reference(axial[0], "Function") - 1];
+        assert!(repeat.contains("/Bounds [1 2 3] /Encode [0 1 1 0 0 1 1 0]"));
+        let radial = find(&objects, "/ShadingType 3");
+        assert_eq!(radial.len(), 2);
+        assert!(radial[0].contains("/ColorSpace /DeviceRGB /Coords [0 0 0 0 0 1]"));
+        assert!(radial[0].contains("/Matrix [20 0 0 -10 50 40]"));
+        assert!(radial[1].contains("/ColorSpace /DeviceGray"));
+        let alpha = &objects[reference(radial[1], "Function") - 1];
+        assert!(alpha.contains("/C0 [1] /C1 [0]"));
+        assert_eq!(find(&objects, "/S /Luminosity").len(), 1);
+        assert!(content.contains("/Pattern cs /P1 scn\n"));
+        assert!(content.contains("/Pattern cs /P2 scn\n/GS3 gs\n"));
+    }
+
+    #[test]
+    fn writes_the_objects_of_a_brush_once() {
+        let gradient = BrushDesc::linear(
+            (0.0, 0.0),
+            (25.0, 0.0),
+            GradientMode::Clamp,
+            &[(0.0, Rgba::RED), (1.0, Rgba::TRANSPARENT)],
+        );
+        let pattern = BrushDesc::from(Pattern::new(HatchStyle::Dots));
+        let (objects, content) = render(|w| {
+            for _ in 0..3 {
+                w.fill(&Rect::new(0.0, 0.0, 10.0, 10.0), &gradient);
+                w.stroke(&Rect::new(0.0, 0.0, 10.0, 10.0), &gradient, 1.0, None);
+                w.fill(&Rect::new(0.0, 0.0, 10.0, 10.0), &pattern);
+            }
+        });
+        assert_eq!(find(&objects, "/ShadingType 2").len(), 2);
+        assert_eq!(find(&objects, "/S /Luminosity").len(), 1);
+        assert_eq!(find(&objects, "/PatternType 1").len(), 1);
+        assert_eq!(
+            find(&objects, "/ColorSpace /DeviceRGB /BitsPerComponent").len(),
+            1
+        );
+        assert_eq!(content.matches("/Pattern cs /P1 scn\n/GS2 gs\n").count(), 3);
+        assert_eq!(content.matches("/Pattern CS /P1 SCN\n/GS2 gs\n").count(), 3);
+    }
+
+    #[test]
+    fn nests_clips_in_saved_states() {
+        let mut writer = PdfWriter::new((100.0, 80.0));
+        writer.push_clip(Rect::new(0.0, 0.0, 50.0, 50.0));
+        writer.push_clip(Rect::new(10.0, 10.0, 40.0, 40.0));
+        writer.fill(
+            &Rect::new(0.0, 0.0, 100.0, 80.0),
+            &BrushDesc::solid(Rgba::RED),
+        );
+        writer.pop_clip();
+        // Left pushed on purpose; the next page closes it.
+        writer.new_page((100.0, 80.0));
+        writer.push_clip(Rect::new(0.0, 0.0, 5.0, 5.0));
+        let contents = page_contents(&objects(&writer.finish()));
+        assert_eq!(
+            contents[0],
+            "1 0 0 -1 0 80 cm\nq\n0 0 50 50 re\nW n\nq\n10 10 30 30 re\nW n\nq\n1 0 0 rg\n0 0 100 80 re\nf\nQ\nQ\nQ\n"
+        );
+        assert_eq!(contents[1], "1 0 0 -1 0 80 cm\nq\n0 0 5 5 re\nW n\nQ\n");
+    }
+
+    #[test]
+    fn embeds_images_with_soft_masks() {
+        let mut pixels = [Rgba8::new(255, 0, 0, 255); 6];
+        pixels[4].a = 0;
+        let mut writer = PdfWriter::new((100.0, 80.0));
+        assert!(writer.add_image((2, 2), &pixels).is_err());
+        let image = writer.add_image((3, 2), &pixels).unwrap();
+        assert_eq!(image.size(), Size::new(3, 2));
+        for _ in 0..2 {
+            writer.draw_image(
+                &image,
+                Some(Rect::new(1.0, 0.0, 3.0, 2.0)),
+                Rect::new(10.0, 10.0, 30.0, 30.0),
+                Some(0.5),
+                Interpolation::NearestNeighbor,
+            );
+        }
+        writer.draw_image(
+            &image,
+            None,
+            Rect::new(0.0, 0.0, 3.0, 2.0),
+            None,
+            Interpolation::Linear,
+        );
+        let objects = objects(&writer.finish());
+        let images = find(&objects, "/Subtype /Image");
+        assert_eq!(images.len(), 3);
+        let mask = images[0];
+        assert!(mask.contains("/Width 3 /Height 2 /ColorSpace /DeviceGray"));
+        assert!(images[1].contains("/Interpolate false"));
+        assert!(images[2].contains("/Interpolate true"));
+        assert_eq!(reference(images[1], "SMask"), reference(images[2], "SMask"));
+        let content = &page_contents(&objects)[0];
+        assert_eq!(
+            content
+                .matches("/GS2 gs\n10 10 20 20 re\nW n\n30 0 0 -20 0 30 cm\n/Im1 Do\n")
+                .count(),
+            2
+        );
+        assert!(content.contains("q\n3 0 0 -2 0 2 cm\n/Im3 Do\nQ\n"));
+    }
+
+    #[test]
+    fn embeds_font_files() {
+        let data = test_font();
+        let mut writer = PdfWriter::new((100.0, 80.0));
+        assert!(matches!(
+            writer.load_font(&Font::System("Arial")),
+            Err(Error::FontNotFound(_))
+        ));
+        assert!(matches!(
+            writer.load_font(&Font::Memory(b"not a font", "Test Sans")),
+            Err(Error::InvalidFont)
+        ));
+        let font = writer.load_font(&Font::Memory(&data, "Test Sans")).unwrap();
+        let black = BrushDesc::solid(Rgba::BLACK);
+        writer.draw_text("AB\nA?", (5.0, 6.0), &font, 10.0, &black);
+        writer.draw_text("Aé☃", (0.0, 0.0), &PdfFont::HELVETICA, 12.0, &black);
+        let objects = objects(&writer.finish());
+        let content = &page_contents(&objects)[0];
+        assert!(content.contains(
+            "BT\n/F1 10 Tf\n12 TL\n1 0 0 -1 5 14 Tm\n<00010002> Tj\nT* <00010000> Tj\nET\n"
+        ));
+        assert!(content.contains("/F0 12 Tf\n14.4 TL\n1 0 0 -1 0 10.86 Tm\n<41E93F> Tj\n"));
+        let type0 = find(&objects, "/Subtype /Type0")[0];
+        assert!(type0.contains("/BaseFont /TestSans /Encoding /Identity-H"));
+        let cid = &objects[reference(type0, "DescendantFonts") - 1];
+        assert!(cid.contains("/Subtype /CIDFontType2"));
+        assert!(cid.contains("/W [0 [250] 1 [500] 2 [600]]"));
+        let descriptor = &objects[reference(cid, "FontDescriptor") - 1];
+        assert!(descriptor.contains("/FontBBox [-50 -200 950 800]"));
+        assert!(descriptor.contains("/Ascent 800 /Descent -200"));
+        let file = &objects[reference(descriptor, "FontFile2") - 1];
+        assert!(file.contains(&format!("/Length1 {}", data.len())));
+        let to_unicode = &objects[reference(type0, "ToUnicode") - 1];
+        assert!(to_unicode.contains("2 beginbfchar\n<0001> <0041>\n<0002> <0042>\n"));
+    }
+
+    #[test]
+    fn replays_display_lists() {
+        let mut list = DisplayList::new();
+        list.fill(Rect::new(0.0, 0.0, 10.0, 10.0), Rgba::RED);
+        list.clear(Rgba::WHITE);
+        list.push_clip(Rect::new(0.0, 0.0, 50.0, 50.0));
+        list.stroke(Circle::new((20.0, 20.0), 5.0), Rgba::BLACK, 1.0, None);
+        list.draw_text("hi", (0.0, 0.0), &FontDesc::new("Arial", 12.0), Rgba::BLACK);
+        list.pop_clip();
+        let mut writer = PdfWriter::new((100.0, 100.0));
+        writer.replay(&list, &[]).unwrap();
+        let mut missing = DisplayList::new();
+        missing.draw_image(
+            ImageId(3),
+            None,
+            Rect::new(0.0, 0.0, 1.0, 1.0),
+            None,
+            Interpolation::Linear,
+        );
+        assert!(matches!(
+            writer.replay(&missing, &[]),
+            Err(Error::ImageNotFound(3))
+        ));
+        let content = page_contents(&objects(&writer.finish())).remove(0);
+        // The red rect is gone with the clear.
+        assert!(!content.contains("1 0 0 rg"));
+        let operators = content
+            .lines()
+            .filter_map(|line| line.rsplit(' ').next())
+            .filter(|op| ["re", "f", "S", "Tj", "Do", "Q"].contains(op))
+            .collect::<Vec<_>>();
+        assert_eq!(operators, ["re", "f", "Q", "re", "S", "Q", "Tj", "Q", "Q"]);
+    }
+
+    #[test]
+    fn replays_text_with_the_font_of_its_family() {
+        let data = test_font();
+        let mut writer = PdfWriter::new((100.0, 100.0));
+        let font = writer.load_font(&Font::Memory(&data, "Test Sans")).unwrap();
+        assert_eq!(writer.find_font("test sans"), Some(font));
+        assert_eq!(writer.find_font("Arial"), None);
+        let mut list = DisplayList::new();
+        list.draw_text(
+            "A",
+            (0.0, 0.0),
+            &FontDesc::new("Test Sans", 10.0),
+            Rgba::BLACK,
+        );
+        list.draw_text("B", (0.0, 20.0), &FontDesc::new("Arial", 14.0), Rgba::BLACK);
+        writer.replay(&list, &[]).unwrap();
+        let content = page_contents(&objects(&writer.finish())).remove(0);
+        let fonts = content
+            .lines()
+            .filter(|line| line.ends_with(" Tf"))
+            .collect::<Vec<_>>();
+        assert_eq!(fonts, ["/F1 10 Tf", "/F0 14 Tf"]);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::*;
+use std::collections::HashMap;
+
+fn u16_at(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or(Error::InvalidFont)
+}
+
+fn i16_at(data: &[u8], offset: usize) -> Result<i16> {
+    u16_at(data, offset).map(|v| v as i16)
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(Error::InvalidFont)
+}
+
+struct TableRecord {
+    tag: [u8; 4],
+    checksum: u32,
+    offset: usize,
+    len: usize,
+}
+
+// One face of an sfnt file, which may be a TrueType collection.
+struct Face<'a> {
+    data: &'a [u8],
+    version: u32,
+    tables: Vec<TableRecord>,
+}
+
+impl<'a> Face<'a> {
+    fn new(data: &'a [u8], offset: usize) -> Result<Self> {
+        let version = u32_at(data, offset)?;
+        let count = u16_at(data, offset + 4)? as usize;
+        let mut tables = Vec::with_capacity(count);
+        for i in 0..count {
+            let record = offset + 12 + i * 16;
+            let tag = data
+                .get(record..record + 4)
+                .ok_or(Error::InvalidFont)?
+                .try_into()
+                .unwrap();
+            let table = TableRecord {
+                tag,
+                checksum: u32_at(data, record + 4)?,
+                offset: u32_at(data, record + 8)? as usize,
+                len: u32_at(data, record + 12)? as usize,
+            };
+            if data.len() < table.offset.saturating_add(table.len) {
+                return Err(Error::InvalidFont);
+            }
+            tables.push(table);
+        }
+        Ok(Self {
+            data,
+            version,
+            tables,
+        })
+    }
+
+    fn faces(data: &'a [u8]) -> Result<Vec<Self>> {
+        if data.get(..4) != Some(b"ttcf") {
+            return Ok(vec![Self::new(data, 0)?]);
+        }
+        let count = u32_at(data, 8)? as usize;
+        (0..count)
+            .map(|i| Self::new(data, u32_at(data, 12 + i * 4)? as usize))
+            .collect()
+    }
+
+    fn table(&self, tag: &[u8; 4]) -> Option<&'a [u8]> {
+        self.tables
+            .iter()
+            .find(|table| &table.tag == tag)
+            .map(|table| &self.data[table.offset..table.offset + table.len])
+    }
+
+    fn required(&self, tag: &[u8; 4]) -> Result<&'a [u8]> {
+        self.table(tag).ok_or(Error::InvalidFont)
+    }
+
+    fn names(&self, id: u16) -> Vec<String> {
+        let Some(table) = self.table(b"name") else {
+            return Vec::new();
+        };
+        let (Ok(count), Ok(storage)) = (u16_at(table, 2), u16_at(table, 4)) else {
+            return Vec::new();
+        };
+        let mut names = Vec::new();
+        for i in 0..count as usize {
+            let record = 6 + i * 12;
+            let (Ok(platform), Ok(encoding), Ok(name_id), Ok(len), Ok(offset)) = (
+                u16_at(table, record),
+                u16_at(table, record + 2),
+                u16_at(table, record + 6),
+                u16_at(table, record + 8),
+                u16_at(table, record + 10),
+            ) else {
+                break;
+            };
+            let start = storage as usize + offset as usize;
+            let Some(bytes) = table.get(start..start + len as usize) else {
+                continue;
+            };
+            if name_id != id {
+                continue;
+            }
+            let name = match (platform, encoding) {
+                (0, _) | (3, 0 | 1 | 10) => String::from_utf16_lossy(
+                    &bytes
+                        .chunks_exact(2)
+                        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                        .collect::<Vec<_>>(),
+                ),
+                (1, 0) => bytes.iter().map(|&b| b as char).collect(),
+                _ => continue,
+            };
+            names.push(name);
+        }
+        names
+    }
+
+    // Copies the face out of a collection so that it can be embedded on its own.
+    fn to_sfnt(&self) -> Vec<u8> {
+        let count = self.tables.len();
+        let mut out = Vec::new();
+        out.extend(self.version.to_be_bytes());
+        out.extend((count as u16).to_be_bytes());
+        let log2 = (usize::BITS - 1 - count.max(1).leading_zeros()) as u16;
+        let search_range = (1u16 << log2) * 16;
+        out.extend(search_range.to_be_bytes());
+        out.extend(log2.to_be_bytes());
+        out.extend((count as u16 * 16 - search_range).to_be_bytes());
+        let mut offset = 12 + count * 16;
+        for table in &self.tables {
+            out.extend(table.tag);
+            out.extend(table.checksum.to_be_bytes());
+            out.extend((offset as u32).to_be_bytes());
+            out.extend((table.len as u32).to_be_bytes());
+            offset += table.len.next_multiple_of(4);
+        }
+        for table in &self.tables {
+            out.extend(&self.data[table.offset..table.offset + table.len]);
+            out.resize(out.len().next_multiple_of(4), 0);
+        }
+        out
+    }
+}
+
+fn parse_cmap(table: &[u8]) -> Result<HashMap<u32, u16>> {
+    let count = u16_at(table, 2)? as usize;
+    let mut best = None;
+    for i in 0..count {
+        let record = 4 + i * 8;
+        let platform = u16_at(table, record)?;
+        let encoding = u16_at(table, record + 2)?;
+        let offset = u32_at(table, record + 4)? as usize;
+        let format = u16_at(table, offset)?;
+        // Full Unicode subtables win over BMP-only ones.
+        let rank = match (platform, encoding, format) {
+            (3, 10, 12) | (0, _, 12) => 2,
+            (3, 1, 4) | (0, _, 4) => 1,
+            _ => continue,
+        };
+        if best.is_none_or(|(r, _)| rank > r) {
+            best = Some((rank, offset));
+        }
+    }
+    let (_, offset) = best.ok_or(Error::InvalidFont)?;
+    let subtable = &table[offset..];
+    let mut map = HashMap::new();
+    if u16_at(subtable, 0)? == 12 {
+        let groups = u32_at(subtable, 12)? as usize;
+        for i in 0..groups {
+            let group = 16 + i * 12;
+            let start = u32_at(subtable, group)?;
+            let end = u32_at(subtable, group + 4)?.min(0x10_ffff);
+            let glyph = u32_at(subtable, group + 8)?;
+            for c in start..=end {
+                map.insert(c, (glyph + (c - start)) as u16);
+            }
+        }
+    } else {
+        let segments = u16_at(subtable, 6)? as usize / 2;
+        let ends = 14;
+        let starts = ends + segments * 2 + 2;
+        let deltas = starts + segments * 2;
+        let range_offsets = deltas + segments * 2;
+        for i in 0..segments {
+            let end = u16_at(subtable, ends + i * 2)?;
+            let start = u16_at(subtable, starts + i * 2)?;
+            let delta = u16_at(subtable, deltas + i * 2)?;
+            let range_offset = u16_at(subtable, range_offsets + i * 2)? as usize;
+            for c in start..=end.min(0xfffe) {
+                let glyph = if range_offset == 0 {
+                    c.wrapping_add(delta)
+                } else {
+                    let index = range_offsets + i * 2 + range_offset + (c - start) as usize * 2;
+                    match u16_at(subtable, index)? {
+                        0 => 0,
+                        glyph => glyph.wrapping_add(delta),
+                    }
+                };
+                if glyph != 0 {
+                    map.insert(c as u32, glyph);
+                }
+            }
+        }
+    }
+    Ok(map)
+}
+
+// The parts of a TrueType or OpenType font that PDF needs to embed it.
+pub(super) struct FontFile {
+    pub data: Vec<u8>,
+    pub cff: bool,
+    pub postscript_name: String,
+    pub units_per_em: f32,
+    pub bbox: [i16; 4],
+    pub ascent: i16,
+    pub descent: i16,
+    pub cap_height: i16,
+    pub italic_angle: f32,
+    advances: Vec<u16>,
+    glyphs: HashMap<u32, u16>,
+}
+
+impl FontFile {
+    // `family` picks the face of a collection; a file with a single face is used whatever
+    // it is called.
+    pub fn parse(data: &[u8], family: &str) -> Result<Self> {
+        let faces = Face::faces(data)?;
+        let matches = |face: &Face| {
+            [16, 1, 4, 6]
+                .into_iter()
+                .flat_map(|id| face.names(id))
+                .any(|name| name.eq_ignore_ascii_case(family))
+        };
+        let face = match faces.iter().position(matches) {
+            Some(i) => &faces[i],
+            None if faces.len() == 1 => &faces[0],
+            None => return Err(Error::FontNotFound(family.to_string())),
+        };
+        let head = face.required(b"head")?;
+        let hhea = face.required(b"hhea")?;
+        let hmtx = face.required(b"hmtx")?;
+        let glyph_count = u16_at(face.required(b"maxp")?, 4)? as usize;
+        let metrics = (u16_at(hhea, 34)? as usize).clamp(1, glyph_count.max(1));
+        let mut advances = Vec::with_capacity(glyph_count);
+        for i in 0..metrics {
+            advances.push(u16_at(hmtx, i * 4)?);
+        }
+        let last = *advances.last().unwrap();
+        advances.resize(glyph_count.max(metrics), last);
+        let ascent = i16_at(hhea, 4)?;
+        let cap_height = face
+            .table(b"OS/2")
+            .filter(|os2| u16_at(os2, 0).is_ok_and(|version| version >= 2))
+            .and_then(|os2| i16_at(os2, 88).ok())
+            .unwrap_or(ascent);
+        let italic_angle = face
+            .table(b"post")
+            .and_then(|post| u32_at(post, 4).ok())
+            .map_or(0.0, |angle| angle as i32 as f32 / 65536.0);
+        // PDF names cannot hold spaces or delimiters.
+        let postscript_name = face
+            .names(6)
+            .into_iter()
+            .chain(face.names(4))
+            .chain([family.to_string()])
+            .map(|name| {
+                name.chars()
+                    .filter(|c| c.is_ascii_graphic() && !"()<>[]{}/%#".contains(*c))
+                    .collect::<String>()
+            })
+            .find(|name| !name.is_empty())
+            .unwrap_or_else(|| "Font".to_string());
+        Ok(Self {
+            data: if faces.len() == 1 {
+                data.to_vec()
+            } else {
+                face.to_sfnt()
+            },
+            cff: face.version == u32::from_be_bytes(*b"OTTO"),
+            postscript_name,
+            units_per_em: u16_at(head, 18)?.max(1) as f32,
+            bbox: [
+                i16_at(head, 36)?,
+                i16_at(head, 38)?,
+                i16_at(head, 40)?,
+                i16_at(head, 42)?,
+            ],
+            ascent,
+            descent: i16_at(hhea, 6)?,
+            cap_height,
+            italic_angle,
+            advances,
+            glyphs: parse_cmap(face.required(b"cmap")?)?,
+        })
+    }
+
+    // Characters the font does not cover map to glyph 0, which draws as a missing glyph.
+    #[inline]
+    pub fn glyph(&self, c: char) -> u16 {
+        self.glyphs.get(&(c as u32)).copied().unwrap_or(0)
+    }
+
+    #[inline]
+    pub fn advance(&self, glyph: u16) -> u16 {
+        self.advances.get(glyph as usize).copied().unwrap_or(0)
+    }
+}
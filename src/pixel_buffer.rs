@@ -0,0 +1,340 @@
+use crate::codec::{decode_png, decode_qoi, encode_bmp, encode_png, encode_qoi};
+use crate::*;
+use std::path::Path;
+
+// File formats that are encoded without WIC.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImageFormat {
+    Png,
+    Bmp,
+    Qoi,
+}
+
+impl ImageFormat {
+    // Recognizes a format by the signature at the start of `data`.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if data.starts_with(b"BM") {
+            Some(Self::Bmp)
+        } else if data.starts_with(b"qoif") {
+            Some(Self::Qoi)
+        } else {
+            None
+        }
+    }
+}
+
+fn premultiply(c: Rgba8) -> Rgba8 {
+    let f = |x: u8| ((x as u32 * c.a as u32 + 127) / 255) as u8;
+    Rgba8::new(f(c.r), f(c.g), f(c.b), c.a)
+}
+
+fn unpremultiply(c: Rgba8) -> Rgba8 {
+    let f = |x: u8| match c.a {
+        0 => 0,
+        a => ((x as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8,
+    };
+    Rgba8::new(f(c.r), f(c.g), f(c.b), c.a)
+}
+
+// Converts tightly packed pixels from one format to another in place.
+pub(crate) fn convert_pixels(pixels: &mut [u8], from: PixelFormat, to: PixelFormat) {
+    if from == to {
+        return;
+    }
+    for pixel in pixels.chunks_exact_mut(4) {
+        let color = Rgba8::from_bytes(pixel.try_into().unwrap(), from.channel_order());
+        let color = match (from.is_premultiplied(), to.is_premultiplied()) {
+            (true, false) => unpremultiply(color),
+            (false, true) => premultiply(color),
+            _ => color,
+        };
+        pixel.copy_from_slice(&color.to_bytes(to.channel_order()));
+    }
+}
+
+// Tightly packed pixels, four bytes each, such as those read back from a target.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PixelBuffer {
+    size: Size<u32>,
+    format: PixelFormat,
+    data: Vec<u8>,
+}
+
+impl PixelBuffer {
+    pub fn new(size: impl Into<Size<u32>>, format: PixelFormat, data: Vec<u8>) -> Result<Self> {
+        let size: Size<u32> = size.into();
+        let expected = size.width as usize * size.height as usize;
+        if data.len() != expected * 4 {
+            return Err(Error::PixelCount {
+                expected,
+                actual: data.len() / 4,
+            });
+        }
+        Ok(Self { size, format, data })
+    }
+
+    // Packs rows that start `stride` bytes apart, as in most pixel buffers handed over by
+    // other libraries. The last row does not need to be padded.
+    pub fn with_stride(
+        size: impl Into<Size<u32>>,
+        stride: u32,
+        format: PixelFormat,
+        data: &[u8],
+    ) -> Result<Self> {
+        let size: Size<u32> = size.into();
+        let row = size.width as usize * 4;
+        if (stride as usize) < row {
+            return Err(Error::InvalidStride(stride));
+        }
+        let expected = match size.height {
+            0 => 0,
+            height => stride as usize * (height as usize - 1) + row,
+        };
+        if data.len() < expected {
+            return Err(Error::ByteCount {
+                expected,
+                actual: data.len(),
+            });
+        }
+        let mut packed = Vec::with_capacity(row * size.height as usize);
+        for y in 0..size.height as usize {
+            let start = y * stride as usize;
+            packed.extend_from_slice(&data[start..start + row]);
+        }
+        Self::new(size, format, packed)
+    }
+
+    #[inline]
+    pub fn from_rgba8(size: impl Into<Size<u32>>, pixels: &[Rgba8]) -> Result<Self> {
+        Self::new(size, PixelFormat::Rgba8, Rgba8::as_bytes(pixels).to_vec())
+    }
+
+    #[inline]
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+
+    #[inline]
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    // Sniffs the format. PNG and QOI are decoded in Rust; other formats need `Image::from_bytes`.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let (size, pixels) = match ImageFormat::detect(data) {
+            Some(ImageFormat::Png) => decode_png(data)?,
+            Some(ImageFormat::Qoi) => decode_qoi(data)?,
+            _ => return Err(Error::UnsupportedImage),
+        };
+        Self::from_rgba8(size, &pixels)
+    }
+
+    pub fn to_format(&self, format: PixelFormat) -> Self {
+        let mut data = self.data.clone();
+        convert_pixels(&mut data, self.format, format);
+        Self {
+            size: self.size,
+            format,
+            data,
+        }
+    }
+
+    // Turns the stored pixels upright, swapping width and height for quarter turns.
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        let Size { width, height } = self.size;
+        let (w, h) = (width as usize, height as usize);
+        let size = if orientation.swaps_axes() {
+            Size::new(height, width)
+        } else {
+            self.size
+        };
+        let mut data = Vec::with_capacity(self.data.len());
+        for y in 0..size.height as usize {
+            for x in 0..size.width as usize {
+                let (sx, sy) = match orientation {
+                    Orientation::Normal => (x, y),
+                    Orientation::FlipHorizontal => (w - 1 - x, y),
+                    Orientation::Rotate180 => (w - 1 - x, h - 1 - y),
+                    Orientation::FlipVertical => (x, h - 1 - y),
+                    Orientation::Transpose => (y, x),
+                    Orientation::Rotate90 => (y, h - 1 - x),
+                    Orientation::Transverse => (w - 1 - y, h - 1 - x),
+                    Orientation::Rotate270 => (w - 1 - y, x),
+                };
+                let i = (sy * w + sx) * 4;
+                data.extend_from_slice(&self.data[i..i + 4]);
+            }
+        }
+        Self {
+            size,
+            format: self.format,
+            data,
+        }
+    }
+
+    // Straight alpha, which is what every `ImageFormat` stores.
+    #[inline]
+    pub fn to_rgba8(&self) -> Vec<Rgba8> {
+        Rgba8::cast_slice(self.to_format(PixelFormat::Rgba8).data()).to_vec()
+    }
+
+    pub fn encode(&self, format: ImageFormat) -> Result<Vec<u8>> {
+        let pixels = self.to_rgba8();
+        match format {
+            ImageFormat::Png => Ok(encode_png(self.size, &pixels)),
+            ImageFormat::Bmp => encode_bmp(self.size, &pixels),
+            ImageFormat::Qoi => Ok(encode_qoi(self.size, &pixels)),
+        }
+    }
+
+    #[inline]
+    pub fn save(&self, path: impl AsRef<Path>, format: ImageFormat) -> Result<()> {
+        std::fs::write(path, self.encode(format)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_data_of_the_wrong_length() {
+        assert!(matches!(
+            PixelBuffer::new((2, 2), PixelFormat::Rgba8, vec![0; 12]),
+            Err(Error::PixelCount {
+                expected: 4,
+                actual: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn encodes_straight_alpha_from_either_format() {
+        let bgra = PixelBuffer::new(
+            (2, 1),
+            PixelFormat::Bgra8Premultiplied,
+            vec![0, 0, 128, 128, 30, 20, 10, 0],
+        )
+        .unwrap();
+        let rgba = PixelBuffer::from_rgba8(
+            (2, 1),
+            &[Rgba8::new(255, 0, 0, 128), Rgba8::new(0, 0, 0, 0)],
+        )
+        .unwrap();
+        assert_eq!(bgra.to_rgba8(), rgba.to_rgba8());
+        for format in [ImageFormat::Png, ImageFormat::Bmp, ImageFormat::Qoi] {
+            assert_eq!(bgra.encode(format).unwrap(), rgba.encode(format).unwrap());
+        }
+        assert!(
+            bgra.encode(ImageFormat::Png)
+                .unwrap()
+                .starts_with(b"\x89PNG")
+        );
+        assert!(bgra.encode(ImageFormat::Bmp).unwrap().starts_with(b"BM"));
+        assert!(bgra.encode(ImageFormat::Qoi).unwrap().starts_with(b"qoif"));
+    }
+
+    #[test]
+    fn packs_strided_rows_and_converts_formats() {
+        #[rustfmt::skip]
+        let data = [
+            255, 0, 0, 128, 9, 9,
+            0, 0, 0, 0, 9, 9,
+            1, 2, 3, 255,
+        ];
+        let buffer = PixelBuffer::with_stride((1, 3), 6, PixelFormat::Rgba8, &data).unwrap();
+        assert_eq!(buffer.data(), [255, 0, 0, 128, 0, 0, 0, 0, 1, 2, 3, 255]);
+        assert_eq!(
+            buffer.to_format(PixelFormat::Bgra8Premultiplied).data(),
+            [0, 0, 128, 128, 0, 0, 0, 0, 3, 2, 1, 255]
+        );
+        assert_eq!(
+            buffer.to_format(PixelFormat::Rgba8Premultiplied).data(),
+            [128, 0, 0, 128, 0, 0, 0, 0, 1, 2, 3, 255]
+        );
+        assert_eq!(
+            buffer
+                .to_format(PixelFormat::Bgra8Premultiplied)
+                .to_format(PixelFormat::Bgra8)
+                .to_format(PixelFormat::Rgba8),
+            buffer
+        );
+        assert!(matches!(
+            PixelBuffer::with_stride((2, 1), 7, PixelFormat::Rgba8, &data),
+            Err(Error::InvalidStride(7))
+        ));
+        assert!(matches!(
+            PixelBuffer::with_stride((1, 3), 8, PixelFormat::Rgba8, &data),
+            Err(Error::ByteCount {
+                expected: 20,
+                actual: 16
+            })
+        ));
+    }
+
+    #[test]
+    fn decodes_sniffed_formats() {
+        let buffer =
+            PixelBuffer::from_rgba8((2, 1), &[Rgba8::new(1, 2, 3, 4), Rgba8::new(5, 6, 7, 255)])
+                .unwrap();
+        for format in [ImageFormat::Png, ImageFormat::Qoi] {
+            let data = buffer.encode(format).unwrap();
+            assert_eq!(ImageFormat::detect(&data), Some(format));
+            assert_eq!(PixelBuffer::decode(&data).unwrap(), buffer);
+        }
+        let bmp = buffer.encode(ImageFormat::Bmp).unwrap();
+        assert_eq!(ImageFormat::detect(&bmp), Some(ImageFormat::Bmp));
+        assert!(matches!(
+            PixelBuffer::decode(&bmp),
+            Err(Error::UnsupportedImage)
+        ));
+        assert_eq!(ImageFormat::detect(b"GIF89a"), None);
+    }
+
+    #[test]
+    fn saves_to_a_file() {
+        let path = std::env::temp_dir().join(format!("pnte-{}.qoi", std::process::id()));
+        let buffer = PixelBuffer::from_rgba8((1, 1), &[Rgba8::new(1, 2, 3, 4)]).unwrap();
+        buffer.save(&path, ImageFormat::Qoi).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(data, buffer.encode(ImageFormat::Qoi).unwrap());
+    }
+
+    #[test]
+    fn orients_every_exif_transform() {
+        let upright = [0, 1, 2, 3, 4, 5];
+        let stored: [(u16, u32, [u8; 6]); 8] = [
+            (1, 3, [0, 1, 2, 3, 4, 5]),
+            (2, 3, [2, 1, 0, 5, 4, 3]),
+            (3, 3, [5, 4, 3, 2, 1, 0]),
+            (4, 3, [3, 4, 5, 0, 1, 2]),
+            (5, 2, [0, 3, 1, 4, 2, 5]),
+            (6, 2, [2, 5, 1, 4, 0, 3]),
+            (7, 2, [5, 2, 4, 1, 3, 0]),
+            (8, 2, [3, 0, 4, 1, 5, 2]),
+        ];
+        for (value, width, pixels) in stored {
+            let pixels = pixels.map(|v| Rgba8::new(v, v, v, 255));
+            let buffer = PixelBuffer::from_rgba8((width, 6 / width), &pixels).unwrap();
+            let oriented = buffer.oriented(Orientation::from_exif(value));
+            assert_eq!(oriented.size(), Size::new(3, 2), "orientation {value}");
+            let values = oriented.to_rgba8().iter().map(|c| c.r).collect::<Vec<_>>();
+            assert_eq!(values, upright, "orientation {value}");
+        }
+    }
+}
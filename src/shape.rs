@@ -1,31 +1,35 @@
 use crate::*;
+#[cfg(windows)]
 use windows::Win32::Graphics::Direct2D::*;
 
 impl Fill for Rect<f32> {
     #[inline]
-    fn fill(&self, dc: &ID2D1DeviceContext5, brush: &ID2D1Brush) {
-        unsafe {
-            dc.FillRectangle(&(*self).into(), brush);
-        }
+    fn fill(&self, sink: &mut dyn GeometrySink) {
+        sink.rect(self);
     }
 }
 
 impl Stroke for Rect<f32> {
     #[inline]
-    fn stroke(
-        &self,
-        dc: &ID2D1DeviceContext5,
-        brush: &ID2D1Brush,
-        width: f32,
-        style: Option<&ID2D1StrokeStyle1>,
-    ) {
-        unsafe {
-            dc.DrawRectangle(&(*self).into(), brush, width, style.map(|s| s.into()));
-        }
+    fn stroke(&self, sink: &mut dyn GeometrySink) {
+        sink.rect(self);
+    }
+}
+
+#[cfg(windows)]
+impl Clip for Rect<f32> {
+    #[inline]
+    fn clip_rect(&self) -> Option<Rect<f32>> {
+        Some(*self)
+    }
+
+    #[inline]
+    fn clip_geometry(&self, factory: &ID2D1Factory6) -> Result<ID2D1Geometry> {
+        unsafe { Ok(factory.CreateRectangleGeometry(&(*self).into())?.into()) }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(C)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line(pub Point<f32>, pub Point<f32>);
@@ -39,26 +43,12 @@ impl Line {
 
 impl Stroke for Line {
     #[inline]
-    fn stroke(
-        &self,
-        dc: &ID2D1DeviceContext5,
-        brush: &ID2D1Brush,
-        width: f32,
-        style: Option<&ID2D1StrokeStyle1>,
-    ) {
-        unsafe {
-            dc.DrawLine(
-                self.0.into(),
-                self.1.into(),
-                brush,
-                width,
-                style.map(|s| s.into()),
-            );
-        }
+    fn stroke(&self, sink: &mut dyn GeometrySink) {
+        sink.line(self);
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(C)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundedRect {
@@ -78,6 +68,7 @@ impl RoundedRect {
     }
 }
 
+#[cfg(windows)]
 impl From<RoundedRect> for D2D1_ROUNDED_RECT {
     #[inline]
     fn from(value: RoundedRect) -> Self {
@@ -91,29 +82,31 @@ impl From<RoundedRect> for D2D1_ROUNDED_RECT {
 
 impl Fill for RoundedRect {
     #[inline]
-    fn fill(&self, dc: &ID2D1DeviceContext5, brush: &ID2D1Brush) {
-        unsafe {
-            dc.FillRoundedRectangle(&(*self).into(), brush);
-        }
+    fn fill(&self, sink: &mut dyn GeometrySink) {
+        sink.rounded_rect(self);
     }
 }
 
 impl Stroke for RoundedRect {
     #[inline]
-    fn stroke(
-        &self,
-        dc: &ID2D1DeviceContext5,
-        brush: &ID2D1Brush,
-        width: f32,
-        style: Option<&ID2D1StrokeStyle1>,
-    ) {
+    fn stroke(&self, sink: &mut dyn GeometrySink) {
+        sink.rounded_rect(self);
+    }
+}
+
+#[cfg(windows)]
+impl Clip for RoundedRect {
+    #[inline]
+    fn clip_geometry(&self, factory: &ID2D1Factory6) -> Result<ID2D1Geometry> {
         unsafe {
-            dc.DrawRoundedRectangle(&(*self).into(), brush, width, style.map(|s| s.into()));
+            Ok(factory
+                .CreateRoundedRectangleGeometry(&(*self).into())?
+                .into())
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(C)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Circle {
@@ -142,34 +135,27 @@ impl Circle {
 
 impl Fill for Circle {
     #[inline]
-    fn fill(&self, dc: &ID2D1DeviceContext5, brush: &ID2D1Brush) {
-        unsafe {
-            dc.FillEllipse(&self.to_ellipse().into(), brush);
-        }
+    fn fill(&self, sink: &mut dyn GeometrySink) {
+        sink.ellipse(&self.to_ellipse());
     }
 }
 
 impl Stroke for Circle {
     #[inline]
-    fn stroke(
-        &self,
-        dc: &ID2D1DeviceContext5,
-        brush: &ID2D1Brush,
-        width: f32,
-        style: Option<&ID2D1StrokeStyle1>,
-    ) {
-        unsafe {
-            dc.DrawEllipse(
-                &self.to_ellipse().into(),
-                brush,
-                width,
-                style.map(|s| s.into()),
-            );
-        }
+    fn stroke(&self, sink: &mut dyn GeometrySink) {
+        sink.ellipse(&self.to_ellipse());
+    }
+}
+
+#[cfg(windows)]
+impl Clip for Circle {
+    #[inline]
+    fn clip_geometry(&self, factory: &ID2D1Factory6) -> Result<ID2D1Geometry> {
+        self.to_ellipse().clip_geometry(factory)
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(C)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ellipse {
@@ -189,6 +175,7 @@ impl Ellipse {
     }
 }
 
+#[cfg(windows)]
 impl From<Ellipse> for D2D1_ELLIPSE {
     #[inline]
     fn from(value: Ellipse) -> Self {
@@ -202,24 +189,22 @@ impl From<Ellipse> for D2D1_ELLIPSE {
 
 impl Fill for Ellipse {
     #[inline]
-    fn fill(&self, dc: &ID2D1DeviceContext5, brush: &ID2D1Brush) {
-        unsafe {
-            dc.FillEllipse(&(*self).into(), brush);
-        }
+    fn fill(&self, sink: &mut dyn GeometrySink) {
+        sink.ellipse(self);
     }
 }
 
 impl Stroke for Ellipse {
     #[inline]
-    fn stroke(
-        &self,
-        dc: &ID2D1DeviceContext5,
-        brush: &ID2D1Brush,
-        width: f32,
-        style: Option<&ID2D1StrokeStyle1>,
-    ) {
-        unsafe {
-            dc.DrawEllipse(&(*self).into(), brush, width, style.map(|s| s.into()));
-        }
+    fn stroke(&self, sink: &mut dyn GeometrySink) {
+        sink.ellipse(self);
+    }
+}
+
+#[cfg(windows)]
+impl Clip for Ellipse {
+    #[inline]
+    fn clip_geometry(&self, factory: &ID2D1Factory6) -> Result<ID2D1Geometry> {
+        unsafe { Ok(factory.CreateEllipseGeometry(&(*self).into())?.into()) }
     }
 }
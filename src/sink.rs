@@ -0,0 +1,258 @@
+use crate::*;
+use std::any::Any;
+
+// Control point distance that makes four cubic beziers approximate an ellipse.
+const KAPPA: f32 = 0.552_284_8;
+
+// Receives the primitives a shape is made of. Whether they are filled or stroked is up to
+// the renderer that implements it.
+pub trait GeometrySink {
+    fn rect(&mut self, rect: &Rect<f32>);
+    fn rounded_rect(&mut self, rounded_rect: &RoundedRect);
+    fn ellipse(&mut self, ellipse: &Ellipse);
+    fn path(&mut self, figure: &PathFigure);
+
+    #[inline]
+    fn line(&mut self, line: &Line) {
+        self.path(&PathFigure::new(line.0).line_to(line.1));
+    }
+
+    // Offers a geometry the renderer may already know how to draw, such as the Direct2D
+    // geometry a `Path` holds. Returns `false` when it is not drawn, and the shape then
+    // describes itself with primitives instead.
+    #[inline]
+    fn native_geometry(&mut self, _geometry: &dyn Any) -> bool {
+        false
+    }
+}
+
+pub trait Fill {
+    fn fill(&self, sink: &mut dyn GeometrySink);
+}
+
+pub trait Stroke {
+    fn stroke(&self, sink: &mut dyn GeometrySink);
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Primitive {
+    Rect(Rect<f32>),
+    RoundedRect(RoundedRect),
+    Ellipse(Ellipse),
+    Line(Line),
+    Path(PathFigure),
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RecordingSink {
+    primitives: Vec<Primitive>,
+}
+
+impl RecordingSink {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn primitives(&self) -> &[Primitive] {
+        &self.primitives
+    }
+
+    #[inline]
+    pub fn into_primitives(self) -> Vec<Primitive> {
+        self.primitives
+    }
+}
+
+impl GeometrySink for RecordingSink {
+    #[inline]
+    fn rect(&mut self, rect: &Rect<f32>) {
+        self.primitives.push(Primitive::Rect(*rect));
+    }
+
+    #[inline]
+    fn rounded_rect(&mut self, rounded_rect: &RoundedRect) {
+        self.primitives.push(Primitive::RoundedRect(*rounded_rect));
+    }
+
+    #[inline]
+    fn ellipse(&mut self, ellipse: &Ellipse) {
+        self.primitives.push(Primitive::Ellipse(*ellipse));
+    }
+
+    #[inline]
+    fn path(&mut self, figure: &PathFigure) {
+        self.primitives.push(Primitive::Path(figure.clone()));
+    }
+
+    #[inline]
+    fn line(&mut self, line: &Line) {
+        self.primitives.push(Primitive::Line(*line));
+    }
+}
+
+// Turns every primitive into a path figure, for renderers that only draw paths.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct FigureSink {
+    figures: Vec<PathFigure>,
+}
+
+impl FigureSink {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn figures(&self) -> &[PathFigure] {
+        &self.figures
+    }
+
+    #[inline]
+    pub fn into_figures(self) -> Vec<PathFigure> {
+        self.figures
+    }
+}
+
+impl GeometrySink for FigureSink {
+    fn rect(&mut self, rect: &Rect<f32>) {
+        self.figures.push(
+            PathFigure::new((rect.left, rect.top))
+                .line_to((rect.right, rect.top))
+                .line_to((rect.right, rect.bottom))
+                .line_to((rect.left, rect.bottom))
+                .close(),
+        );
+    }
+
+    fn rounded_rect(&mut self, rounded_rect: &RoundedRect) {
+        let Rect {
+            left: l,
+            top: t,
+            right: r,
+            bottom: b,
+        } = rounded_rect.rect;
+        let rx = rounded_rect.radius_x.min((r - l).abs() / 2.0).max(0.0);
+        let ry = rounded_rect.radius_y.min((b - t).abs() / 2.0).max(0.0);
+        if rx == 0.0 || ry == 0.0 {
+            self.rect(&rounded_rect.rect);
+            return;
+        }
+        let kx = rx * KAPPA;
+        let ky = ry * KAPPA;
+        self.figures.push(
+            PathFigure::new((l + rx, t))
+                .line_to((r - rx, t))
+                .cubic_bezier_to((r - rx + kx, t), (r, t + ry - ky), (r, t + ry))
+                .line_to((r, b - ry))
+                .cubic_bezier_to((r, b - ry + ky), (r - rx + kx, b), (r - rx, b))
+                .line_to((l + rx, b))
+                .cubic_bezier_to((l + rx - kx, b), (l, b - ry + ky), (l, b - ry))
+                .line_to((l, t + ry))
+                .cubic_bezier_to((l, t + ry - ky), (l + rx - kx, t), (l + rx, t))
+                .close(),
+        );
+    }
+
+    fn ellipse(&mut self, ellipse: &Ellipse) {
+        let Point { x, y } = ellipse.center;
+        let (rx, ry) = (ellipse.radius_x, ellipse.radius_y);
+        let kx = rx * KAPPA;
+        let ky = ry * KAPPA;
+        self.figures.push(
+            PathFigure::new((x + rx, y))
+                .cubic_bezier_to((x + rx, y + ky), (x + kx, y + ry), (x, y + ry))
+                .cubic_bezier_to((x - kx, y + ry), (x - rx, y + ky), (x - rx, y))
+                .cubic_bezier_to((x - rx, y - ky), (x - kx, y - ry), (x, y - ry))
+                .cubic_bezier_to((x + kx, y - ry), (x + rx, y - ky), (x + rx, y))
+                .close(),
+        );
+    }
+
+    fn path(&mut self, figure: &PathFigure) {
+        self.figures.push(figure.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Cross {
+        center: Point<f32>,
+        size: f32,
+        thickness: f32,
+    }
+
+    impl Fill for Cross {
+        fn fill(&self, sink: &mut dyn GeometrySink) {
+            let Point { x, y } = self.center;
+            let (s, t) = (self.size / 2.0, self.thickness / 2.0);
+            sink.rect(&Rect::new(x - s, y - t, x + s, y + t));
+            sink.rect(&Rect::new(x - t, y - s, x + t, y + s));
+        }
+    }
+
+    fn fill(object: &impl Fill) -> Vec<Primitive> {
+        let mut sink = RecordingSink::new();
+        object.fill(&mut sink);
+        sink.into_primitives()
+    }
+
+    fn stroke(object: &impl Stroke) -> Vec<Primitive> {
+        let mut sink = RecordingSink::new();
+        object.stroke(&mut sink);
+        sink.into_primitives()
+    }
+
+    #[test]
+    fn custom_shapes_describe_themselves() {
+        let cross = Cross {
+            center: Point::new(10.0, 10.0),
+            size: 8.0,
+            thickness: 2.0,
+        };
+        assert_eq!(
+            fill(&cross),
+            [
+                Primitive::Rect(Rect::new(6.0, 9.0, 14.0, 11.0)),
+                Primitive::Rect(Rect::new(9.0, 6.0, 11.0, 14.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn shapes_forward_to_primitives() {
+        let circle = Circle::new((1.0, 2.0), 3.0);
+        assert_eq!(fill(&circle), [Primitive::Ellipse(circle.to_ellipse())]);
+        let line = Line::new((0.0, 0.0), (4.0, 0.0));
+        assert_eq!(stroke(&line), [Primitive::Line(line)]);
+        assert_eq!(stroke(&Shape::Line(line)), [Primitive::Line(line)]);
+        assert_eq!(fill(&Shape::Line(line)), []);
+        let figure = PathFigure::new((0.0, 0.0))
+            .quadratic_bezier_to((2.0, 2.0), (4.0, 0.0))
+            .close();
+        assert_eq!(fill(&figure), [Primitive::Path(figure.clone())]);
+    }
+
+    #[test]
+    fn figure_sink_turns_primitives_into_paths() {
+        let mut sink = FigureSink::new();
+        Line::new((1.0, 1.0), (3.0, 2.0)).stroke(&mut sink);
+        // Without both radii the corners stay square.
+        RoundedRect::new(Rect::new(0.0, 0.0, 4.0, 2.0), 0.0, 1.0).fill(&mut sink);
+        assert_eq!(
+            sink.figures(),
+            [
+                PathFigure::new((1.0, 1.0)).line_to((3.0, 2.0)),
+                PathFigure::new((0.0, 0.0))
+                    .line_to((4.0, 0.0))
+                    .line_to((4.0, 2.0))
+                    .line_to((0.0, 2.0))
+                    .close(),
+            ]
+        );
+    }
+}
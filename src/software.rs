@@ -0,0 +1,235 @@
+mod bitmap;
+mod canvas;
+mod outline;
+mod raster;
+mod stroke;
+
+pub use bitmap::*;
+pub use canvas::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn solid(color: impl Into<Rgba>) -> BrushDesc {
+        BrushDesc::solid(color)
+    }
+
+    fn alpha(canvas: &Canvas, x: u32, y: u32) -> f32 {
+        canvas.pixel(x, y).unwrap().a
+    }
+
+    #[test]
+    fn fills_rect_with_antialiased_edges() {
+        let mut canvas = Canvas::new((8, 8));
+        canvas.fill(&Rect::new(1.0, 1.0, 4.5, 6.0), &solid(Rgba::RED));
+        assert_eq!(canvas.pixel(2, 2), Some(Rgba::RED));
+        assert_eq!(alpha(&canvas, 0, 0), 0.0);
+        assert_eq!(alpha(&canvas, 6, 3), 0.0);
+        assert!((alpha(&canvas, 4, 3) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn aliased_fill_has_no_partial_pixels() {
+        let mut canvas = Canvas::new((8, 8));
+        canvas.set_antialias_mode(AntialiasMode::Aliased);
+        canvas.fill(&Circle::new((4.0, 4.0), 3.3), &solid(Rgba::BLACK));
+        for y in 0..8 {
+            for x in 0..8 {
+                let a = alpha(&canvas, x, y);
+                assert!(a == 0.0 || a == 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn ellipse_covers_its_area() {
+        let mut canvas = Canvas::new((64, 64));
+        canvas.fill(&Ellipse::new((32.0, 32.0), 20.0, 10.0), &solid(Rgba::BLACK));
+        let mut area = 0.0;
+        for y in 0..64 {
+            for x in 0..64 {
+                area += alpha(&canvas, x, y);
+            }
+        }
+        let expected = std::f32::consts::PI * 20.0 * 10.0;
+        assert!((area - expected).abs() < expected * 0.01);
+    }
+
+    #[test]
+    fn clips_to_pushed_rects() {
+        let mut canvas = Canvas::new((8, 8));
+        canvas.push_clip(Rect::new(0.0, 0.0, 4.0, 8.0));
+        canvas.push_clip(Rect::new(2.0, 0.0, 8.0, 8.0));
+        assert_eq!(canvas.clip_bounds(), Some(Rect::new(2.0, 0.0, 4.0, 8.0)));
+        canvas.fill(&Rect::new(0.0, 0.0, 8.0, 8.0), &solid(Rgba::BLACK));
+        canvas.pop_clip();
+        canvas.pop_clip();
+        assert_eq!(canvas.clip_bounds(), None);
+        assert_eq!(alpha(&canvas, 1, 4), 0.0);
+        assert_eq!(alpha(&canvas, 3, 4), 1.0);
+        assert_eq!(alpha(&canvas, 5, 4), 0.0);
+    }
+
+    #[test]
+    fn caps_extend_stroke_ends() {
+        let line = Line::new((4.0, 8.0), (12.0, 8.0));
+        let mut flat = Canvas::new((16, 16));
+        flat.stroke(&line, &solid(Rgba::BLACK), 4.0, None);
+        assert_eq!(alpha(&flat, 3, 8), 0.0);
+        assert_eq!(alpha(&flat, 8, 7), 1.0);
+        assert_eq!(alpha(&flat, 12, 8), 0.0);
+        let style = StrokeStyleProperties {
+            start_cap: CapStyle::Square,
+            end_cap: CapStyle::Round,
+            ..Default::default()
+        };
+        let mut capped = Canvas::new((16, 16));
+        capped.stroke(&line, &solid(Rgba::BLACK), 4.0, Some(&style));
+        assert_eq!(alpha(&capped, 2, 6), 1.0);
+        assert_eq!(alpha(&capped, 12, 8), 1.0);
+        assert_eq!(alpha(&capped, 14, 8), 0.0);
+    }
+
+    #[test]
+    fn dashes_leave_gaps() {
+        let style = StrokeStyleProperties {
+            dash: Some(Dash {
+                style: DashStyle::Dash,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new((32, 4));
+        canvas.stroke(
+            &Line::new((0.0, 2.0), (32.0, 2.0)),
+            &solid(Rgba::BLACK),
+            2.0,
+            Some(&style),
+        );
+        let row = (0..32).map(|x| alpha(&canvas, x, 2)).collect::<Vec<_>>();
+        assert_eq!(
+            &row[..10],
+            &[1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn linear_gradient_runs_between_points() {
+        let brush = BrushDesc::linear(
+            (0.0, 0.0),
+            (16.0, 0.0),
+            GradientMode::Clamp,
+            &[(0.0, Rgba::RED), (1.0, Rgba::BLUE)],
+        );
+        let mut canvas = Canvas::new((16, 1));
+        canvas.fill(&Rect::new(0.0, 0.0, 16.0, 1.0), &brush);
+        let left = canvas.pixel(0, 0).unwrap();
+        let right = canvas.pixel(15, 0).unwrap();
+        assert!(left.r > 0.9 && left.b < 0.1);
+        assert!(right.b > 0.9 && right.r < 0.1);
+    }
+
+    #[test]
+    fn draws_images_with_nearest_neighbor() {
+        let pixels = [
+            Rgba8::new(255, 0, 0, 255),
+            Rgba8::new(0, 255, 0, 255),
+            Rgba8::new(0, 0, 255, 255),
+            Rgba8::new(255, 255, 255, 255),
+        ];
+        let image = Bitmap::from_rgba8((2, 2), &pixels).unwrap();
+        let mut canvas = Canvas::new((4, 4));
+        canvas.draw_image(
+            &image,
+            None,
+            Rect::new(0.0, 0.0, 4.0, 4.0),
+            None,
+            Interpolation::NearestNeighbor,
+        );
+        let out = canvas.to_rgba8();
+        assert_eq!(out[0], pixels[0]);
+        assert_eq!(out[3], pixels[1]);
+        assert_eq!(out[12], pixels[2]);
+        assert_eq!(out[15], pixels[3]);
+        assert!(Bitmap::from_rgba8((3, 3), &pixels).is_err());
+    }
+
+    #[test]
+    fn replays_display_lists() {
+        let mut list = DisplayList::new();
+        list.fill(Rect::new(0.0, 0.0, 10.0, 10.0), Rgba::RED);
+        list.clear(Rgba::WHITE);
+        list.push_clip(Rect::new(0.0, 0.0, 50.0, 50.0));
+        list.stroke(Circle::new((20.0, 20.0), 5.0), Rgba::BLACK, 1.0, None);
+        list.draw_text("hi", (0.0, 0.0), &FontDesc::new("Arial", 12.0), Rgba::BLACK);
+        list.pop_clip();
+        let mut canvas = Canvas::new((100, 100));
+        canvas.replay(&list, &[]).unwrap();
+        // The red rect is gone with the clear, and the text draws nothing.
+        assert_eq!(canvas.pixel(5, 5), Some(Rgba::WHITE));
+        assert_eq!(canvas.pixel(0, 0), Some(Rgba::WHITE));
+        assert_eq!(canvas.pixel(20, 20), Some(Rgba::WHITE));
+        let edge = canvas
+            .pixel(24, 20)
+            .unwrap()
+            .r
+            .min(canvas.pixel(25, 20).unwrap().r);
+        assert!(edge < 0.75, "{edge}");
+        assert_eq!(canvas.clip_bounds(), None);
+        let mut missing = DisplayList::new();
+        missing.draw_image(
+            ImageId(3),
+            None,
+            Rect::new(0.0, 0.0, 1.0, 1.0),
+            None,
+            Interpolation::Linear,
+        );
+        assert!(matches!(
+            canvas.replay(&missing, &[]),
+            Err(Error::ImageNotFound(3))
+        ));
+    }
+
+    #[test]
+    fn replays_images_inside_clips() {
+        let image = Bitmap::from_rgba8((1, 1), &[Rgba8::new(0, 0, 255, 255)]).unwrap();
+        let mut list = DisplayList::new();
+        list.push_clip(Rect::new(0.0, 0.0, 4.0, 8.0));
+        list.draw_image(
+            ImageId(0),
+            None,
+            Rect::new(0.0, 0.0, 8.0, 8.0),
+            None,
+            Interpolation::NearestNeighbor,
+        );
+        list.pop_clip();
+        let mut canvas = Canvas::new((8, 8));
+        canvas.replay(&list, &[image]).unwrap();
+        assert_eq!(canvas.pixel(2, 2), Some(Rgba::new(0.0, 0.0, 1.0, 1.0)));
+        assert_eq!(alpha(&canvas, 6, 2), 0.0);
+    }
+
+    #[test]
+    fn reads_pixels_back_in_either_format() {
+        let mut canvas = Canvas::new((4, 2));
+        canvas.fill(
+            &Rect::new(2.0, 0.0, 4.0, 2.0),
+            &solid(Rgba::new(1.0, 0.0, 0.0, 0.5)),
+        );
+        let rgba = canvas.read_pixels(PixelFormat::Rgba8);
+        assert_eq!(rgba.len(), 4 * 2 * 4);
+        assert_eq!(&rgba[..4], [0, 0, 0, 0]);
+        assert_eq!(&rgba[8..12], [255, 0, 0, 128]);
+        assert_eq!(canvas.pixel_buffer().data(), rgba);
+        let region = canvas
+            .read_pixels_in(Rect::new(1, 1, 3, 2), PixelFormat::Bgra8Premultiplied)
+            .unwrap();
+        assert_eq!(region, [0, 0, 0, 0, 0, 0, 128, 128]);
+        assert!(matches!(
+            canvas.read_pixels_in(Rect::new(0, 0, 5, 1), PixelFormat::Rgba8),
+            Err(Error::OutOfBounds)
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::*;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bitmap {
+    size: Size<u32>,
+    pixels: Vec<PremulRgba>,
+}
+
+impl Bitmap {
+    pub fn from_rgba8(size: impl Into<Size<u32>>, pixels: &[Rgba8]) -> Result<Self> {
+        let size: Size<u32> = size.into();
+        let expected = size.width as usize * size.height as usize;
+        if pixels.len() != expected {
+            return Err(Error::PixelCount {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+        Ok(Self {
+            size,
+            pixels: pixels
+                .iter()
+                .map(|&pixel| Rgba::from(pixel).premultiply())
+                .collect(),
+        })
+    }
+
+    pub(crate) fn from_premultiplied(size: Size<u32>, pixels: Vec<PremulRgba>) -> Self {
+        Self { size, pixels }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+
+    #[inline]
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Rgba> {
+        if x >= self.size.width || y >= self.size.height {
+            return None;
+        }
+        Some(self.texel(x as i64, y as i64).unpremultiply())
+    }
+
+    pub fn to_rgba8(&self) -> Vec<Rgba8> {
+        self.pixels
+            .iter()
+            .map(|pixel| pixel.unpremultiply().into())
+            .collect()
+    }
+
+    fn texel(&self, x: i64, y: i64) -> PremulRgba {
+        let x = x.clamp(0, self.size.width as i64 - 1) as usize;
+        let y = y.clamp(0, self.size.height as i64 - 1) as usize;
+        self.pixels[y * self.size.width as usize + x]
+    }
+
+    // Samples at `(u, v)` in pixels, where one destination pixel spans `footprint` source
+    // pixels; the multi-sample modes average over the footprint when shrinking.
+    pub(crate) fn sample(
+        &self,
+        u: f32,
+        v: f32,
+        footprint: (f32, f32),
+        interpolation: Interpolation,
+    ) -> PremulRgba {
+        if self.pixels.is_empty() {
+            return PremulRgba::TRANSPARENT;
+        }
+        let filter: fn(&Self, f32, f32) -> PremulRgba = match interpolation {
+            Interpolation::NearestNeighbor => Self::nearest,
+            Interpolation::Linear => Self::bilinear,
+            Interpolation::Cubic => Self::bicubic,
+            Interpolation::MultiSampleLinear | Interpolation::Anisotropic => {
+                return self.multi_sample(u, v, footprint, Self::bilinear);
+            }
+            Interpolation::HighQualityCubic => {
+                return self.multi_sample(u, v, footprint, Self::bicubic);
+            }
+        };
+        filter(self, u, v)
+    }
+
+    fn nearest(&self, u: f32, v: f32) -> PremulRgba {
+        self.texel(u.floor() as i64, v.floor() as i64)
+    }
+
+    fn bilinear(&self, u: f32, v: f32) -> PremulRgba {
+        let (x, y) = (u - 0.5, v - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let mut acc = [0.0; 4];
+        for (dy, wy) in [(0, 1.0 - ty), (1, ty)] {
+            for (dx, wx) in [(0, 1.0 - tx), (1, tx)] {
+                add(&mut acc, self.texel(x0 + dx, y0 + dy), wx * wy);
+            }
+        }
+        PremulRgba::new(acc[0], acc[1], acc[2], acc[3])
+    }
+
+    fn bicubic(&self, u: f32, v: f32) -> PremulRgba {
+        let (x, y) = (u - 0.5, v - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let wx = catmull_rom(x - x0);
+        let wy = catmull_rom(y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let mut acc = [0.0; 4];
+        for (j, wy) in wy.iter().enumerate() {
+            for (i, wx) in wx.iter().enumerate() {
+                let texel = self.texel(x0 + i as i64 - 1, y0 + j as i64 - 1);
+                add(&mut acc, texel, wx * wy);
+            }
+        }
+        // The negative lobes can overshoot, so keep the result a valid premultiplied color.
+        let a = acc[3].clamp(0.0, 1.0);
+        PremulRgba::new(
+            acc[0].clamp(0.0, a),
+            acc[1].clamp(0.0, a),
+            acc[2].clamp(0.0, a),
+            a,
+        )
+    }
+
+    fn multi_sample(
+        &self,
+        u: f32,
+        v: f32,
+        footprint: (f32, f32),
+        filter: fn(&Self, f32, f32) -> PremulRgba,
+    ) -> PremulRgba {
+        let nx = footprint.0.abs().ceil().clamp(1.0, 8.0) as usize;
+        let ny = footprint.1.abs().ceil().clamp(1.0, 8.0) as usize;
+        if nx == 1 && ny == 1 {
+            return filter(self, u, v);
+        }
+        let mut acc = [0.0; 4];
+        let weight = 1.0 / (nx * ny) as f32;
+        for j in 0..ny {
+            let sv = v + footprint.1 * ((j as f32 + 0.5) / ny as f32 - 0.5);
+            for i in 0..nx {
+                let su = u + footprint.0 * ((i as f32 + 0.5) / nx as f32 - 0.5);
+                add(&mut acc, filter(self, su, sv), weight);
+            }
+        }
+        PremulRgba::new(acc[0], acc[1], acc[2], acc[3])
+    }
+}
+
+fn add(acc: &mut [f32; 4], c: PremulRgba, w: f32) {
+    acc[0] += c.r * w;
+    acc[1] += c.g * w;
+    acc[2] += c.b * w;
+    acc[3] += c.a * w;
+}
+
+fn catmull_rom(t: f32) -> [f32; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+    ]
+}
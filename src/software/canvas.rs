@@ -0,0 +1,409 @@
+use super::Bitmap;
+use super::outline::flatten;
+use super::raster::Rasterizer;
+use super::stroke::stroke;
+use crate::*;
+
+enum Paint {
+    Solid(PremulRgba),
+    Gradient(Gradient),
+    Pattern {
+        tile: Vec<PremulRgba>,
+        size: u32,
+        inverse: Matrix3x2,
+    },
+}
+
+impl Paint {
+    fn new(brush: &BrushDesc) -> Self {
+        let gradient = |shape, mode, stops: &GradientStops, options| {
+            Self::Gradient(Gradient {
+                shape,
+                mode,
+                stops: stops.clone(),
+                options,
+            })
+        };
+        match brush {
+            BrushDesc::Solid(color) => Self::Solid(color.premultiply()),
+            BrushDesc::Linear {
+                start,
+                end,
+                mode,
+                stops,
+                options,
+            } => gradient(
+                GradientShape::Linear {
+                    start: *start,
+                    end: *end,
+                },
+                *mode,
+                stops,
+                *options,
+            ),
+            BrushDesc::Radial {
+                ellipse,
+                offset,
+                mode,
+                stops,
+                options,
+            } => gradient(
+                GradientShape::Radial {
+                    ellipse: *ellipse,
+                    offset: *offset,
+                },
+                *mode,
+                stops,
+                *options,
+            ),
+            BrushDesc::Conic {
+                center,
+                start_angle,
+                end_angle,
+                mode,
+                stops,
+                options,
+                ..
+            } => gradient(
+                GradientShape::Conic {
+                    center: *center,
+                    start_angle: *start_angle,
+                    end_angle: *end_angle,
+                },
+                *mode,
+                stops,
+                *options,
+            ),
+            BrushDesc::Pattern(pattern) => Self::Pattern {
+                tile: pattern
+                    .render_tile(1.0)
+                    .into_iter()
+                    .map(Rgba::premultiply)
+                    .collect(),
+                size: pattern.tile_size(1.0).width,
+                inverse: pattern
+                    .tile_transform(1.0)
+                    .invert()
+                    .unwrap_or(Matrix3x2::IDENTITY),
+            },
+        }
+    }
+
+    fn color_at(&self, x: f32, y: f32) -> PremulRgba {
+        match self {
+            Self::Solid(color) => *color,
+            Self::Gradient(gradient) => gradient.color_at((x, y)).premultiply(),
+            Self::Pattern {
+                tile,
+                size,
+                inverse,
+            } => {
+                let p = inverse.transform_point((x, y));
+                let n = *size as f32;
+                let tx = p.x.rem_euclid(n).min(n - 1.0) as usize;
+                let ty = p.y.rem_euclid(n).min(n - 1.0) as usize;
+                tile[ty * *size as usize + tx]
+            }
+        }
+    }
+}
+
+fn scale(color: PremulRgba, s: f32) -> PremulRgba {
+    PremulRgba::new(color.r * s, color.g * s, color.b * s, color.a * s)
+}
+
+// How much of the pixel at `(x, y)` lies inside `rect`, or whether its center does when
+// aliased.
+fn rect_coverage(rect: &Rect<f32>, x: u32, y: u32, mode: AntialiasMode) -> f32 {
+    let (x, y) = (x as f32, y as f32);
+    match mode {
+        AntialiasMode::PerPrimitive => {
+            let cx = (rect.right.min(x + 1.0) - rect.left.max(x)).clamp(0.0, 1.0);
+            let cy = (rect.bottom.min(y + 1.0) - rect.top.max(y)).clamp(0.0, 1.0);
+            cx * cy
+        }
+        AntialiasMode::Aliased => {
+            let (cx, cy) = (x + 0.5, y + 0.5);
+            let inside = cx >= rect.left && cx < rect.right && cy >= rect.top && cy < rect.bottom;
+            if inside { 1.0 } else { 0.0 }
+        }
+    }
+}
+
+pub struct Canvas {
+    size: Size<u32>,
+    pixels: Vec<PremulRgba>,
+    clips: Vec<Rect<f32>>,
+    antialias_mode: AntialiasMode,
+}
+
+impl Canvas {
+    pub fn new(size: impl Into<Size<u32>>) -> Self {
+        let size: Size<u32> = size.into();
+        Self {
+            size,
+            pixels: vec![PremulRgba::TRANSPARENT; size.width as usize * size.height as usize],
+            clips: vec![],
+            antialias_mode: AntialiasMode::PerPrimitive,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+
+    #[inline]
+    pub fn antialias_mode(&self) -> AntialiasMode {
+        self.antialias_mode
+    }
+
+    #[inline]
+    pub fn set_antialias_mode(&mut self, mode: AntialiasMode) {
+        self.antialias_mode = mode;
+    }
+
+    pub fn clear(&mut self, color: impl Into<Rgba>) {
+        let color = color.into().premultiply();
+        let bounds = self.pixel_bounds(self.clip_bounds());
+        for y in bounds.top..bounds.bottom {
+            for x in bounds.left..bounds.right {
+                let coverage = self.clip_coverage(x, y);
+                let dest = &mut self.pixels[(y * self.size.width + x) as usize];
+                *dest = PremulRgba::new(
+                    color.r * coverage + dest.r * (1.0 - coverage),
+                    color.g * coverage + dest.g * (1.0 - coverage),
+                    color.b * coverage + dest.b * (1.0 - coverage),
+                    color.a * coverage + dest.a * (1.0 - coverage),
+                );
+            }
+        }
+    }
+
+    pub fn fill(&mut self, object: &impl Fill, brush: &BrushDesc) {
+        let mut sink = FigureSink::new();
+        object.fill(&mut sink);
+        let mut raster = Rasterizer::new(self.size);
+        for figure in sink.figures() {
+            raster.add_polygon(&flatten(figure).points);
+        }
+        self.paint(&raster, brush);
+    }
+
+    pub fn stroke(
+        &mut self,
+        object: &impl Stroke,
+        brush: &BrushDesc,
+        width: f32,
+        style: Option<&StrokeStyleProperties>,
+    ) {
+        let mut sink = FigureSink::new();
+        object.stroke(&mut sink);
+        let lines = sink.figures().iter().map(flatten).collect::<Vec<_>>();
+        let mut raster = Rasterizer::new(self.size);
+        for polygon in stroke(&lines, width, style) {
+            raster.add_polygon(&polygon);
+        }
+        self.paint(&raster, brush);
+    }
+
+    pub fn draw_image(
+        &mut self,
+        image: &Bitmap,
+        src_rect: Option<Rect<f32>>,
+        dest_rect: impl Into<Rect<f32>>,
+        opacity: Option<f32>,
+        interpolation: Interpolation,
+    ) {
+        let dest: Rect<f32> = dest_rect.into();
+        let image_size = image.size();
+        let src = src_rect.unwrap_or(Rect::new(
+            0.0,
+            0.0,
+            image_size.width as f32,
+            image_size.height as f32,
+        ));
+        let dest_width = dest.right - dest.left;
+        let dest_height = dest.bottom - dest.top;
+        if dest_width == 0.0 || dest_height == 0.0 {
+            return;
+        }
+        let sx = (src.right - src.left) / dest_width;
+        let sy = (src.bottom - src.top) / dest_height;
+        let opacity = opacity.unwrap_or(1.0);
+        let dest_bounds = Rect::new(
+            dest.left.min(dest.right),
+            dest.top.min(dest.bottom),
+            dest.left.max(dest.right),
+            dest.top.max(dest.bottom),
+        );
+        let bounds = self.pixel_bounds(Some(dest_bounds));
+        for y in bounds.top..bounds.bottom {
+            let v = src.top + (y as f32 + 0.5 - dest.top) * sy;
+            for x in bounds.left..bounds.right {
+                let coverage = rect_coverage(&dest_bounds, x, y, self.antialias_mode);
+                let u = src.left + (x as f32 + 0.5 - dest.left) * sx;
+                let color = image.sample(u, v, (sx, sy), interpolation);
+                self.blend(x, y, color, coverage * opacity);
+            }
+        }
+    }
+
+    pub fn push_clip(&mut self, rect: impl Into<Rect<f32>>) {
+        let rect: Rect<f32> = rect.into();
+        let rect = match self.clips.last() {
+            Some(clip) => Rect::new(
+                rect.left.max(clip.left),
+                rect.top.max(clip.top),
+                rect.right.min(clip.right),
+                rect.bottom.min(clip.bottom),
+            ),
+            None => rect,
+        };
+        self.clips.push(rect);
+    }
+
+    #[inline]
+    pub fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    // Images in the list are looked up by `ImageId`, and an id without an image fails the
+    // replay. Text is skipped, as the canvas has no way to shape it.
+    pub fn replay(&mut self, list: &DisplayList, images: &[Bitmap]) -> Result<()> {
+        for command in list {
+            match command {
+                DisplayCommand::Clear(color) => self.clear(*color),
+                DisplayCommand::Fill { shape, brush } => self.fill(shape, brush),
+                DisplayCommand::Stroke {
+                    shape,
+                    brush,
+                    width,
+                    style,
+                } => {
+                    let style = style.as_ref().map(|style| style.properties());
+                    self.stroke(shape, brush, *width, style.as_ref());
+                }
+                DisplayCommand::DrawText { .. } => {}
+                DisplayCommand::DrawImage {
+                    image,
+                    src_rect,
+                    dest_rect,
+                    opacity,
+                    interpolation,
+                } => {
+                    let image = image.get(images)?;
+                    self.draw_image(image, *src_rect, *dest_rect, *opacity, *interpolation);
+                }
+                DisplayCommand::PushClip(rect) => self.push_clip(*rect),
+                DisplayCommand::PopClip => self.pop_clip(),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn clip_bounds(&self) -> Option<Rect<f32>> {
+        self.clips.last().copied()
+    }
+
+    #[inline]
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Rgba> {
+        if x >= self.size.width || y >= self.size.height {
+            return None;
+        }
+        Some(self.pixels[(y * self.size.width + x) as usize].unpremultiply())
+    }
+
+    pub fn to_rgba8(&self) -> Vec<Rgba8> {
+        self.pixels
+            .iter()
+            .map(|pixel| pixel.unpremultiply().into())
+            .collect()
+    }
+
+    #[inline]
+    pub fn read_pixels(&self, format: PixelFormat) -> Vec<u8> {
+        let Size { width, height } = self.size;
+        self.read_pixels_in(Rect::new(0, 0, width, height), format)
+            .unwrap()
+    }
+
+    pub fn read_pixels_in(
+        &self,
+        rect: impl Into<Rect<u32>>,
+        format: PixelFormat,
+    ) -> Result<Vec<u8>> {
+        let rect: Rect<u32> = rect.into();
+        let size = region_size(rect, self.size)?;
+        let mut out = Vec::with_capacity(size.width as usize * size.height as usize * 4);
+        for y in rect.top..rect.bottom {
+            let row = (y * self.size.width) as usize;
+            for pixel in &self.pixels[row + rect.left as usize..row + rect.right as usize] {
+                let color = if format.is_premultiplied() {
+                    let PremulRgba { r, g, b, a } = *pixel;
+                    Rgba { r, g, b, a }
+                } else {
+                    pixel.unpremultiply()
+                };
+                out.extend(Rgba8::from(color).to_bytes(format.channel_order()));
+            }
+        }
+        Ok(out)
+    }
+
+    // Straight alpha is what encoders store, and converting from floats loses less.
+    #[inline]
+    pub fn pixel_buffer(&self) -> PixelBuffer {
+        let format = PixelFormat::Rgba8;
+        PixelBuffer::new(self.size, format, self.read_pixels(format)).unwrap()
+    }
+
+    pub fn to_bitmap(&self) -> Bitmap {
+        Bitmap::from_premultiplied(self.size, self.pixels.clone())
+    }
+
+    fn paint(&mut self, raster: &Rasterizer, brush: &BrushDesc) {
+        let paint = Paint::new(brush);
+        raster.for_each(self.antialias_mode, |x, y, coverage| {
+            let color = paint.color_at(x as f32 + 0.5, y as f32 + 0.5);
+            self.blend(x, y, color, coverage);
+        });
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: PremulRgba, coverage: f32) {
+        let coverage = coverage * self.clip_coverage(x, y);
+        if coverage <= 0.0 {
+            return;
+        }
+        let dest = &mut self.pixels[(y * self.size.width + x) as usize];
+        *dest = scale(color, coverage).composite(*dest, CompositeMode::SourceOver);
+    }
+
+    fn clip_coverage(&self, x: u32, y: u32) -> f32 {
+        match self.clips.last() {
+            Some(clip) => rect_coverage(clip, x, y, self.antialias_mode),
+            None => 1.0,
+        }
+    }
+
+    // The pixels touched by `rect`, limited to the canvas.
+    fn pixel_bounds(&self, rect: Option<Rect<f32>>) -> Rect<u32> {
+        let full = Rect::new(0, 0, self.size.width, self.size.height);
+        let Some(rect) = rect else {
+            return full;
+        };
+        let clamp = |v: f32, max: u32| {
+            if v.is_nan() {
+                0
+            } else {
+                v.clamp(0.0, max as f32) as u32
+            }
+        };
+        Rect::new(
+            clamp(rect.left.floor(), full.right),
+            clamp(rect.top.floor(), full.bottom),
+            clamp(rect.right.ceil(), full.right),
+            clamp(rect.bottom.ceil(), full.bottom),
+        )
+    }
+}
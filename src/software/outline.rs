@@ -0,0 +1,65 @@
+use crate::*;
+
+// Curves are flattened until they stay within this distance of the true curve, in pixels.
+pub(crate) const TOLERANCE: f32 = 0.1;
+
+pub(crate) struct Polyline {
+    pub points: Vec<Point<f32>>,
+    pub closed: bool,
+}
+
+pub(crate) fn flatten(figure: &PathFigure) -> Polyline {
+    let mut points = vec![figure.start];
+    for segment in &figure.segments {
+        let p0 = *points.last().unwrap();
+        match *segment {
+            PathSegment::Line(to) => push(&mut points, to),
+            PathSegment::QuadraticBezier(s) => {
+                let dd = second_difference(p0, s.ctrl, s.to);
+                for t in steps(0.25 * dd) {
+                    let u = 1.0 - t;
+                    let p = Point::new(
+                        u * u * p0.x + 2.0 * u * t * s.ctrl.x + t * t * s.to.x,
+                        u * u * p0.y + 2.0 * u * t * s.ctrl.y + t * t * s.to.y,
+                    );
+                    push(&mut points, p);
+                }
+            }
+            PathSegment::CubicBezier(s) => {
+                let dd = second_difference(p0, s.c0, s.c1).max(second_difference(s.c0, s.c1, s.to));
+                for t in steps(0.75 * dd) {
+                    let u = 1.0 - t;
+                    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                    let p = Point::new(
+                        a * p0.x + b * s.c0.x + c * s.c1.x + d * s.to.x,
+                        a * p0.y + b * s.c0.y + c * s.c1.y + d * s.to.y,
+                    );
+                    push(&mut points, p);
+                }
+            }
+        }
+    }
+    let closed = figure.end == PathEnd::Closed;
+    if closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    Polyline { points, closed }
+}
+
+fn push(points: &mut Vec<Point<f32>>, p: Point<f32>) {
+    if points.last() != Some(&p) {
+        points.push(p);
+    }
+}
+
+fn second_difference(a: Point<f32>, b: Point<f32>, c: Point<f32>) -> f32 {
+    (a.x - 2.0 * b.x + c.x).hypot(a.y - 2.0 * b.y + c.y)
+}
+
+// A curve whose second difference is `error` strays `error / n^2` from its chords when
+// split into `n` pieces; the last step lands exactly on the end point.
+fn steps(error: f32) -> impl Iterator<Item = f32> {
+    let n = (error / TOLERANCE).sqrt().ceil();
+    let n = if n >= 1.0 { n.min(256.0) as u32 } else { 1 };
+    (1..=n).map(move |i| if i == n { 1.0 } else { i as f32 / n as f32 })
+}
@@ -0,0 +1,147 @@
+use crate::*;
+
+// Accumulates the signed area each edge covers to the right of it, so that a running
+// sum along a row gives the exact coverage of every pixel under the nonzero rule for
+// paths that do not overlap themselves; overlaps saturate at full coverage.
+pub(crate) struct Rasterizer {
+    width: usize,
+    height: usize,
+    stride: usize,
+    area: Vec<f32>,
+    rows: Option<(usize, usize)>,
+}
+
+impl Rasterizer {
+    pub fn new(size: Size<u32>) -> Self {
+        let width = size.width as usize;
+        let height = size.height as usize;
+        // Edges clamped to the right border write up to two cells past the last column.
+        let stride = width + 2;
+        Self {
+            width,
+            height,
+            stride,
+            area: vec![0.0; stride * height],
+            rows: None,
+        }
+    }
+
+    pub fn add_polygon(&mut self, points: &[Point<f32>]) {
+        if points.len() < 2 {
+            return;
+        }
+        for (i, &p0) in points.iter().enumerate() {
+            let p1 = points[(i + 1) % points.len()];
+            self.add_line(p0, p1);
+        }
+    }
+
+    fn add_line(&mut self, p0: Point<f32>, p1: Point<f32>) {
+        if !(p0.x.is_finite() && p0.y.is_finite() && p1.x.is_finite() && p1.y.is_finite()) {
+            return;
+        }
+        // Split at the left and right borders; the parts outside are folded onto the
+        // border where they still cover everything to their right.
+        let right = self.width as f32;
+        let mut ts = [0.0, 1.0, 1.0, 1.0];
+        let mut n = 1;
+        for x in [0.0, right] {
+            let t = (x - p0.x) / (p1.x - p0.x);
+            if t > 0.0 && t < 1.0 {
+                ts[n] = t;
+                n += 1;
+            }
+        }
+        ts[..=n].sort_by(f32::total_cmp);
+        let at = |t: f32| {
+            Point::new(
+                (p0.x + (p1.x - p0.x) * t).clamp(0.0, right),
+                p0.y + (p1.y - p0.y) * t,
+            )
+        };
+        for pair in ts[..=n].windows(2) {
+            self.add_clamped_line(at(pair[0]), at(pair[1]));
+        }
+    }
+
+    fn add_clamped_line(&mut self, p0: Point<f32>, p1: Point<f32>) {
+        if p0.y == p1.y {
+            return;
+        }
+        let (dir, p0, p1) = if p0.y < p1.y {
+            (1.0, p0, p1)
+        } else {
+            (-1.0, p1, p0)
+        };
+        if p1.y <= 0.0 || p0.y >= self.height as f32 {
+            return;
+        }
+        let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+        let y0 = p0.y.max(0.0) as usize;
+        let y1 = (p1.y.ceil() as usize).min(self.height);
+        let mut x = (p0.x + (y0 as f32 - p0.y).max(0.0) * dxdy).clamp(0.0, self.width as f32);
+        self.rows = Some(match self.rows {
+            Some((top, bottom)) => (top.min(y0), bottom.max(y1)),
+            None => (y0, y1),
+        });
+        for y in y0..y1 {
+            let row = &mut self.area[y * self.stride..(y + 1) * self.stride];
+            let dy = ((y + 1) as f32).min(p1.y) - (y as f32).max(p0.y);
+            let x_next = (x + dxdy * dy).clamp(0.0, self.width as f32);
+            let d = dy * dir;
+            let (xa, xb) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let xa_floor = xa.floor();
+            let xa_i = xa_floor as usize;
+            let xb_ceil = xb.ceil();
+            let xb_i = xb_ceil as usize;
+            if xb_i <= xa_i + 1 {
+                // The edge stays within one pixel of this row.
+                let mid = 0.5 * (x + x_next) - xa_floor;
+                row[xa_i] += d - d * mid;
+                row[xa_i + 1] += d * mid;
+            } else {
+                let s = (xb - xa).recip();
+                let xa_f = xa - xa_floor;
+                let a0 = 0.5 * s * (1.0 - xa_f) * (1.0 - xa_f);
+                let xb_f = xb - xb_ceil + 1.0;
+                let am = 0.5 * s * xb_f * xb_f;
+                row[xa_i] += d * a0;
+                if xb_i == xa_i + 2 {
+                    row[xa_i + 1] += d * (1.0 - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - xa_f);
+                    row[xa_i + 1] += d * (a1 - a0);
+                    for cell in &mut row[xa_i + 2..xb_i - 1] {
+                        *cell += d * s;
+                    }
+                    let a2 = a1 + (xb_i - xa_i - 3) as f32 * s;
+                    row[xb_i - 1] += d * (1.0 - a2 - am);
+                }
+                row[xb_i] += d * am;
+            }
+            x = x_next;
+        }
+    }
+
+    pub fn for_each(&self, mode: AntialiasMode, mut f: impl FnMut(u32, u32, f32)) {
+        let Some((top, bottom)) = self.rows else {
+            return;
+        };
+        for y in top..bottom {
+            let row = &self.area[y * self.stride..y * self.stride + self.width];
+            let mut acc = 0.0;
+            for (x, cell) in row.iter().enumerate() {
+                acc += cell;
+                let coverage = acc.abs().min(1.0);
+                let coverage = match mode {
+                    AntialiasMode::PerPrimitive => coverage,
+                    AntialiasMode::Aliased if coverage >= 0.5 => 1.0,
+                    AntialiasMode::Aliased => 0.0,
+                };
+                if coverage > 1.0 / 1024.0 {
+                    f(x as u32, y as u32, coverage);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use super::outline::{Polyline, TOLERANCE};
+use crate::*;
+use std::f32::consts::PI;
+
+// Direct2D's miter limit when no stroke style is given.
+const DEFAULT_MITER_LIMIT: f32 = 10.0;
+
+#[derive(Clone, Copy)]
+struct Vec2 {
+    x: f32,
+    y: f32,
+}
+
+impl Vec2 {
+    fn between(a: Point<f32>, b: Point<f32>) -> Self {
+        Self {
+            x: b.x - a.x,
+            y: b.y - a.y,
+        }
+    }
+
+    fn length(self) -> f32 {
+        self.x.hypot(self.y)
+    }
+
+    fn normalize(self) -> Self {
+        let len = self.length();
+        Self {
+            x: self.x / len,
+            y: self.y / len,
+        }
+    }
+
+    fn perp(self) -> Self {
+        Self {
+            x: -self.y,
+            y: self.x,
+        }
+    }
+
+    fn scale(self, s: f32) -> Self {
+        Self {
+            x: self.x * s,
+            y: self.y * s,
+        }
+    }
+
+    fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    fn cross(self, other: Self) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+}
+
+fn offset(p: Point<f32>, v: Vec2) -> Point<f32> {
+    Point::new(p.x + v.x, p.y + v.y)
+}
+
+struct Piece {
+    points: Vec<Point<f32>>,
+    closed: bool,
+    start_cap: CapStyle,
+    end_cap: CapStyle,
+}
+
+// Outlines the stroke as a set of polygons that all wind the same way, so that the
+// rasterizer can fill them together and overlapping parts merge.
+pub(crate) fn stroke(
+    lines: &[Polyline],
+    width: f32,
+    style: Option<&StrokeStyleProperties>,
+) -> Vec<Vec<Point<f32>>> {
+    // Like `StrokeStyle`, a given style limits plain miters to half the stroke width.
+    let miter_limit = match style.map(|style| style.line_join) {
+        None => DEFAULT_MITER_LIMIT,
+        Some(LineJoin::MiterOrBevel(limit)) => limit,
+        Some(_) => 1.0,
+    };
+    let default_style = StrokeStyleProperties::default();
+    let style = style.unwrap_or(&default_style);
+    let half = width / 2.0;
+    let mut polygons = vec![];
+    for line in lines {
+        let pieces = match &style.dash {
+            Some(dash) if dash.style != DashStyle::Solid => dash_line(line, width, style, dash),
+            _ => vec![Piece {
+                points: line.points.clone(),
+                closed: line.closed,
+                start_cap: style.start_cap,
+                end_cap: style.end_cap,
+            }],
+        };
+        for piece in &pieces {
+            stroke_piece(piece, half, style.line_join, miter_limit, &mut polygons);
+        }
+    }
+    polygons
+}
+
+// Dash lengths and the offset are in multiples of the stroke width, as in Direct2D.
+fn dash_line(
+    line: &Polyline,
+    width: f32,
+    style: &StrokeStyleProperties,
+    dash: &Dash,
+) -> Vec<Piece> {
+    let pattern = dash
+        .style
+        .pattern()
+        .iter()
+        .map(|d| d.max(0.0) * width)
+        .collect::<Vec<_>>();
+    let period: f32 = pattern.iter().sum();
+    if pattern.len() < 2 || period <= 0.0 || !period.is_finite() {
+        return vec![Piece {
+            points: line.points.clone(),
+            closed: line.closed,
+            start_cap: style.start_cap,
+            end_cap: style.end_cap,
+        }];
+    }
+    let mut points = line.points.clone();
+    if line.closed {
+        points.extend(line.points.first().copied());
+    }
+    // Find where the offset lands in the pattern.
+    let mut index = 0;
+    let mut remaining = (dash.offset * width).rem_euclid(period);
+    while remaining > pattern[index] {
+        remaining -= pattern[index];
+        index = (index + 1) % pattern.len();
+    }
+    remaining = pattern[index] - remaining;
+    let mut pieces = vec![];
+    let mut current = (index % 2 == 0).then(|| vec![points[0]]);
+    let start_cap = |pieces: &[Piece]| {
+        if pieces.is_empty() && !line.closed {
+            style.start_cap
+        } else {
+            dash.cap
+        }
+    };
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let len = Vec2::between(a, b).length();
+        let mut pos = 0.0;
+        while len - pos > remaining {
+            pos += remaining;
+            let p = offset(a, Vec2::between(a, b).scale(pos / len));
+            match current.take() {
+                Some(mut dash_points) => {
+                    dash_points.push(p);
+                    pieces.push(Piece {
+                        points: dash_points,
+                        closed: false,
+                        start_cap: start_cap(&pieces),
+                        end_cap: dash.cap,
+                    });
+                }
+                None => current = Some(vec![p]),
+            }
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+        remaining -= len - pos;
+        if let Some(dash_points) = current.as_mut() {
+            dash_points.push(b);
+        }
+    }
+    if let Some(dash_points) = current {
+        pieces.push(Piece {
+            points: dash_points,
+            closed: false,
+            start_cap: start_cap(&pieces),
+            end_cap: if line.closed { dash.cap } else { style.end_cap },
+        });
+    }
+    pieces
+}
+
+fn stroke_piece(
+    piece: &Piece,
+    half: f32,
+    join: LineJoin,
+    miter_limit: f32,
+    polygons: &mut Vec<Vec<Point<f32>>>,
+) {
+    let mut points = piece.points.clone();
+    points.dedup();
+    if points.is_empty() || half <= 0.0 {
+        return;
+    }
+    if points.len() == 1 {
+        // A zero-length stroke only shows its caps, facing along the x axis.
+        if !piece.closed {
+            let dir = Vec2 { x: 1.0, y: 0.0 };
+            cap(points[0], dir.scale(-1.0), half, piece.start_cap, polygons);
+            cap(points[0], dir, half, piece.end_cap, polygons);
+        }
+        return;
+    }
+    let closed = piece.closed && points.len() > 2;
+    let count = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    let dirs = (0..count)
+        .map(|i| Vec2::between(points[i], points[(i + 1) % points.len()]).normalize())
+        .collect::<Vec<_>>();
+    for (i, dir) in dirs.iter().enumerate() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        let n = dir.perp().scale(half);
+        push_polygon(
+            polygons,
+            vec![
+                offset(a, n),
+                offset(b, n),
+                offset(b, n.scale(-1.0)),
+                offset(a, n.scale(-1.0)),
+            ],
+        );
+    }
+    for i in 0..dirs.len() {
+        if !closed && i + 1 == dirs.len() {
+            break;
+        }
+        let next = (i + 1) % dirs.len();
+        line_join(
+            points[next],
+            dirs[i],
+            dirs[next],
+            half,
+            join,
+            miter_limit,
+            polygons,
+        );
+    }
+    if !closed {
+        cap(
+            points[0],
+            dirs[0].scale(-1.0),
+            half,
+            piece.start_cap,
+            polygons,
+        );
+        let last = dirs[dirs.len() - 1];
+        cap(
+            points[points.len() - 1],
+            last,
+            half,
+            piece.end_cap,
+            polygons,
+        );
+    }
+}
+
+fn cap(p: Point<f32>, dir: Vec2, half: f32, style: CapStyle, polygons: &mut Vec<Vec<Point<f32>>>) {
+    let n = dir.perp().scale(half);
+    let d = dir.scale(half);
+    match style {
+        CapStyle::Flat => {}
+        CapStyle::Square => push_polygon(
+            polygons,
+            vec![
+                offset(p, n),
+                offset(offset(p, n), d),
+                offset(offset(p, n.scale(-1.0)), d),
+                offset(p, n.scale(-1.0)),
+            ],
+        ),
+        CapStyle::Round => push_polygon(polygons, circle(p, half)),
+        CapStyle::Triangle => push_polygon(
+            polygons,
+            vec![offset(p, n), offset(p, d), offset(p, n.scale(-1.0))],
+        ),
+    }
+}
+
+fn line_join(
+    p: Point<f32>,
+    d0: Vec2,
+    d1: Vec2,
+    half: f32,
+    join: LineJoin,
+    miter_limit: f32,
+    polygons: &mut Vec<Vec<Point<f32>>>,
+) {
+    let cross = d0.cross(d1);
+    if cross.abs() < 1e-6 && d0.dot(d1) > 0.0 {
+        return;
+    }
+    if join == LineJoin::Round {
+        push_polygon(polygons, circle(p, half));
+        return;
+    }
+    // The outer side of the turn is away from the direction it bends to.
+    let side = if cross > 0.0 { -half } else { half };
+    let n0 = d0.perp().scale(side);
+    let n1 = d1.perp().scale(side);
+    let o0 = offset(p, n0);
+    let o1 = offset(p, n1);
+    let bisector = Vec2 {
+        x: n0.x + n1.x,
+        y: n0.y + n1.y,
+    };
+    if join == LineJoin::Bevel || bisector.length() < 1e-6 {
+        push_polygon(polygons, vec![p, o0, o1]);
+        return;
+    }
+    let m = bisector.normalize();
+    let cos_half = m.dot(n0) / half;
+    let limit = miter_limit.max(1.0);
+    if 1.0 / cos_half <= limit {
+        push_polygon(
+            polygons,
+            vec![p, o0, offset(p, m.scale(half / cos_half)), o1],
+        );
+        return;
+    }
+    if matches!(join, LineJoin::MiterOrBevel(_)) {
+        push_polygon(polygons, vec![p, o0, o1]);
+        return;
+    }
+    // A plain miter past the limit is cut off square to the bisector.
+    let reach = limit * half;
+    let inset = m.dot(n0);
+    if reach <= inset {
+        push_polygon(polygons, vec![p, o0, o1]);
+        return;
+    }
+    let t0 = (reach - inset) / d0.dot(m);
+    let t1 = (reach - inset) / -d1.dot(m);
+    push_polygon(
+        polygons,
+        vec![
+            p,
+            o0,
+            offset(o0, d0.scale(t0)),
+            offset(o1, d1.scale(-t1)),
+            o1,
+        ],
+    );
+}
+
+fn circle(center: Point<f32>, radius: f32) -> Vec<Point<f32>> {
+    let step = 2.0 * (1.0 - TOLERANCE / radius).clamp(-1.0, 1.0).acos();
+    let n = if step > 0.0 {
+        ((2.0 * PI / step).ceil() as usize).clamp(8, 256)
+    } else {
+        8
+    };
+    (0..n)
+        .map(|i| {
+            let a = 2.0 * PI * i as f32 / n as f32;
+            Point::new(center.x + radius * a.cos(), center.y + radius * a.sin())
+        })
+        .collect()
+}
+
+fn push_polygon(polygons: &mut Vec<Vec<Point<f32>>>, mut points: Vec<Point<f32>>) {
+    let area: f32 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum();
+    if area < 0.0 {
+        points.reverse();
+    }
+    polygons.push(points);
+}
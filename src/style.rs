@@ -0,0 +1,92 @@
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CapStyle {
+    Flat,
+    Square,
+    Round,
+    Triangle,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineJoin {
+    Miter,
+    Bevel,
+    Round,
+    MiterOrBevel(f32),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DashStyle<'a> {
+    Solid,
+    Dash,
+    Dot,
+    DashDot,
+    DashDotDot,
+    // Borrowed lengths cannot be deserialized; `DashDesc` owns them instead.
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    Custom(&'a [f32]),
+}
+
+impl<'a> DashStyle<'a> {
+    // Alternating dash and gap lengths in multiples of the stroke width, as Direct2D
+    // draws them; empty for solid lines.
+    pub fn pattern(&self) -> &'a [f32] {
+        match self {
+            Self::Solid => &[],
+            Self::Dash => &[2.0, 2.0],
+            Self::Dot => &[0.0, 2.0],
+            Self::DashDot => &[2.0, 2.0, 0.0, 2.0],
+            Self::DashDotDot => &[2.0, 2.0, 0.0, 2.0, 0.0, 2.0],
+            Self::Custom(dashes) => dashes,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dash<'a> {
+    pub cap: CapStyle,
+    pub style: DashStyle<'a>,
+    pub offset: f32,
+}
+
+impl Default for Dash<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            cap: CapStyle::Flat,
+            style: DashStyle::Solid,
+            offset: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StrokeStyleProperties<'a> {
+    pub start_cap: CapStyle,
+    pub end_cap: CapStyle,
+    pub line_join: LineJoin,
+    pub dash: Option<Dash<'a>>,
+}
+
+impl Default for StrokeStyleProperties<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            start_cap: CapStyle::Flat,
+            end_cap: CapStyle::Flat,
+            line_join: LineJoin::Miter,
+            dash: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AntialiasMode {
+    PerPrimitive,
+    Aliased,
+}
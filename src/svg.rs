@@ -0,0 +1,982 @@
+use crate::codec::encode_png;
+use crate::*;
+use std::fmt::{self, Write};
+
+// Formats a coordinate the way SVG parsers accept it: no exponent, no negative zero and
+// nothing that is not a number.
+struct Num(f32);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 == 0.0 || !self.0.is_finite() {
+            f.write_str("0")
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn png_uri(size: Size<u32>, pixels: &[Rgba8]) -> String {
+    format!(
+        "data:image/png;base64,{}",
+        base64(&encode_png(size, pixels))
+    )
+}
+
+fn normalize(rect: Rect<f32>) -> Rect<f32> {
+    Rect::new(
+        rect.left.min(rect.right),
+        rect.top.min(rect.bottom),
+        rect.left.max(rect.right),
+        rect.top.max(rect.bottom),
+    )
+}
+
+fn color_attributes(name: &str, color: Rgba) -> String {
+    let hex = Rgba { a: 1.0, ..color }.to_hex();
+    if color.a < 1.0 {
+        format!(
+            r#" {name}="{hex}" {name}-opacity="{}""#,
+            Num(color.a.max(0.0))
+        )
+    } else {
+        format!(r#" {name}="{hex}""#)
+    }
+}
+
+fn line_cap(cap: CapStyle) -> &'static str {
+    match cap {
+        CapStyle::Flat => "",
+        CapStyle::Square => r#" stroke-linecap="square""#,
+        // SVG has no triangle caps; round ones reach as far past the end.
+        CapStyle::Round | CapStyle::Triangle => r#" stroke-linecap="round""#,
+    }
+}
+
+fn stroke_attributes(width: f32, style: Option<&StrokeStyleProperties>) -> String {
+    let mut out = format!(r#" stroke-width="{}""#, Num(width));
+    let Some(style) = style else {
+        // Direct2D's miter limit when no stroke style is given.
+        out.push_str(r#" stroke-miterlimit="10""#);
+        return out;
+    };
+    match style.line_join {
+        // `StrokeStyle` gives plain miters a limit of 1, past which they are clipped.
+        LineJoin::Miter => out.push_str(r#" stroke-linejoin="miter-clip" stroke-miterlimit="1""#),
+        LineJoin::Bevel => out.push_str(r#" stroke-linejoin="bevel""#),
+        LineJoin::Round => out.push_str(r#" stroke-linejoin="round""#),
+        LineJoin::MiterOrBevel(limit) => {
+            let _ = write!(out, r#" stroke-miterlimit="{}""#, Num(limit.max(1.0)));
+        }
+    }
+    let pattern = style
+        .dash
+        .as_ref()
+        .map_or(&[][..], |dash| dash.style.pattern());
+    let period: f32 = pattern.iter().map(|d| d.max(0.0)).sum();
+    match &style.dash {
+        Some(dash) if period > 0.0 && period.is_finite() => {
+            out.push_str(line_cap(dash.cap));
+            let dashes = pattern
+                .iter()
+                .map(|d| Num(d.max(0.0) * width).to_string())
+                .collect::<Vec<_>>();
+            let _ = write!(out, r#" stroke-dasharray="{}""#, dashes.join(" "));
+            if dash.offset != 0.0 {
+                let _ = write!(out, r#" stroke-dashoffset="{}""#, Num(dash.offset * width));
+            }
+        }
+        _ => out.push_str(line_cap(style.start_cap)),
+    }
+    out
+}
+
+// Writes every primitive as its own element with the same paint.
+struct Elements<'a> {
+    out: &'a mut String,
+    attributes: &'a str,
+}
+
+impl GeometrySink for Elements<'_> {
+    fn rect(&mut self, rect: &Rect<f32>) {
+        let r = normalize(*rect);
+        let _ = writeln!(
+            self.out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+            Num(r.left),
+            Num(r.top),
+            Num(r.right - r.left),
+            Num(r.bottom - r.top),
+            self.attributes
+        );
+    }
+
+    fn rounded_rect(&mut self, rounded_rect: &RoundedRect) {
+        let r = normalize(rounded_rect.rect);
+        let _ = writeln!(
+            self.out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" ry="{}"{}/>"#,
+            Num(r.left),
+            Num(r.top),
+            Num(r.right - r.left),
+            Num(r.bottom - r.top),
+            Num(rounded_rect.radius_x.max(0.0)),
+            Num(rounded_rect.radius_y.max(0.0)),
+            self.attributes
+        );
+    }
+
+    fn ellipse(&mut self, ellipse: &Ellipse) {
+        let _ = writeln!(
+            self.out,
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"{}/>"#,
+            Num(ellipse.center.x),
+            Num(ellipse.center.y),
+            Num(ellipse.radius_x.abs()),
+            Num(ellipse.radius_y.abs()),
+            self.attributes
+        );
+    }
+
+    fn line(&mut self, line: &Line) {
+        let _ = writeln!(
+            self.out,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+            Num(line.0.x),
+            Num(line.0.y),
+            Num(line.1.x),
+            Num(line.1.y),
+            self.attributes
+        );
+    }
+
+    fn path(&mut self, figure: &PathFigure) {
+        let mut d = format!("M{} {}", Num(figure.start.x), Num(figure.start.y));
+        for segment in &figure.segments {
+            let _ = match segment {
+                PathSegment::Line(p) => write!(d, " L{} {}", Num(p.x), Num(p.y)),
+                PathSegment::QuadraticBezier(s) => write!(
+                    d,
+                    " Q{} {} {} {}",
+                    Num(s.ctrl.x),
+                    Num(s.ctrl.y),
+                    Num(s.to.x),
+                    Num(s.to.y)
+                ),
+                PathSegment::CubicBezier(s) => write!(
+                    d,
+                    " C{} {} {} {} {} {}",
+                    Num(s.c0.x),
+                    Num(s.c0.y),
+                    Num(s.c1.x),
+                    Num(s.c1.y),
+                    Num(s.to.x),
+                    Num(s.to.y)
+                ),
+            };
+        }
+        if figure.end == PathEnd::Closed {
+            d.push_str(" Z");
+        }
+        let _ = writeln!(self.out, r#"<path d="{d}"{}/>"#, self.attributes);
+    }
+}
+
+// Pixels embedded in the document as a PNG data URI.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SvgImage {
+    size: Size<u32>,
+    uri: String,
+}
+
+impl SvgImage {
+    pub fn from_rgba8(size: impl Into<Size<u32>>, pixels: &[Rgba8]) -> Result<Self> {
+        let size: Size<u32> = size.into();
+        let expected = size.width as usize * size.height as usize;
+        if pixels.len() != expected {
+            return Err(Error::PixelCount {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+        Ok(Self {
+            size,
+            uri: png_uri(size, pixels),
+        })
+    }
+
+    #[inline]
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+}
+
+// Builds a standalone SVG document from drawing operations, in the order they are made.
+pub struct SvgWriter {
+    size: Size<f32>,
+    defs: String,
+    body: String,
+    next_id: usize,
+    clip_depth: usize,
+}
+
+impl SvgWriter {
+    pub fn new(size: impl Into<Size<f32>>) -> Self {
+        Self {
+            size: size.into(),
+            defs: String::new(),
+            body: String::new(),
+            next_id: 0,
+            clip_depth: 0,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    // SVG cannot replace what is underneath, so outside of clips everything drawn so far is
+    // dropped; inside a clip the color is painted over it.
+    pub fn clear(&mut self, color: impl Into<Rgba>) {
+        let color = color.into();
+        if self.clip_depth == 0 {
+            self.body.clear();
+        }
+        if color.a > 0.0 {
+            let attributes = color_attributes("fill", color);
+            Rect::new(0.0, 0.0, self.size.width, self.size.height).fill(&mut Elements {
+                out: &mut self.body,
+                attributes: &attributes,
+            });
+        }
+    }
+
+    pub fn fill(&mut self, object: &impl Fill, brush: &BrushDesc) {
+        let attributes = self.paint("fill", brush);
+        object.fill(&mut Elements {
+            out: &mut self.body,
+            attributes: &attributes,
+        });
+    }
+
+    pub fn stroke(
+        &mut self,
+        object: &impl Stroke,
+        brush: &BrushDesc,
+        width: f32,
+        style: Option<&StrokeStyleProperties>,
+    ) {
+        let attributes = format!(
+            r#" fill="none"{}{}"#,
+            self.paint("stroke", brush),
+            stroke_attributes(width, style)
+        );
+        object.stroke(&mut Elements {
+            out: &mut self.body,
+            attributes: &attributes,
+        });
+    }
+
+    // `position` is the top left of the text, as with `DrawCommand::draw_text`.
+    pub fn draw_text(
+        &mut self,
+        text: &str,
+        position: impl Into<Point<f32>>,
+        font: &FontDesc,
+        brush: &BrushDesc,
+    ) {
+        let position: Point<f32> = position.into();
+        let x = Num(position.x);
+        let _ = write!(
+            self.body,
+            r#"<text x="{x}" y="{}" font-family="'{}'" font-size="{}" font-weight="{}""#,
+            Num(position.y),
+            escape(&font.family.replace('\'', "\\'")),
+            Num(font.size),
+            Num(font.weight)
+        );
+        if font.italic {
+            self.body.push_str(r#" font-style="italic""#);
+        }
+        let paint = self.paint("fill", brush);
+        let _ = write!(
+            self.body,
+            r#"{paint} dominant-baseline="text-before-edge" xml:space="preserve">"#
+        );
+        let mut lines = text.split('\n');
+        let first = lines.next().unwrap_or_default();
+        let rest = lines.collect::<Vec<_>>();
+        if rest.is_empty() {
+            self.body.push_str(&escape(first));
+        } else {
+            let _ = write!(self.body, r#"<tspan x="{x}">{}</tspan>"#, escape(first));
+            for line in rest {
+                let _ = write!(
+                    self.body,
+                    r#"<tspan x="{x}" dy="1.2em">{}</tspan>"#,
+                    escape(line)
+                );
+            }
+        }
+        self.body.push_str("</text>\n");
+    }
+
+    pub fn draw_image(
+        &mut self,
+        image: &SvgImage,
+        src_rect: Option<Rect<f32>>,
+        dest_rect: impl Into<Rect<f32>>,
+        opacity: Option<f32>,
+        interpolation: Interpolation,
+    ) {
+        let dest: Rect<f32> = dest_rect.into();
+        let (width, height) = (image.size.width as f32, image.size.height as f32);
+        let src = src_rect.unwrap_or(Rect::new(0.0, 0.0, width, height));
+        let (src_width, src_height) = (src.right - src.left, src.bottom - src.top);
+        if src_width == 0.0 || src_height == 0.0 {
+            return;
+        }
+        // Map the source rect onto the destination, flipping it if either is reversed.
+        let sx = (dest.right - dest.left) / src_width;
+        let sy = (dest.bottom - dest.top) / src_height;
+        let _ = write!(
+            self.body,
+            r#"<g transform="matrix({} 0 0 {} {} {})""#,
+            Num(sx),
+            Num(sy),
+            Num(dest.left - src.left * sx),
+            Num(dest.top - src.top * sy)
+        );
+        if let Some(opacity) = opacity {
+            let _ = write!(self.body, r#" opacity="{}""#, Num(opacity.clamp(0.0, 1.0)));
+        }
+        self.body.push_str(">\n");
+        let cropped = src_rect.is_some();
+        if cropped {
+            let src = normalize(src);
+            let _ = writeln!(
+                self.body,
+                r#"<svg x="{0}" y="{1}" width="{2}" height="{3}" viewBox="{0} {1} {2} {3}" overflow="hidden">"#,
+                Num(src.left),
+                Num(src.top),
+                Num(src.right - src.left),
+                Num(src.bottom - src.top)
+            );
+        }
+        let rendering = match interpolation {
+            Interpolation::NearestNeighbor => r#" image-rendering="pixelated""#,
+            _ => "",
+        };
+        let _ = writeln!(
+            self.body,
+            r#"<image width="{}" height="{}" preserveAspectRatio="none"{rendering} xlink:href="{}"/>"#,
+            Num(width),
+            Num(height),
+            image.uri
+        );
+        if cropped {
+            self.body.push_str("</svg>\n");
+        }
+        self.body.push_str("</g>\n");
+    }
+
+    pub fn push_clip(&mut self, rect: impl Into<Rect<f32>>) {
+        let id = self.id("clip");
+        let _ = writeln!(self.defs, r#"<clipPath id="{id}">"#);
+        rect.into().fill(&mut Elements {
+            out: &mut self.defs,
+            attributes: "",
+        });
+        self.defs.push_str("</clipPath>\n");
+        let _ = writeln!(self.body, r#"<g clip-path="url(#{id})">"#);
+        self.clip_depth += 1;
+    }
+
+    #[inline]
+    pub fn pop_clip(&mut self) {
+        if self.clip_depth > 0 {
+            self.body.push_str("</g>\n");
+            self.clip_depth -= 1;
+        }
+    }
+
+    // Images in the list are looked up by `ImageId`, and an id without an image fails the
+    // replay.
+    pub fn replay(&mut self, list: &DisplayList, images: &[SvgImage]) -> Result<()> {
+        for command in list {
+            match command {
+                DisplayCommand::Clear(color) => self.clear(*color),
+                DisplayCommand::Fill { shape, brush } => self.fill(shape, brush),
+                DisplayCommand::Stroke {
+                    shape,
+                    brush,
+                    width,
+                    style,
+                } => {
+                    let style = style.as_ref().map(|style| style.properties());
+                    self.stroke(shape, brush, *width, style.as_ref());
+                }
+                DisplayCommand::DrawText {
+                    text,
+                    position,
+                    font,
+                    brush,
+                } => self.draw_text(text, *position, font, brush),
+                DisplayCommand::DrawImage {
+                    image,
+                    src_rect,
+                    dest_rect,
+                    opacity,
+                    interpolation,
+                } => {
+                    let image = image.get(images)?;
+                    self.draw_image(image, *src_rect, *dest_rect, *opacity, *interpolation);
+                }
+                DisplayCommand::PushClip(rect) => self.push_clip(*rect),
+                DisplayCommand::PopClip => self.pop_clip(),
+            }
+        }
+        Ok(())
+    }
+
+    // Clips that are still pushed are closed.
+    pub fn finish(mut self) -> String {
+        while self.clip_depth > 0 {
+            self.pop_clip();
+        }
+        let mut out = String::with_capacity(self.defs.len() + self.body.len() + 256);
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            Num(self.size.width),
+            Num(self.size.height)
+        );
+        if !self.defs.is_empty() {
+            out.push_str("<defs>\n");
+            out.push_str(&self.defs);
+            out.push_str("</defs>\n");
+        }
+        out.push_str(&self.body);
+        out.push_str("</svg>\n");
+        out
+    }
+
+    fn id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}{}", self.next_id)
+    }
+
+    // Returns the attributes that paint with `brush`, defining gradients and patterns
+    // as needed.
+    fn paint(&mut self, name: &str, brush: &BrushDesc) -> String {
+        let last_stop = |stops: &GradientStops| {
+            stops
+                .as_slice()
+                .last()
+                .map_or(Rgba::TRANSPARENT, |stop| stop.color)
+        };
+        let id = match brush {
+            BrushDesc::Solid(color) => return color_attributes(name, *color),
+            BrushDesc::Linear {
+                start,
+                end,
+                mode,
+                stops,
+                options,
+            } => {
+                if start == end {
+                    return color_attributes(name, last_stop(stops));
+                }
+                let id = self.id("paint");
+                let _ = write!(
+                    self.defs,
+                    r#"<linearGradient id="{id}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}""#,
+                    Num(start.x),
+                    Num(start.y),
+                    Num(end.x),
+                    Num(end.y)
+                );
+                self.gradient_stops(*mode, stops, options);
+                self.defs.push_str("</linearGradient>\n");
+                id
+            }
+            BrushDesc::Radial {
+                ellipse,
+                offset,
+                mode,
+                stops,
+                options,
+            } => {
+                if ellipse.radius_x <= 0.0 || ellipse.radius_y <= 0.0 {
+                    return color_attributes(name, last_stop(stops));
+                }
+                // A unit circle stretched onto the ellipse.
+                let id = self.id("paint");
+                let _ = write!(
+                    self.defs,
+                    r#"<radialGradient id="{id}" gradientUnits="userSpaceOnUse" cx="0" cy="0" r="1" fx="{}" fy="{}" gradientTransform="matrix({} 0 0 {} {} {})""#,
+                    Num(offset.x / ellipse.radius_x),
+                    Num(offset.y / ellipse.radius_y),
+                    Num(ellipse.radius_x),
+                    Num(ellipse.radius_y),
+                    Num(ellipse.center.x),
+                    Num(ellipse.center.y)
+                );
+                self.gradient_stops(*mode, stops, options);
+                self.defs.push_str("</radialGradient>\n");
+                id
+            }
+            // SVG has no conic gradients, so the bounds are filled with an image of one.
+            BrushDesc::Conic {
+                bounds,
+                center,
+                start_angle,
+                end_angle,
+                mode,
+                stops,
+                options,
+            } => {
+                let bounds = normalize(*bounds);
+                let pixels = |len: f32| (len.ceil() as u32).clamp(1, 4096);
+                let size = Size::new(
+                    pixels(bounds.right - bounds.left),
+                    pixels(bounds.bottom - bounds.top),
+                );
+                let gradient = Gradient {
+                    shape: GradientShape::Conic {
+                        center: *center,
+                        start_angle: *start_angle,
+                        end_angle: *end_angle,
+                    },
+                    mode: *mode,
+                    stops: stops.clone(),
+                    options: *options,
+                };
+                let pixels = gradient
+                    .rasterize(bounds, size)
+                    .into_iter()
+                    .map(Rgba8::from)
+                    .collect::<Vec<_>>();
+                self.image_pattern(&png_uri(size, &pixels), bounds, None)
+            }
+            BrushDesc::Pattern(pattern) => {
+                let size = pattern.tile_size(1.0);
+                let pixels = pattern
+                    .render_tile(1.0)
+                    .into_iter()
+                    .map(Rgba8::from)
+                    .collect::<Vec<_>>();
+                let tile = Rect::new(0.0, 0.0, size.width as f32, size.height as f32);
+                let transform = pattern.tile_transform(1.0);
+                self.image_pattern(&png_uri(size, &pixels), tile, Some(transform))
+            }
+        };
+        format!(r#" {name}="url(#{id})""#)
+    }
+
+    fn gradient_stops(
+        &mut self,
+        mode: GradientMode,
+        stops: &GradientStops,
+        options: &GradientOptions,
+    ) {
+        match mode {
+            GradientMode::Clamp => {}
+            GradientMode::Mirror => self.defs.push_str(r#" spreadMethod="reflect""#),
+            GradientMode::Wrap => self.defs.push_str(r#" spreadMethod="repeat""#),
+        }
+        if options.color_space == GradientColorSpace::Linear {
+            self.defs.push_str(r#" color-interpolation="linearRGB""#);
+        }
+        self.defs.push_str(">\n");
+        for stop in stops.expand(options) {
+            let color = stop.color;
+            let _ = write!(
+                self.defs,
+                r#"<stop offset="{}" stop-color="{}""#,
+                Num(stop.position.clamp(0.0, 1.0)),
+                Rgba { a: 1.0, ..color }.to_hex()
+            );
+            if color.a < 1.0 {
+                let _ = write!(self.defs, r#" stop-opacity="{}""#, Num(color.a.max(0.0)));
+            }
+            self.defs.push_str("/>\n");
+        }
+    }
+
+    fn image_pattern(
+        &mut self,
+        uri: &str,
+        tile: Rect<f32>,
+        transform: Option<Matrix3x2>,
+    ) -> String {
+        let id = self.id("paint");
+        let (width, height) = (Num(tile.right - tile.left), Num(tile.bottom - tile.top));
+        let _ = write!(
+            self.defs,
+            r#"<pattern id="{id}" patternUnits="userSpaceOnUse" x="{}" y="{}" width="{width}" height="{height}""#,
+            Num(tile.left),
+            Num(tile.top)
+        );
+        if let Some(m) = transform {
+            let _ = write!(
+                self.defs,
+                r#" patternTransform="matrix({} {} {} {} {} {})""#,
+                Num(m.m11),
+                Num(m.m12),
+                Num(m.m21),
+                Num(m.m22),
+                Num(m.m31),
+                Num(m.m32)
+            );
+        }
+        let _ = writeln!(
+            self.defs,
+            r#"><image width="{width}" height="{height}" preserveAspectRatio="none" xlink:href="{uri}"/></pattern>"#
+        );
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(f: impl FnOnce(&mut SvgWriter)) -> String {
+        let mut writer = SvgWriter::new((100.0, 80.0));
+        f(&mut writer);
+        writer.finish()
+    }
+
+    fn elements<'a>(doc: &'a roxmltree::Document, name: &str) -> Vec<roxmltree::Node<'a, 'a>> {
+        doc.descendants()
+            .filter(|node| node.tag_name().name() == name)
+            .collect()
+    }
+
+    fn reference(value: Option<&str>) -> &str {
+        value
+            .and_then(|v| v.strip_prefix("url(#"))
+            .and_then(|v| v.strip_suffix(')'))
+            .unwrap()
+    }
+
+    #[test]
+    fn writes_shapes_as_elements() {
+        let svg = render(|w| {
+            w.fill(
+                &Rect::new(30.0, 40.0, 10.0, 20.0),
+                &BrushDesc::solid(Rgba::RED),
+            );
+            w.fill(
+                &RoundedRect::new(Rect::new(0.0, 0.0, 20.0, 10.0), 2.0, 3.0),
+                &BrushDesc::solid(Rgba::new(0.0, 0.0, 1.0, 0.5)),
+            );
+            w.fill(
+                &Circle::new((50.0, 40.0), 5.0),
+                &BrushDesc::solid(Rgba::BLACK),
+            );
+            w.stroke(
+                &Line::new((0.0, 0.0), (10.0, 10.0)),
+                &BrushDesc::solid(Rgba::BLACK),
+                2.0,
+                None,
+            );
+            w.fill(
+                &PathFigure::new((0.0, 0.0))
+                    .line_to((10.0, 0.0))
+                    .cubic_bezier_to((10.0, 5.0), (5.0, 10.0), (0.0, 10.0))
+                    .close(),
+                &BrushDesc::solid(Rgba::BLACK),
+            );
+        });
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let root = doc.root_element();
+        assert_eq!(root.tag_name().name(), "svg");
+        assert_eq!(root.attribute("viewBox"), Some("0 0 100 80"));
+        let names = root
+            .children()
+            .filter(|node| node.is_element())
+            .map(|node| node.tag_name().name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["rect", "rect", "ellipse", "line", "path"]);
+        let rects = elements(&doc, "rect");
+        assert_eq!(rects[0].attribute("x"), Some("10"));
+        assert_eq!(rects[0].attribute("width"), Some("20"));
+        assert_eq!(rects[0].attribute("fill"), Some("#ff0000"));
+        assert_eq!(rects[1].attribute("ry"), Some("3"));
+        assert_eq!(rects[1].attribute("fill-opacity"), Some("0.5"));
+        let line = &elements(&doc, "line")[0];
+        assert_eq!(line.attribute("fill"), Some("none"));
+        assert_eq!(line.attribute("stroke-width"), Some("2"));
+        assert_eq!(
+            elements(&doc, "path")[0].attribute("d"),
+            Some("M0 0 L10 0 C10 5 5 10 0 10 Z")
+        );
+    }
+
+    #[test]
+    fn maps_stroke_style() {
+        let dashes = [3.0, 1.0];
+        let style = StrokeStyleProperties {
+            start_cap: CapStyle::Square,
+            line_join: LineJoin::Round,
+            dash: Some(Dash {
+                cap: CapStyle::Round,
+                style: DashStyle::Custom(&dashes),
+                offset: 1.0,
+            }),
+            ..Default::default()
+        };
+        let svg = render(|w| {
+            w.stroke(
+                &Rect::new(10.0, 10.0, 50.0, 50.0),
+                &BrushDesc::solid(Rgba::BLACK),
+                2.0,
+                Some(&style),
+            );
+        });
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let rect = &elements(&doc, "rect")[0];
+        assert_eq!(rect.attribute("stroke-linejoin"), Some("round"));
+        assert_eq!(rect.attribute("stroke-linecap"), Some("round"));
+        assert_eq!(rect.attribute("stroke-dasharray"), Some("6 2"));
+        assert_eq!(rect.attribute("stroke-dashoffset"), Some("2"));
+    }
+
+    #[test]
+    fn defines_gradients_in_defs() {
+        let svg = render(|w| {
+            w.fill(
+                &Rect::new(0.0, 0.0, 100.0, 80.0),
+                &BrushDesc::linear(
+                    (0.0, 0.0),
+                    (100.0, 0.0),
+                    GradientMode::Mirror,
+                    &[(0.0, Rgba::RED), (1.0, Rgba::BLUE)],
+                ),
+            );
+            w.fill(
+                &Circle::new((50.0, 40.0), 20.0),
+                &BrushDesc::radial(
+                    Ellipse::new((50.0, 40.0), 20.0, 10.0),
+                    (0.0, 0.0),
+                    GradientMode::Clamp,
+                    &[(0.0, Rgba::WHITE), (1.0, Rgba::BLACK)],
+                ),
+            );
+        });
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let linear = &elements(&doc, "linearGradient")[0];
+        assert_eq!(linear.parent_element().unwrap().tag_name().name(), "defs");
+        assert_eq!(linear.attribute("spreadMethod"), Some("reflect"));
+        assert_eq!(linear.children().filter(|n| n.is_element()).count(), 2);
+        let rect = &elements(&doc, "rect")[0];
+        assert_eq!(
+            reference(rect.attribute("fill")),
+            linear.attribute("id").unwrap()
+        );
+        let radial = &elements(&doc, "radialGradient")[0];
+        assert_eq!(
+            radial.attribute("gradientTransform"),
+            Some("matrix(20 0 0 10 50 40)")
+        );
+        let ellipse = &elements(&doc, "ellipse")[0];
+        assert_eq!(
+            reference(ellipse.attribute("fill")),
+            radial.attribute("id").unwrap()
+        );
+    }
+
+    #[test]
+    fn nests_clips_in_groups() {
+        let svg = render(|w| {
+            w.push_clip(Rect::new(0.0, 0.0, 50.0, 50.0));
+            w.push_clip(Rect::new(10.0, 10.0, 40.0, 40.0));
+            w.fill(
+                &Rect::new(0.0, 0.0, 100.0, 80.0),
+                &BrushDesc::solid(Rgba::RED),
+            );
+            w.pop_clip();
+            // Left pushed on purpose; `finish` closes it.
+        });
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let clips = elements(&doc, "clipPath");
+        assert_eq!(clips.len(), 2);
+        let groups = elements(&doc, "g");
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1].parent_element(), Some(groups[0]));
+        for (group, clip) in groups.iter().zip(&clips) {
+            assert_eq!(
+                reference(group.attribute("clip-path")),
+                clip.attribute("id").unwrap()
+            );
+        }
+        let rect = groups[1].first_element_child().unwrap();
+        assert_eq!(rect.attribute("fill"), Some("#ff0000"));
+    }
+
+    #[test]
+    fn writes_text_with_font() {
+        let font = FontDesc::new("Segoe UI", 16.0).weight(700.0).italic(true);
+        let svg = render(|w| {
+            w.draw_text(
+                "a < b\nc",
+                (5.0, 6.0),
+                &font,
+                &BrushDesc::solid(Rgba::BLACK),
+            );
+        });
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let text = &elements(&doc, "text")[0];
+        assert_eq!(text.attribute("font-family"), Some("'Segoe UI'"));
+        assert_eq!(text.attribute("font-size"), Some("16"));
+        assert_eq!(text.attribute("font-weight"), Some("700"));
+        assert_eq!(text.attribute("font-style"), Some("italic"));
+        let lines = text
+            .children()
+            .map(|span| span.text().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, ["a < b", "c"]);
+    }
+
+    #[test]
+    fn replayed_texts_keep_their_own_fonts() {
+        let mut list = DisplayList::new();
+        let title = FontDesc::new("Georgia", 24.0).weight(700.0).italic(true);
+        list.draw_text("title", (0.0, 0.0), &title, Rgba::BLACK);
+        list.draw_text(
+            "body",
+            (0.0, 30.0),
+            &FontDesc::new("Segoe UI", 12.0),
+            Rgba::BLACK,
+        );
+        let mut writer = SvgWriter::new((100.0, 100.0));
+        writer.replay(&list, &[]).unwrap();
+        let svg = writer.finish();
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let fonts = elements(&doc, "text")
+            .iter()
+            .map(|text| {
+                ["font-family", "font-size", "font-weight", "font-style"]
+                    .map(|name| text.attribute(name))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fonts,
+            [
+                [Some("'Georgia'"), Some("24"), Some("700"), Some("italic")],
+                [Some("'Segoe UI'"), Some("12"), Some("400"), None],
+            ]
+        );
+    }
+
+    #[test]
+    fn embeds_images_as_png() {
+        let pixels = [Rgba8::new(255, 0, 0, 255); 6];
+        let image = SvgImage::from_rgba8((3, 2), &pixels).unwrap();
+        assert!(SvgImage::from_rgba8((2, 2), &pixels).is_err());
+        let svg = render(|w| {
+            w.draw_image(
+                &image,
+                Some(Rect::new(1.0, 0.0, 3.0, 2.0)),
+                Rect::new(10.0, 10.0, 30.0, 30.0),
+                Some(0.5),
+                Interpolation::NearestNeighbor,
+            );
+        });
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let group = &elements(&doc, "g")[0];
+        assert_eq!(group.attribute("transform"), Some("matrix(10 0 0 10 0 10)"));
+        assert_eq!(group.attribute("opacity"), Some("0.5"));
+        let viewport = &elements(&doc, "svg")[1];
+        assert_eq!(viewport.attribute("viewBox"), Some("1 0 2 2"));
+        let image = &elements(&doc, "image")[0];
+        assert_eq!(image.attribute("width"), Some("3"));
+        assert_eq!(image.attribute("image-rendering"), Some("pixelated"));
+        let href = image
+            .attribute(("http://www.w3.org/1999/xlink", "href"))
+            .unwrap();
+        // "iVBORw0KGgo" is the PNG signature in base64.
+        assert!(href.starts_with("data:image/png;base64,iVBORw0KGgo"));
+    }
+
+    #[test]
+    fn replays_display_lists() {
+        let mut list = DisplayList::new();
+        list.fill(Rect::new(0.0, 0.0, 10.0, 10.0), Rgba::RED);
+        list.clear(Rgba::WHITE);
+        list.push_clip(Rect::new(0.0, 0.0, 50.0, 50.0));
+        list.stroke(Circle::new((20.0, 20.0), 5.0), Rgba::BLACK, 1.0, None);
+        list.draw_text("hi", (0.0, 0.0), &FontDesc::new("Arial", 12.0), Rgba::BLACK);
+        list.pop_clip();
+        let mut writer = SvgWriter::new((100.0, 100.0));
+        writer.replay(&list, &[]).unwrap();
+        let mut missing = DisplayList::new();
+        missing.draw_image(
+            ImageId(3),
+            None,
+            Rect::new(0.0, 0.0, 1.0, 1.0),
+            None,
+            Interpolation::Linear,
+        );
+        assert!(matches!(
+            writer.replay(&missing, &[]),
+            Err(Error::ImageNotFound(3))
+        ));
+        let svg = writer.finish();
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let names = doc
+            .root_element()
+            .children()
+            .filter(|node| node.is_element())
+            .map(|node| node.tag_name().name())
+            .collect::<Vec<_>>();
+        // The red rect is gone with the clear.
+        assert_eq!(names, ["defs", "rect", "g"]);
+        let group = &elements(&doc, "g")[0];
+        let inner = group
+            .children()
+            .filter(|node| node.is_element())
+            .map(|node| node.tag_name().name())
+            .collect::<Vec<_>>();
+        assert_eq!(inner, ["ellipse", "text"]);
+    }
+}
@@ -231,13 +231,6 @@ impl From<DWRITE_LINE_SPACING> for LineSpacing {
     }
 }
 
-#[derive(Clone, Debug)]
-pub enum Font<'a, 'b> {
-    System(&'b str),
-    File(&'a std::path::Path, &'b str),
-    Memory(&'a [u8], &'b str),
-}
-
 impl Font<'_, '_> {
     fn font_name(&self) -> &str {
         match self {